@@ -0,0 +1,228 @@
+use ndarray::{s, Array1, Array2, ArrayView1, ArrayView2, Axis};
+use std::collections::HashMap;
+
+use crate::error::{BayesError, Result};
+use linfa::dataset::Label;
+use linfa::Float;
+
+/// Common interface of the fitted Naive Bayes models
+///
+/// The class probabilities are derived from the unnormalized joint log
+/// likelihood of each model. The columns of the returned matrices are ordered
+/// by the classes in ascending order.
+pub trait NaiveBayes<A: Float, L: Label + Ord> {
+    /// Compute the unnormalized posterior log probability of each class
+    fn joint_log_likelihood(&self, x: ArrayView2<A>) -> HashMap<&L, Array1<A>>;
+
+    /// Whether class `a` wins a tie of the joint log likelihood against the smaller class `b`
+    ///
+    /// By default ties are resolved in favour of the smallest class.
+    fn wins_tie(&self, _a: &L, _b: &L) -> bool {
+        false
+    }
+
+    /// Predict the class with the largest joint log likelihood for each sample
+    ///
+    /// The classes are compared in ascending order and ties are resolved with
+    /// `wins_tie`, the prediction is therefore independent of the order in
+    /// which the classes are stored. Returns `BayesError::InvalidValue` if a
+    /// log likelihood is NaN or the model has no classes.
+    fn predict_classes(&self, x: ArrayView2<A>) -> Result<Array1<L>> {
+        let joint_log_likelihood = self.joint_log_likelihood(x);
+
+        let mut classes = joint_log_likelihood.keys().copied().collect::<Vec<_>>();
+        classes.sort_unstable();
+
+        let mut likelihood = Array2::zeros((x.nrows(), classes.len()));
+        for (i, class) in classes.iter().enumerate() {
+            likelihood
+                .column_mut(i)
+                .assign(joint_log_likelihood.get(*class).unwrap());
+        }
+
+        likelihood
+            .outer_iter()
+            .map(|row| {
+                let i = argmax_class(row, &classes, |a, b| self.wins_tie(a, b))
+                    .ok_or(BayesError::InvalidValue)?;
+                Ok(classes[i].clone())
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Array1::from)
+    }
+
+    /// Compute the log probability of each class for the incoming array
+    ///
+    /// Returns a matrix of shape `n_samples x n_classes`.
+    fn predict_log_proba(&self, x: ArrayView2<A>) -> Array2<A> {
+        let joint_log_likelihood = self.joint_log_likelihood(x);
+
+        let mut classes = joint_log_likelihood.keys().collect::<Vec<_>>();
+        classes.sort_unstable();
+
+        let mut log_proba = Array2::zeros((x.nrows(), classes.len()));
+        for (i, class) in classes.iter().enumerate() {
+            log_proba
+                .column_mut(i)
+                .assign(joint_log_likelihood.get(*class).unwrap());
+        }
+
+        // We shift each sample by its maximum first, the remaining
+        // normalization is then between zero and `ln(n_classes)` and does not
+        // cancel out small differences of large log likelihoods
+        let max = log_proba.map_axis(Axis(1), |row| {
+            let max = row.fold(A::neg_infinity(), |acc, x| acc.max(*x));
+            if max.is_finite() {
+                max
+            } else {
+                A::zero()
+            }
+        });
+        let log_proba = log_proba - &max.insert_axis(Axis(1));
+        let norm = log_sum_exp(log_proba.view());
+
+        log_proba - &norm.insert_axis(Axis(1))
+    }
+
+    /// Compute the probability of each class for the incoming array
+    ///
+    /// Returns a matrix of shape `n_samples x n_classes` whose rows sum to
+    /// one.
+    fn predict_proba(&self, x: ArrayView2<A>) -> Array2<A> {
+        self.predict_log_proba(x).mapv(|x| x.exp())
+    }
+}
+
+// Index of the class with the largest value in a row, whose entries are
+// ordered like the ascending `classes`. Equal values are resolved in favour of
+// the smaller class, unless `wins_tie` prefers the larger one. Returns `None`
+// for empty rows and rows containing NaN
+pub(crate) fn argmax_class<A: Float, L>(
+    row: ArrayView1<A>,
+    classes: &[&L],
+    wins_tie: impl Fn(&L, &L) -> bool,
+) -> Option<usize> {
+    let mut best: Option<usize> = None;
+    for (i, x) in row.iter().enumerate() {
+        if x.is_nan() {
+            return None;
+        }
+
+        best = match best {
+            Some(j) if *x < row[j] || (*x == row[j] && !wins_tie(classes[i], classes[j])) => {
+                Some(j)
+            }
+            _ => Some(i),
+        };
+    }
+
+    best
+}
+
+// Returns a subset of x corresponding to the class specified by `ycondition`
+pub(crate) fn filter<A: Float, L: Label>(x: &ArrayView2<A>, y: &[L], ycondition: &L) -> Array2<A> {
+    // We identify the row numbers corresponding to the class we are interested in
+    let index = y
+        .iter()
+        .enumerate()
+        .filter_map(|(i, y)| {
+            if ycondition == y {
+                return Some(i);
+            }
+            None
+        })
+        .collect::<Vec<_>>();
+
+    // We subset x to only records corresponding to the class represented in `ycondition`
+    let mut xsubset = Array2::zeros((index.len(), x.ncols()));
+    index
+        .iter()
+        .enumerate()
+        .for_each(|(i, &r)| xsubset.row_mut(i).assign(&x.slice(s![r, ..])));
+
+    xsubset
+}
+
+// Computes `ln(sum_j exp(x_ij))` over the class axis of the joint log likelihood
+//
+// The maximum of each row is subtracted before exponentiating to avoid overflow and underflow
+// for samples with large log likelihoods.
+pub(crate) fn log_sum_exp<A: Float>(x: ArrayView2<A>) -> Array1<A> {
+    x.map_axis(Axis(1), |row| {
+        let max = row.fold(A::neg_infinity(), |acc, x| acc.max(*x));
+        // all classes impossible or a single dominant infinite value
+        if max.is_infinite() {
+            return max;
+        }
+
+        max + row.mapv(|x| (x - max).exp()).sum().ln()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NaiveBayes;
+    use crate::BayesError;
+    use crate::{
+        BernoulliNbParams, CategoricalNbParams, ComplementNbParams, GaussianNbParams,
+        MultinomialNbParams,
+    };
+    use approx::assert_abs_diff_eq;
+    use linfa::traits::Fit;
+    use linfa::Dataset;
+    use ndarray::{array, Array1, ArrayView2, Axis};
+
+    fn assert_proba<N: NaiveBayes<f64, usize>>(model: &N, x: ArrayView2<f64>) {
+        let proba = model.predict_proba(x);
+        assert_eq!(proba.dim(), (x.nrows(), 2));
+        assert_abs_diff_eq!(
+            proba.sum_axis(Axis(1)),
+            Array1::ones(x.nrows()),
+            epsilon = 1e-10
+        );
+
+        let log_proba = model.predict_log_proba(x);
+        assert_abs_diff_eq!(log_proba.mapv(f64::exp), proba, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_predict_proba() {
+        let x = array![[1., 5.], [2., 4.], [0., 3.], [5., 1.], [4., 0.], [3., 1.]];
+        let y = array![1, 1, 1, 2, 2, 2];
+        let data = Dataset::new(x.view(), y.view());
+
+        assert_proba(&GaussianNbParams::params().fit(&data).unwrap(), x.view());
+        assert_proba(&MultinomialNbParams::params().fit(&data).unwrap(), x.view());
+        assert_proba(&BernoulliNbParams::params().fit(&data).unwrap(), x.view());
+        assert_proba(&CategoricalNbParams::params().fit(&data).unwrap(), x.view());
+        assert_proba(&ComplementNbParams::params().fit(&data).unwrap(), x.view());
+    }
+
+    fn assert_lowest_class<N: NaiveBayes<f64, usize>>(model: &N, x: ArrayView2<f64>) {
+        assert_eq!(
+            model.predict_classes(x).unwrap(),
+            Array1::from(vec![3; x.nrows()])
+        );
+    }
+
+    #[test]
+    fn test_predict_ties() {
+        // both classes have identical statistics, every sample is a tie
+        let x = array![[1., 0.], [0., 1.], [1., 0.], [0., 1.]];
+        let y = array![5, 5, 3, 3];
+        let data = Dataset::new(x.view(), y.view());
+
+        assert_lowest_class(&GaussianNbParams::params().fit(&data).unwrap(), x.view());
+        assert_lowest_class(&MultinomialNbParams::params().fit(&data).unwrap(), x.view());
+        assert_lowest_class(&BernoulliNbParams::params().fit(&data).unwrap(), x.view());
+        assert_lowest_class(&CategoricalNbParams::params().fit(&data).unwrap(), x.view());
+        assert_lowest_class(&ComplementNbParams::params().fit(&data).unwrap(), x.view());
+
+        // NaN likelihoods have no maximum
+        let model = MultinomialNbParams::params().fit(&data).unwrap();
+        assert!(matches!(
+            model.predict_classes(array![[f64::NAN, 1.]].view()),
+            Err(BayesError::InvalidValue)
+        ));
+    }
+}
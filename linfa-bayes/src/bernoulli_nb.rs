@@ -0,0 +1,419 @@
+//! Bernoulli Naive Bayes (BernoulliNB)
+//!
+//! Implements Bernoulli Naive Bayes algorithm for classification of binary
+//! features, for example the presence or absence of words in text
+//! classification. The likelihood of the feature P(x_i | y) is assumed to
+//! follow a Bernoulli distribution, whose parameters are estimated with a
+//! smoothed version of maximum likelihood.
+
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2, Axis};
+use std::collections::HashMap;
+
+use crate::base_nb::{filter, NaiveBayes};
+use crate::error::Result;
+use linfa::dataset::{Dataset, Labels};
+use linfa::traits::{Fit, IncrementalFit, Predict};
+use linfa::Float;
+
+/// Bernoulli Naive Bayes (BernoulliNB)
+#[derive(Debug)]
+pub struct BernoulliNbParams {
+    // Additive (Laplace/Lidstone) smoothing parameter
+    alpha: f64,
+    // Threshold for mapping the features to binary values
+    binarize: Option<f64>,
+}
+
+impl Default for BernoulliNbParams {
+    fn default() -> Self {
+        Self::params()
+    }
+}
+
+impl BernoulliNbParams {
+    /// Create new BernoulliNB model with default values for its parameters
+    pub fn params() -> Self {
+        BernoulliNbParams {
+            alpha: 1.0,
+            binarize: Some(0.0),
+        }
+    }
+
+    // Specifies the additive smoothing parameter, a value of `1.0` corresponds
+    // to Laplace smoothing, smaller values to Lidstone smoothing and `0.0`
+    // disables the smoothing
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    // Specifies the threshold for binarizing the features, every feature
+    // larger than the threshold is mapped to one and every other to zero. If
+    // `None` the input is assumed to consist of binary features already
+    pub fn binarize(mut self, binarize: Option<f64>) -> Self {
+        self.binarize = binarize;
+        self
+    }
+}
+
+impl<'a, A, L> Fit<'a, ArrayView2<'_, A>, L> for BernoulliNbParams
+where
+    A: Float,
+    L: Labels<Elem = usize>,
+{
+    type Object = Result<BernoulliNb<A>>;
+
+    /// Fit the model
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ndarray::array;
+    /// # use linfa::Dataset;
+    /// # use linfa_bayes::BernoulliNbParams;
+    /// # use linfa::traits::{Fit, Predict};
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let x = array![
+    ///     [1., 1., 0.],
+    ///     [1., 0., 0.],
+    ///     [1., 1., 0.],
+    ///     [0., 0., 1.],
+    ///     [0., 1., 1.],
+    ///     [0., 0., 1.]
+    /// ];
+    /// let y = vec![1, 1, 1, 2, 2, 2];
+    ///
+    /// let data = Dataset::new(x.view(), &y);
+    /// let model = BernoulliNbParams::params().fit(&data)?;
+    /// let pred = model.predict(x.view());
+    ///
+    /// assert_eq!(pred.to_vec(), y);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn fit(&self, dataset: &'a Dataset<ArrayView2<A>, L>) -> Self::Object {
+        let model = self.fit_with(None, dataset)?;
+
+        Ok(model.unwrap())
+    }
+}
+
+impl<A, L> IncrementalFit<'_, ArrayView2<'_, A>, L> for BernoulliNbParams
+where
+    A: Float,
+    L: Labels<Elem = usize>,
+{
+    type ObjectIn = Option<BernoulliNb<A>>;
+    type ObjectOut = Result<Option<BernoulliNb<A>>>;
+
+    /// Incrementally fit on a batch of samples
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ndarray::{array, Axis};
+    /// # use linfa::Dataset;
+    /// # use linfa_bayes::BernoulliNbParams;
+    /// # use linfa::traits::{Predict, IncrementalFit};
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let x = array![
+    ///     [1., 1., 0.],
+    ///     [1., 0., 0.],
+    ///     [1., 1., 0.],
+    ///     [0., 0., 1.],
+    ///     [0., 1., 1.],
+    ///     [0., 0., 1.]
+    /// ];
+    /// let y = array![1, 1, 1, 2, 2, 2];
+    ///
+    /// let clf = BernoulliNbParams::params();
+    /// let mut model = None;
+    ///
+    /// for (x, y) in x
+    ///     .axis_chunks_iter(Axis(0), 2)
+    ///     .zip(y.axis_chunks_iter(Axis(0), 2))
+    /// {
+    ///     model = clf.fit_with(model, &Dataset::new(x, y))?;
+    /// }
+    ///
+    /// let pred = model.as_ref().unwrap().predict(x.view());
+    ///
+    /// assert_eq!(pred, y);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn fit_with(
+        &self,
+        model_in: Self::ObjectIn,
+        dataset: &Dataset<ArrayView2<A>, L>,
+    ) -> Self::ObjectOut {
+        let x = binarize(dataset.records(), self.binarize);
+        let y = dataset.targets();
+
+        let mut model = match model_in {
+            Some(temp) => temp,
+            None => BernoulliNb {
+                class_info: HashMap::new(),
+                binarize: self.binarize,
+            },
+        };
+
+        let yunique = y.labels();
+
+        for class in yunique.iter() {
+            // We filter x for records that correspond to the current class
+            let xclass = filter(&x.view(), y.as_slice(), class);
+
+            // We count the number of occurances of the class
+            let nclass = xclass.nrows();
+
+            // We compute the update of the feature counts
+            let class_info = model
+                .class_info
+                .entry(*class)
+                .or_insert_with(ClassInfo::default);
+            let feature_count =
+                Self::update_feature_count(&class_info.feature_count.view(), &xclass);
+
+            // We now update the feature counts, class count and log probabilities
+            class_info.feature_count = feature_count;
+            class_info.class_count += nclass;
+            class_info.feature_log_prob =
+                self.feature_log_prob(&class_info.feature_count.view(), class_info.class_count);
+        }
+
+        // We update the priors
+        let class_count_sum = model
+            .class_info
+            .values()
+            .fold(0, |acc, x| acc + x.class_count);
+        for info in model.class_info.values_mut() {
+            info.prior = A::from(info.class_count).unwrap() / A::from(class_count_sum).unwrap();
+        }
+
+        Ok(Some(model))
+    }
+}
+
+impl BernoulliNbParams {
+    // Compute online update of the feature counts
+    fn update_feature_count<A: Float>(count_old: &ArrayView1<A>, x_new: &Array2<A>) -> Array1<A> {
+        let count_new = x_new.sum_axis(Axis(0));
+
+        // If previous batch was empty, we send the new counts
+        if count_old.is_empty() {
+            return count_new;
+        }
+
+        count_new + count_old
+    }
+
+    // Compute the smoothed log probabilities of the features being present
+    fn feature_log_prob<A: Float>(
+        &self,
+        feature_count: &ArrayView1<A>,
+        class_count: usize,
+    ) -> Array1<A> {
+        let alpha = A::from(self.alpha).unwrap();
+        let total = (A::from(class_count).unwrap() + alpha + alpha).ln();
+
+        feature_count.mapv(|x| (x + alpha).ln() - total)
+    }
+}
+
+// Maps the features to binary values if a threshold is given
+fn binarize<A: Float>(x: &ArrayView2<A>, threshold: Option<f64>) -> Array2<A> {
+    match threshold {
+        Some(threshold) => {
+            let threshold = A::from(threshold).unwrap();
+            x.mapv(|x| if x > threshold { A::one() } else { A::zero() })
+        }
+        None => x.to_owned(),
+    }
+}
+
+/// Fitted BernoulliNB for predicting classes
+#[derive(Debug, Clone)]
+pub struct BernoulliNb<A> {
+    class_info: HashMap<usize, ClassInfo<A>>,
+    binarize: Option<f64>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct ClassInfo<A> {
+    class_count: usize,
+    prior: A,
+    feature_count: Array1<A>,
+    feature_log_prob: Array1<A>,
+}
+
+impl<A: Float> Predict<ArrayView2<'_, A>, Array1<usize>> for BernoulliNb<A> {
+    /// Perform classification on incoming array
+    ///
+    /// Ties are resolved in favour of the smallest class. __Panics__ if
+    /// pairwise orderings are undefined (this occurs in presence of NaN
+    /// values), use `predict_classes` to handle this case
+    fn predict(&self, x: ArrayView2<'_, A>) -> Array1<usize> {
+        self.predict_classes(x).unwrap()
+    }
+}
+
+impl<A: Float> NaiveBayes<A, usize> for BernoulliNb<A> {
+    fn joint_log_likelihood(&self, x: ArrayView2<A>) -> HashMap<&usize, Array1<A>> {
+        let x = binarize(&x, self.binarize);
+        let mut joint_log_likelihood = HashMap::new();
+
+        for (class, info) in self.class_info.iter() {
+            let jointi = info.prior.ln();
+
+            // In contrast to the multinomial distribution the absence of a
+            // feature contributes to the likelihood with `log(1 - p)`
+            let neg_prob = info.feature_log_prob.mapv(|x| (A::one() - x.exp()).ln());
+
+            let nij = x.map_axis(Axis(1), |row| {
+                row.iter()
+                    .zip(info.feature_log_prob.iter().zip(neg_prob.iter()))
+                    .map(|(x, (log_prob, neg_prob))| {
+                        if *x != A::zero() {
+                            *x * *log_prob
+                        } else {
+                            *neg_prob
+                        }
+                    })
+                    .sum::<A>()
+            });
+
+            joint_log_likelihood.insert(class, nij + jointi);
+        }
+
+        joint_log_likelihood
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use linfa::Dataset;
+    use ndarray::array;
+
+    #[test]
+    fn test_bernoulli_nb() {
+        let x = array![
+            [1., 1., 0.],
+            [1., 0., 0.],
+            [1., 1., 0.],
+            [0., 0., 1.],
+            [0., 1., 1.],
+            [0., 0., 1.]
+        ];
+        let y = array![1, 1, 1, 2, 2, 2];
+
+        let clf = BernoulliNbParams::params();
+        let data = Dataset::new(x.view(), y.view());
+        let fitted_clf = clf.fit(&data).unwrap();
+        let pred = fitted_clf.predict(x.view());
+        assert_eq!(pred, y);
+
+        // class 1 has the probabilities [4/5, 3/5, 1/5] of the features being present
+        let jll = fitted_clf.joint_log_likelihood(array![[1., 0., 0.]].view());
+        assert_abs_diff_eq!(
+            jll[&1usize][0],
+            0.5f64.ln() + 0.8f64.ln() + 0.4f64.ln() + 0.8f64.ln(),
+            epsilon = 1e-10
+        );
+        // class 2 has the probabilities [1/5, 2/5, 4/5] of the features being present
+        assert_abs_diff_eq!(
+            jll[&2usize][0],
+            0.5f64.ln() + 0.2f64.ln() + 0.6f64.ln() + 0.2f64.ln(),
+            epsilon = 1e-10
+        );
+    }
+
+    #[test]
+    fn test_bnb_fit_with() {
+        let x = array![
+            [1., 1., 0.],
+            [1., 0., 0.],
+            [1., 1., 0.],
+            [0., 0., 1.],
+            [0., 1., 1.],
+            [0., 0., 1.]
+        ];
+        let y = array![1, 1, 1, 2, 2, 2];
+
+        let clf = BernoulliNbParams::params();
+
+        let model = x
+            .axis_chunks_iter(Axis(0), 2)
+            .zip(y.axis_chunks_iter(Axis(0), 2))
+            .map(|(a, b)| Dataset::new(a, b))
+            .fold(None, |current, d| clf.fit_with(current, &d).unwrap())
+            .unwrap();
+
+        let pred = model.predict(x.view());
+        assert_eq!(pred, y);
+
+        // the accumulated model has to be identical to the one trained at once
+        let expected = clf.fit(&Dataset::new(x.view(), y.view())).unwrap();
+        let jll = model.joint_log_likelihood(x.view());
+        let jll_expected = expected.joint_log_likelihood(x.view());
+
+        for (key, value) in jll.iter() {
+            assert_abs_diff_eq!(value, jll_expected.get(key).unwrap(), epsilon = 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_bnb_binarize() {
+        let x = array![
+            [1., 1., 0.],
+            [1., 0., 0.],
+            [1., 1., 0.],
+            [0., 0., 1.],
+            [0., 1., 1.],
+            [0., 0., 1.]
+        ];
+        let y = array![1, 1, 1, 2, 2, 2];
+        let data = Dataset::new(x.view(), y.view());
+
+        // binary input is left untouched by a threshold of zero
+        let model_binarized = BernoulliNbParams::params()
+            .binarize(Some(0.0))
+            .fit(&data)
+            .unwrap();
+        let model = BernoulliNbParams::params()
+            .binarize(None)
+            .fit(&data)
+            .unwrap();
+
+        assert_eq!(model.predict(x.view()), model_binarized.predict(x.view()));
+
+        let jll = model.joint_log_likelihood(x.view());
+        let jll_binarized = model_binarized.joint_log_likelihood(x.view());
+        for (key, value) in jll.iter() {
+            assert_abs_diff_eq!(value, jll_binarized.get(key).unwrap(), epsilon = 1e-10);
+        }
+
+        // a threshold maps continuous features to the same binary features
+        let x_cont = array![
+            [3., 2., 0.5],
+            [4., 0., 0.],
+            [2., 5., 1.],
+            [0., 1., 3.],
+            [1., 2., 4.],
+            [0., 0., 2.]
+        ];
+        let model_cont = BernoulliNbParams::params()
+            .binarize(Some(1.0))
+            .fit(&Dataset::new(x_cont.view(), y.view()))
+            .unwrap();
+
+        let jll_cont = model_cont.joint_log_likelihood(x_cont.view());
+        for (key, value) in jll_cont.iter() {
+            assert_abs_diff_eq!(value, jll_binarized.get(key).unwrap(), epsilon = 1e-10);
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! Categorical Naive Bayes (CategoricalNB)
+//!
+//! Implements Categorical Naive Bayes algorithm for classification of discrete
+//! features, which are neither counts nor continuous. Each feature takes a
+//! category encoded as a non-negative integer and the likelihood P(x_i | y) is
+//! assumed to follow a separate categorical distribution per feature and class,
+//! whose parameters are estimated with a smoothed version of maximum likelihood.
+//!
+//! Like the other estimators the records are floating point matrices, the
+//! categories have to be integral values, for example `0.0`, `1.0` and `2.0`.
+
+use ndarray::{Array1, ArrayView2, Axis};
+use std::collections::HashMap;
+
+use crate::base_nb::{filter, NaiveBayes};
+use crate::error::{BayesError, Result};
+use linfa::dataset::{Dataset, Labels};
+use linfa::traits::{Fit, Predict};
+use linfa::Float;
+
+/// Categorical Naive Bayes (CategoricalNB)
+#[derive(Debug)]
+pub struct CategoricalNbParams {
+    // Additive (Laplace/Lidstone) smoothing parameter
+    alpha: f64,
+    // Minimal number of categories of each feature
+    min_categories: usize,
+}
+
+impl Default for CategoricalNbParams {
+    fn default() -> Self {
+        Self::params()
+    }
+}
+
+impl CategoricalNbParams {
+    /// Create new CategoricalNB model with default values for its parameters
+    pub fn params() -> Self {
+        CategoricalNbParams {
+            alpha: 1.0,
+            min_categories: 0,
+        }
+    }
+
+    // Specifies the additive smoothing parameter, a value of `1.0` corresponds
+    // to Laplace smoothing, smaller values to Lidstone smoothing and `0.0`
+    // disables the smoothing
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    // Specifies the minimal number of categories of each feature, categories
+    // which are absent from the training set still receive their share of the
+    // smoothing
+    pub fn min_categories(mut self, min_categories: usize) -> Self {
+        self.min_categories = min_categories;
+        self
+    }
+}
+
+impl<'a, A, L> Fit<'a, ArrayView2<'_, A>, L> for CategoricalNbParams
+where
+    A: Float,
+    L: Labels<Elem = usize>,
+{
+    type Object = Result<CategoricalNb<A>>;
+
+    /// Fit the model
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ndarray::array;
+    /// # use linfa::Dataset;
+    /// # use linfa_bayes::CategoricalNbParams;
+    /// # use linfa::traits::{Fit, Predict};
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let x = array![[0., 1.], [0., 2.], [1., 2.], [2., 0.], [2., 1.], [1., 0.]];
+    /// let y = vec![1, 1, 1, 2, 2, 2];
+    ///
+    /// let data = Dataset::new(x.view(), &y);
+    /// let model = CategoricalNbParams::params().fit(&data)?;
+    /// let pred = model.predict(x.view());
+    ///
+    /// assert_eq!(pred.to_vec(), y);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn fit(&self, dataset: &'a Dataset<ArrayView2<A>, L>) -> Self::Object {
+        let x = dataset.records();
+        let y = dataset.targets();
+
+        if x.nrows() == 0 {
+            return Err(BayesError::EmptyDataset);
+        }
+
+        // We validate the categories once, the counting below relies on it
+        if x.iter().any(|x| category(*x).is_none()) {
+            return Err(BayesError::InvalidValue);
+        }
+
+        // The number of categories of each feature is shared by all classes
+        let ncategories = x
+            .map_axis(Axis(0), |col| {
+                col.iter()
+                    .map(|x| category(*x).unwrap() + 1)
+                    .max()
+                    .unwrap_or(0)
+            })
+            .mapv(|x| usize::max(x, self.min_categories))
+            .to_vec();
+
+        let mut class_info = HashMap::new();
+        for class in y.labels() {
+            // We filter x for records that correspond to the current class
+            let xclass = filter(x, y.as_slice(), &class);
+
+            // We count the occurrences of each category per feature
+            let category_count = xclass
+                .gencolumns()
+                .into_iter()
+                .zip(ncategories.iter())
+                .map(|(col, ncat)| {
+                    let mut count = Array1::zeros(*ncat);
+                    for value in col.iter() {
+                        count[category(*value).unwrap()] += A::one();
+                    }
+                    count
+                })
+                .collect::<Vec<_>>();
+
+            let info = ClassInfo {
+                class_count: xclass.nrows(),
+                prior: A::zero(),
+                feature_log_prob: category_count
+                    .iter()
+                    .map(|count| self.feature_log_prob(count))
+                    .collect(),
+                unseen_log_prob: category_count
+                    .iter()
+                    .map(|count| self.unseen_log_prob(count))
+                    .collect(),
+            };
+
+            class_info.insert(class, info);
+        }
+
+        // We update the priors
+        let nsamples = A::from(x.nrows()).unwrap();
+        for info in class_info.values_mut() {
+            info.prior = A::from(info.class_count).unwrap() / nsamples;
+        }
+
+        Ok(CategoricalNb {
+            class_info,
+            ncategories,
+        })
+    }
+}
+
+// Returns the category of a record, if it is a non-negative integral value
+fn category<A: Float>(x: A) -> Option<usize> {
+    if x >= A::zero() && x.fract() == A::zero() {
+        x.to_usize()
+    } else {
+        None
+    }
+}
+
+impl CategoricalNbParams {
+    // Compute the smoothed log probabilities of each category from their counts
+    fn feature_log_prob<A: Float>(&self, category_count: &Array1<A>) -> Array1<A> {
+        let alpha = A::from(self.alpha).unwrap();
+        let total = self.smoothed_total(category_count);
+
+        category_count.mapv(|x| (x + alpha).ln() - total)
+    }
+
+    // Compute the smoothed log probability of a category without counts
+    fn unseen_log_prob<A: Float>(&self, category_count: &Array1<A>) -> A {
+        A::from(self.alpha).unwrap().ln() - self.smoothed_total(category_count)
+    }
+
+    // Compute the log of the smoothed number of observations of a feature
+    fn smoothed_total<A: Float>(&self, category_count: &Array1<A>) -> A {
+        let alpha = A::from(self.alpha).unwrap();
+        let ncategories = A::from(category_count.len()).unwrap();
+
+        (category_count.sum() + alpha * ncategories).ln()
+    }
+}
+
+/// Fitted CategoricalNB for predicting classes
+#[derive(Debug, Clone)]
+pub struct CategoricalNb<A> {
+    class_info: HashMap<usize, ClassInfo<A>>,
+    ncategories: Vec<usize>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct ClassInfo<A> {
+    class_count: usize,
+    prior: A,
+    // Log probability of each category, one array per feature
+    feature_log_prob: Vec<Array1<A>>,
+    // Log probability of a category outside of the table, one value per feature
+    unseen_log_prob: Vec<A>,
+}
+
+impl<A: Float> CategoricalNb<A> {
+    /// Returns the number of categories of each feature
+    pub fn ncategories(&self) -> &[usize] {
+        &self.ncategories
+    }
+
+    /// Returns the log probability of `category` for a feature and class
+    ///
+    /// Categories, which were not part of the training set, have the smoothed
+    /// probability of a category without observations. Returns `None` for
+    /// unknown classes or features.
+    pub fn log_prob(&self, class: usize, feature: usize, category: usize) -> Option<A> {
+        let info = self.class_info.get(&class)?;
+        let log_prob = info.feature_log_prob.get(feature)?;
+
+        Some(
+            log_prob
+                .get(category)
+                .cloned()
+                .unwrap_or(info.unseen_log_prob[feature]),
+        )
+    }
+}
+
+impl<A: Float> NaiveBayes<A, usize> for CategoricalNb<A> {
+    // Compute unnormalized posterior log probability
+    //
+    // Records, which are not a valid category, are treated like categories
+    // outside of the table
+    fn joint_log_likelihood(&self, x: ArrayView2<A>) -> HashMap<&usize, Array1<A>> {
+        let mut joint_log_likelihood = HashMap::new();
+
+        for (class, info) in self.class_info.iter() {
+            let jointi = info.prior.ln();
+
+            let nij = x.map_axis(Axis(1), |row| {
+                row.iter()
+                    .enumerate()
+                    .map(|(j, x)| {
+                        category(*x)
+                            .and_then(|category| info.feature_log_prob[j].get(category))
+                            .cloned()
+                            .unwrap_or(info.unseen_log_prob[j])
+                    })
+                    .sum::<A>()
+            });
+
+            joint_log_likelihood.insert(class, nij + jointi);
+        }
+
+        joint_log_likelihood
+    }
+}
+
+impl<A: Float> Predict<ArrayView2<'_, A>, Array1<usize>> for CategoricalNb<A> {
+    /// Perform classification on incoming array
+    ///
+    /// Ties are resolved in favour of the smallest class. __Panics__ if the
+    /// input has a different number of features than the training set
+    fn predict(&self, x: ArrayView2<'_, A>) -> Array1<usize> {
+        self.predict_classes(x).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use linfa::Dataset;
+    use ndarray::{array, Array2};
+
+    #[test]
+    fn test_categorical_nb() {
+        // animals described by covering (0: fur, 1: feathers, 2: scales),
+        // number of legs (0: none, 1: two, 2: four) and habitat (0: land,
+        // 1: water, 2: air), the classes are 0: mammal, 1: bird, 2: fish
+        let x = array![
+            [0., 2., 0.],
+            [0., 2., 0.],
+            [0., 1., 0.],
+            [0., 1., 1.],
+            [1., 1., 2.],
+            [1., 1., 2.],
+            [1., 1., 1.],
+            [1., 1., 0.],
+            [2., 0., 1.],
+            [2., 0., 1.],
+            [2., 0., 1.],
+            [2., 0., 2.]
+        ];
+        let y = array![0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2];
+
+        let model = CategoricalNbParams::params()
+            .fit(&Dataset::new(x.view(), y.view()))
+            .unwrap();
+
+        let pred = model.predict(x.view());
+        assert_eq!(pred, y);
+
+        let test = array![[0., 2., 1.], [1., 1., 0.], [2., 0., 0.]];
+        let pred = model.predict(test.view());
+        assert_eq!(pred, array![0, 1, 2]);
+
+        // mammals have fur three times out of four with three categories
+        assert_eq!(model.ncategories(), &[3, 3, 3]);
+        assert_abs_diff_eq!(
+            model.log_prob(0, 0, 0).unwrap(),
+            (5f64 / 7.).ln(),
+            epsilon = 1e-10
+        );
+        assert_abs_diff_eq!(
+            model.log_prob(0, 0, 1).unwrap(),
+            (1f64 / 7.).ln(),
+            epsilon = 1e-10
+        );
+    }
+
+    #[test]
+    fn test_cnb_unseen_categories() {
+        let x = array![[0., 1.], [0., 1.], [1., 0.], [1., 0.]];
+        let y = array![1, 1, 2, 2];
+
+        let model = CategoricalNbParams::params()
+            .min_categories(4)
+            .fit(&Dataset::new(x.view(), y.view()))
+            .unwrap();
+
+        // the table has room for categories, which were not observed
+        assert_eq!(model.ncategories(), &[4, 4]);
+        assert_abs_diff_eq!(
+            model.log_prob(1, 0, 3).unwrap(),
+            (1f64 / 6.).ln(),
+            epsilon = 1e-10
+        );
+
+        // categories outside of the table fall back to the smoothed prior
+        assert_abs_diff_eq!(
+            model.log_prob(1, 0, 10).unwrap(),
+            (1f64 / 6.).ln(),
+            epsilon = 1e-10
+        );
+        assert_eq!(model.log_prob(3, 0, 0), None);
+        assert_eq!(model.log_prob(1, 2, 0), None);
+
+        let test = array![[0., 7.], [9., 0.]];
+        let pred = model.predict(test.view());
+        assert_eq!(pred, array![1, 2]);
+
+        // negative or fractional records are no categories
+        let x = array![[0., 1.5], [1., 0.]];
+        let y = array![1, 2];
+        assert!(CategoricalNbParams::params()
+            .fit(&Dataset::new(x.view(), y.view()))
+            .is_err());
+
+        // as is a dataset without records
+        let x = Array2::<f64>::zeros((0, 2));
+        let y = Array1::<usize>::zeros(0);
+        assert!(matches!(
+            CategoricalNbParams::params().fit(&Dataset::new(x.view(), y.view())),
+            Err(BayesError::EmptyDataset)
+        ));
+    }
+}
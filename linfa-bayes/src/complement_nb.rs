@@ -0,0 +1,348 @@
+//! Complement Naive Bayes (ComplementNB)
+//!
+//! Implements Complement Naive Bayes algorithm for classification of count
+//! data. Instead of estimating the feature distribution of a class from its own
+//! samples, the weights are estimated from the samples of all other classes
+//! (the complement). This reduces the bias of Multinomial Naive Bayes towards
+//! classes with many samples and makes it suited for imbalanced data sets.
+
+use ndarray::{Array1, ArrayView1, ArrayView2, Axis};
+use std::collections::HashMap;
+
+use crate::base_nb::{filter, NaiveBayes};
+use crate::error::{BayesError, Result};
+use linfa::dataset::{Dataset, Labels};
+use linfa::traits::{Fit, IncrementalFit, Predict};
+use linfa::Float;
+
+/// Complement Naive Bayes (ComplementNB)
+#[derive(Debug)]
+pub struct ComplementNbParams {
+    // Additive (Laplace/Lidstone) smoothing parameter
+    alpha: f64,
+    // Whether a second normalization of the weights is performed
+    norm: bool,
+}
+
+impl Default for ComplementNbParams {
+    fn default() -> Self {
+        Self::params()
+    }
+}
+
+impl ComplementNbParams {
+    /// Create new ComplementNB model with default values for its parameters
+    pub fn params() -> Self {
+        ComplementNbParams {
+            alpha: 1.0,
+            norm: false,
+        }
+    }
+
+    // Specifies the additive smoothing parameter, a value of `1.0` corresponds
+    // to Laplace smoothing, smaller values to Lidstone smoothing. The weights
+    // are logarithms of the smoothed counts, values which are not positive
+    // are therefore rejected when fitting
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    // Specifies whether the weights of each class are normalized by their
+    // length, this reduces the influence of features with many occurances
+    pub fn norm(mut self, norm: bool) -> Self {
+        self.norm = norm;
+        self
+    }
+}
+
+impl<'a, A, L> Fit<'a, ArrayView2<'_, A>, L> for ComplementNbParams
+where
+    A: Float,
+    L: Labels<Elem = usize>,
+{
+    type Object = Result<ComplementNb<A>>;
+
+    /// Fit the model
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ndarray::array;
+    /// # use linfa::Dataset;
+    /// # use linfa_bayes::ComplementNbParams;
+    /// # use linfa::traits::{Fit, Predict};
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let x = array![
+    ///     [4., 4., 1.],
+    ///     [5., 4., 0.],
+    ///     [4., 5., 1.],
+    ///     [5., 5., 0.],
+    ///     [1., 1., 3.],
+    ///     [1., 0., 3.]
+    /// ];
+    /// let y = vec![1, 1, 1, 1, 2, 2];
+    ///
+    /// let data = Dataset::new(x.view(), &y);
+    /// let model = ComplementNbParams::params().fit(&data)?;
+    /// let pred = model.predict(x.view());
+    ///
+    /// assert_eq!(pred.to_vec(), y);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn fit(&self, dataset: &'a Dataset<ArrayView2<A>, L>) -> Self::Object {
+        let model = self.fit_with(None, dataset)?;
+
+        Ok(model.unwrap())
+    }
+}
+
+impl<A, L> IncrementalFit<'_, ArrayView2<'_, A>, L> for ComplementNbParams
+where
+    A: Float,
+    L: Labels<Elem = usize>,
+{
+    type ObjectIn = Option<ComplementNb<A>>;
+    type ObjectOut = Result<Option<ComplementNb<A>>>;
+
+    /// Incrementally fit on a batch of samples
+    ///
+    /// The feature counts of each class are accumulated over all batches and
+    /// the complement weights of all classes are re-estimated afterwards.
+    fn fit_with(
+        &self,
+        model_in: Self::ObjectIn,
+        dataset: &Dataset<ArrayView2<A>, L>,
+    ) -> Self::ObjectOut {
+        let x = dataset.records();
+        let y = dataset.targets();
+
+        if x.nrows() == 0 {
+            return Err(BayesError::EmptyDataset);
+        }
+        if self.alpha.is_nan() || self.alpha <= 0.0 {
+            return Err(BayesError::InvalidParameter {
+                name: "alpha",
+                value: self.alpha,
+            });
+        }
+
+        let mut model = match model_in {
+            Some(temp) => temp,
+            None => ComplementNb {
+                class_info: HashMap::new(),
+            },
+        };
+
+        let yunique = y.labels();
+
+        for class in yunique.iter() {
+            // We filter x for records that correspond to the current class
+            let xclass = filter(x, y.as_slice(), class);
+
+            // We count the number of occurances of the class
+            let nclass = xclass.nrows();
+
+            // We compute the update of the feature counts
+            let class_info = model
+                .class_info
+                .entry(*class)
+                .or_insert_with(ClassInfo::default);
+            let feature_count = xclass.sum_axis(Axis(0));
+
+            // We now update the feature counts and class count
+            if class_info.feature_count.is_empty() {
+                class_info.feature_count = feature_count;
+            } else {
+                class_info.feature_count += &feature_count;
+            }
+            class_info.class_count += nclass;
+        }
+
+        // The complement of a class depends on the counts of all other classes,
+        // we therefore have to update the weights of every class
+        let feature_all = model
+            .class_info
+            .values()
+            .map(|info| info.feature_count.view())
+            .fold(None, |acc: Option<Array1<A>>, x| match acc {
+                Some(acc) => Some(acc + x),
+                None => Some(x.to_owned()),
+            })
+            .unwrap();
+
+        for info in model.class_info.values_mut() {
+            info.feature_weight = self.feature_weight(&feature_all.view(), &info.feature_count);
+        }
+
+        // We update the priors
+        let class_count_sum = model
+            .class_info
+            .values()
+            .fold(0, |acc, x| acc + x.class_count);
+        for info in model.class_info.values_mut() {
+            info.prior = A::from(info.class_count).unwrap() / A::from(class_count_sum).unwrap();
+        }
+
+        Ok(Some(model))
+    }
+}
+
+impl ComplementNbParams {
+    // Compute the smoothed log probabilities of the complement, optionally
+    // normalized by their length
+    fn feature_weight<A: Float>(
+        &self,
+        feature_all: &ArrayView1<A>,
+        feature_count: &Array1<A>,
+    ) -> Array1<A> {
+        let alpha = A::from(self.alpha).unwrap();
+
+        let complement_count = feature_all - feature_count + alpha;
+        let total = complement_count.sum().ln();
+        let weight = complement_count.mapv(|x| x.ln() - total);
+
+        if self.norm {
+            let length = weight.sum().abs();
+            weight.mapv(|x| x / length)
+        } else {
+            weight
+        }
+    }
+}
+
+/// Fitted ComplementNB for predicting classes
+#[derive(Debug, Clone)]
+pub struct ComplementNb<A> {
+    class_info: HashMap<usize, ClassInfo<A>>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct ClassInfo<A> {
+    class_count: usize,
+    prior: A,
+    feature_count: Array1<A>,
+    feature_weight: Array1<A>,
+}
+
+impl<A: Float> Predict<ArrayView2<'_, A>, Array1<usize>> for ComplementNb<A> {
+    /// Perform classification on incoming array
+    ///
+    /// Ties are resolved in favour of the smallest class. __Panics__ if
+    /// pairwise orderings are undefined (this occurs in presence of NaN
+    /// values), use `predict_classes` to handle this case
+    fn predict(&self, x: ArrayView2<'_, A>) -> Array1<usize> {
+        self.predict_classes(x).unwrap()
+    }
+}
+
+impl<A: Float> NaiveBayes<A, usize> for ComplementNb<A> {
+    // Compute the unnormalized score of each class
+    //
+    // A sample is assigned to the class whose complement matches it the least,
+    // the weights of the complement are therefore subtracted. The priors are
+    // ignored, except for the degenerate case of a single class.
+    fn joint_log_likelihood(&self, x: ArrayView2<A>) -> HashMap<&usize, Array1<A>> {
+        let mut joint_log_likelihood = HashMap::new();
+
+        for (class, info) in self.class_info.iter() {
+            let mut nij = x.dot(&info.feature_weight).mapv(|x| -x);
+
+            if self.class_info.len() == 1 {
+                nij += info.prior.ln();
+            }
+
+            joint_log_likelihood.insert(class, nij);
+        }
+
+        joint_log_likelihood
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MultinomialNbParams;
+    use linfa::Dataset;
+    use ndarray::{array, Array2};
+
+    #[test]
+    fn test_complement_nb() {
+        let x = array![
+            [4., 4., 1.],
+            [5., 4., 0.],
+            [4., 5., 1.],
+            [5., 5., 0.],
+            [1., 1., 3.],
+            [1., 0., 3.]
+        ];
+        let y = array![1, 1, 1, 1, 2, 2];
+
+        let data = Dataset::new(x.view(), y.view());
+        let model = ComplementNbParams::params().fit(&data).unwrap();
+        assert_eq!(model.predict(x.view()), y);
+
+        let model = ComplementNbParams::params().norm(true).fit(&data).unwrap();
+        assert_eq!(model.predict(x.view()), y);
+
+        // the smoothing has to be positive
+        assert!(matches!(
+            ComplementNbParams::params().alpha(0.).fit(&data),
+            Err(BayesError::InvalidParameter { name: "alpha", .. })
+        ));
+    }
+
+    #[test]
+    fn test_cnb_empty_dataset() {
+        let x = Array2::<f64>::zeros((0, 3));
+        let y = Array1::<usize>::zeros(0);
+
+        let data = Dataset::new(x.view(), y.view());
+        assert!(matches!(
+            ComplementNbParams::params().fit(&data),
+            Err(BayesError::EmptyDataset)
+        ));
+    }
+
+    #[test]
+    fn test_cnb_imbalanced() {
+        // the first class has eight times as many samples as the second one
+        let mut records = Vec::new();
+        for _ in 0..8 {
+            records.extend(&[4., 4., 1., 5., 4., 0.]);
+        }
+        records.extend(&[1., 1., 3., 1., 0., 3.]);
+        let x = Array2::from_shape_vec((18, 3), records).unwrap();
+        let mut y = vec![1; 16];
+        y.extend(vec![2; 2]);
+
+        let test = array![
+            [1., 1., 1.],
+            [0., 1., 1.],
+            [1., 0., 1.],
+            [1., 1., 2.],
+            [2., 1., 0.],
+            [1., 2., 0.],
+            [1., 1., 0.],
+            [2., 2., 1.]
+        ];
+        let test_y = [2, 2, 2, 2, 1, 1, 1, 1];
+
+        let accuracy = |pred: Array1<usize>| {
+            pred.iter()
+                .zip(test_y.iter())
+                .filter(|(a, b)| a == b)
+                .count()
+        };
+
+        let data = Dataset::new(x.view(), &y);
+        let multinomial = MultinomialNbParams::params().fit(&data).unwrap();
+        let complement = ComplementNbParams::params().fit(&data).unwrap();
+
+        // the prior of the majority class outweighs the evidence for multinomial
+        assert_eq!(accuracy(multinomial.predict(test.view())), 5);
+        assert_eq!(accuracy(complement.predict(test.view())), 8);
+    }
+}
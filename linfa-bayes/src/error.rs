@@ -8,12 +8,64 @@ pub type Result<T> = std::result::Result<T, BayesError>;
 pub enum BayesError {
     /// Error when performing Max operation on data
     Stats(MinMaxError),
+    /// Error when predicting on an empty input
+    EmptyInput,
+    /// Error when the input contains values without an ordering, like NaN
+    InvalidValue,
+    /// Error when fitting on a dataset without any records
+    EmptyDataset,
+    /// Error when a class of the label set, for example declared with `Dataset::with_labels`,
+    /// has no records, identified by its position in the sorted label set
+    EmptyClass(usize),
+    /// Error when a batch contains a class, which was not declared upfront,
+    /// identified by the index of its first record
+    UndeclaredClass(usize),
+    /// Error when fitting on a dataset, which contains a single class
+    SingleClass,
+    /// Error when the number of features differs from the training data
+    FeatureCountMismatch { expected: usize, found: usize },
+    /// Error when a feature is negative, while the log transform is enabled
+    NegativeValue,
+    /// Error when a hyperparameter is outside of its valid range
+    InvalidParameter { name: &'static str, value: f64 },
 }
 
 impl fmt::Display for BayesError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::Stats(error) => write!(f, "Ndarray Stats Error: {}", error),
+            Self::EmptyInput => write!(f, "Empty input"),
+            Self::InvalidValue => write!(f, "Invalid value encountered, the input may contain NaN"),
+            Self::EmptyDataset => write!(
+                f,
+                "Empty dataset, at least one record is required for fitting"
+            ),
+            Self::EmptyClass(idx) => write!(
+                f,
+                "Class at index {} has no records, make sure each label has at least one sample",
+                idx
+            ),
+            Self::UndeclaredClass(idx) => write!(
+                f,
+                "Record {} has a class, which was not declared with `with_classes`",
+                idx
+            ),
+            Self::SingleClass => write!(
+                f,
+                "Dataset contains a single class, enable `allow_single_class` if this is intended"
+            ),
+            Self::FeatureCountMismatch { expected, found } => write!(
+                f,
+                "Expected {} features as in the training data, found {}",
+                expected, found
+            ),
+            Self::NegativeValue => write!(
+                f,
+                "Negative value encountered, the log transform requires non-negative features"
+            ),
+            Self::InvalidParameter { name, value } => {
+                write!(f, "Invalid value {} of parameter `{}`", value, name)
+            }
         }
     }
 }
@@ -4,33 +4,89 @@
 //! of the feature P(x_i | y) is assumed to be Gaussian, the mean and variance will
 //! be estimated using maximum likelihood.
 
-use ndarray::{s, Array1, Array2, ArrayView1, ArrayView2, Axis};
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2, Axis};
 use ndarray_stats::QuantileExt;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::error::Result;
-use linfa::dataset::{Dataset, Labels};
+use crate::base_nb::{argmax_class, filter, log_sum_exp, NaiveBayes};
+use crate::error::{BayesError, Result};
+use linfa::dataset::{Dataset, Label, Labels};
+use linfa::metrics::Classifier;
 use linfa::traits::{Fit, IncrementalFit, Predict};
 use linfa::Float;
 
+/// Resolution of ties between classes with the same likelihood
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Predict the smallest of the tied classes
+    LowestClass,
+    /// Predict the tied class with the largest prior, equal priors are
+    /// resolved in favour of the smallest class
+    HighestPrior,
+    /// Predict the tied class, which was declared or observed first during
+    /// fitting
+    First,
+}
+
 /// Gaussian Naive Bayes (GaussianNB)
-#[derive(Debug)]
-pub struct GaussianNbParams {
+///
+/// The classes are of type `L`, which defaults to `usize` for integer encoded
+/// targets
+#[derive(Debug, Clone)]
+pub struct GaussianNbParams<L = usize> {
     // Required for calculation stability
     var_smoothing: f64,
+    // Exponential forgetting factor of previous batches
+    lambda: f64,
+    // Whether the variance is pooled across classes
+    shared_variance: bool,
+    // Classes declared upfront for incremental fits
+    classes: Option<Vec<L>>,
+    // Candidate smoothing values, selected with cross-validation
+    var_smoothing_search: Option<Vec<f64>>,
+    // Lower bound of every per-class variance
+    min_variance: Option<f64>,
+    // Whether NaN features are skipped in predictions
+    ignore_missing: bool,
+    // Resolution of ties between classes in predictions
+    tie_break: TieBreak,
+    // Whether `fit` accepts a dataset with a single class
+    allow_single_class: bool,
+    // Whether the features are transformed with `ln(1 + x)`
+    log_transform: bool,
+    // Seed of the randomized steps of fitting
+    seed: Option<u64>,
 }
 
-impl Default for GaussianNbParams {
+impl<L> Default for GaussianNbParams<L> {
     fn default() -> Self {
         Self::params()
     }
 }
 
-impl GaussianNbParams {
+impl<L> GaussianNbParams<L> {
     /// Create new GaussianNB model with default values for its parameters
     pub fn params() -> Self {
         GaussianNbParams {
             var_smoothing: 1e-9,
+            lambda: 1.0,
+            shared_variance: false,
+            classes: None,
+            var_smoothing_search: None,
+            min_variance: None,
+            ignore_missing: false,
+            tie_break: TieBreak::LowestClass,
+            allow_single_class: false,
+            log_transform: false,
+            seed: None,
         }
     }
 
@@ -40,14 +96,214 @@ impl GaussianNbParams {
         self.var_smoothing = var_smoothing;
         self
     }
+
+    // Specifies the forgetting factor of incremental fits, the statistics of
+    // previous batches are weighted by `lambda` before a new batch is folded
+    // in. A value of `1.0` keeps all observations, smaller values let old data
+    // decay geometrically, which helps to adapt to drifting streams. Values
+    // outside of `(0, 1]` are rejected when fitting
+    pub fn lambda(mut self, lambda: f64) -> Self {
+        self.lambda = lambda;
+        self
+    }
+
+    // Specifies whether all classes share a common variance. When enabled the
+    // per-class variances are pooled, weighted by the class counts, once the
+    // per-class means are computed. This can generalize better when only few
+    // samples are available per class
+    pub fn shared_variance(mut self, shared_variance: bool) -> Self {
+        self.shared_variance = shared_variance;
+        self
+    }
+
+    // Specifies all classes of an incremental fit upfront. The model then
+    // knows every class from the first batch on, classes without
+    // observations have a prior of zero, and a batch with an undeclared class
+    // is rejected. This keeps the columns of `predict_proba` stable across
+    // the stream
+    pub fn with_classes(mut self, classes: Vec<L>) -> Self {
+        self.classes = Some(classes);
+        self
+    }
+
+    // Specifies candidate values of `var_smoothing`, which are scored with a
+    // k-fold cross-validation of the training set when the model is fitted.
+    // The value with the best held-out accuracy is used for the final fit,
+    // ties are resolved in favour of the earlier candidate. This only applies
+    // to `fit`, incremental fits use `var_smoothing`
+    pub fn var_smoothing_search(mut self, values: &[f64]) -> Self {
+        self.var_smoothing_search = Some(values.to_vec());
+        self
+    }
+
+    // Specifies a lower bound of the variance of every feature within every
+    // class, which is applied after fitting in addition to `var_smoothing`.
+    // Unlike the global smoothing this only affects features whose variance
+    // is tiny within a class and keeps them from dominating the likelihood.
+    // Incremental fits continue from the statistics before flooring
+    pub fn min_variance(mut self, min_variance: f64) -> Self {
+        self.min_variance = Some(min_variance);
+        self
+    }
+
+    // Specifies whether NaN feature values are treated as missing at random
+    // when predicting. The likelihood of a sample is then the marginal
+    // likelihood of its observed features, missing features neither
+    // contribute to the sum nor to the normalization. The training data has
+    // to be complete
+    pub fn ignore_missing(mut self, ignore_missing: bool) -> Self {
+        self.ignore_missing = ignore_missing;
+        self
+    }
+
+    // Specifies which class is predicted when several classes have exactly
+    // the same likelihood, for example for samples on the symmetry axis of
+    // two classes. Defaults to `TieBreak::LowestClass`
+    pub fn tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    // Specifies whether `fit` accepts a dataset, which contains a single
+    // class. Such a model predicts this class for every sample, which usually
+    // points to a bug when loading the data, and is rejected by default.
+    // Enable this for one-class, density-style use. Incremental fits accept
+    // single-class batches regardless
+    pub fn allow_single_class(mut self, allow_single_class: bool) -> Self {
+        self.allow_single_class = allow_single_class;
+        self
+    }
+
+    // Specifies whether the Gaussians are fitted to `ln(1 + x)` instead of the
+    // raw features, which suits right-skewed, non-negative features like
+    // counts or durations. The offset of one keeps zeros finite. The model
+    // applies the same transform to the records it predicts, its likelihoods
+    // are then densities of the transformed features. Negative features are
+    // rejected
+    pub fn log_transform(mut self, log_transform: bool) -> Self {
+        self.log_transform = log_transform;
+        self
+    }
+
+    // Specifies the seed of the randomized steps of fitting. The seed
+    // shuffles the records before they are split into the cross-validation
+    // folds of `var_smoothing_search`. Without a seed the random number
+    // generator is seeded from the entropy of the operating system, such that
+    // repeated fits may select different values. Identical seeds always yield
+    // identical models
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// Number of folds used to score the candidates of `var_smoothing_search`
+const VAR_SMOOTHING_FOLDS: usize = 5;
+
+impl<L: Label + Ord> GaussianNbParams<L> {
+    /// Fit the model on records and targets without building a `Dataset`
+    ///
+    /// __Panics__ if the number of records and targets differ
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ndarray::array;
+    /// # use linfa_bayes::GaussianNbParams;
+    /// # use linfa::traits::Predict;
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let x = array![[-2., -1.], [-1., -1.], [1., 1.], [2., 1.]];
+    /// let y = [1, 1, 2, 2];
+    ///
+    /// let model = GaussianNbParams::params().fit_arrays(x.view(), &y)?;
+    ///
+    /// assert_eq!(model.predict(x.view()).to_vec(), y);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fit_arrays<A: Float>(&self, x: ArrayView2<A>, y: &[L]) -> Result<GaussianNb<A, L>> {
+        assert_eq!(
+            x.nrows(),
+            y.len(),
+            "number of records and targets have to be equal"
+        );
+
+        self.fit(&Dataset::new(x, y))
+    }
+
+    // Select the smoothing value with the best cross-validated accuracy
+    fn select_var_smoothing<A: Float, T: Labels<Elem = L>>(
+        &self,
+        dataset: &Dataset<ArrayView2<A>, T>,
+    ) -> Result<f64> {
+        let values = match &self.var_smoothing_search {
+            Some(values) if !values.is_empty() => values,
+            _ => return Ok(self.var_smoothing),
+        };
+
+        let (x, y) = (dataset.records(), dataset.targets().as_slice());
+        let nfolds = usize::min(VAR_SMOOTHING_FOLDS, x.nrows());
+        if nfolds < 2 {
+            return Ok(values[0]);
+        }
+
+        // Every `nfolds`-th record is assigned to the same validation fold,
+        // after shuffling the records
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut order = (0..x.nrows()).collect::<Vec<_>>();
+        order.shuffle(&mut rng);
+        let mut assignment = vec![0; x.nrows()];
+        for (pos, idx) in order.into_iter().enumerate() {
+            assignment[idx] = pos % nfolds;
+        }
+        let folds = (0..nfolds)
+            .map(|fold| (0..x.nrows()).partition::<Vec<_>, _>(|i| assignment[*i] == fold))
+            .collect::<Vec<_>>();
+
+        let mut best = (values[0], 0);
+        for value in values {
+            let params = GaussianNbParams {
+                var_smoothing: *value,
+                var_smoothing_search: None,
+                ..self.clone()
+            };
+
+            let mut correct = 0;
+            for (valid, train) in &folds {
+                let train_records = x.select(Axis(0), train);
+                let train_targets = train.iter().map(|i| y[*i].clone()).collect::<Vec<_>>();
+                let model = params
+                    .fit_with(None, &Dataset::new(train_records.view(), train_targets))?
+                    .unwrap();
+
+                let predicted = model.try_predict(x.select(Axis(0), valid).view())?;
+                correct += predicted
+                    .iter()
+                    .zip(valid.iter())
+                    .filter(|(p, i)| **p == y[**i])
+                    .count();
+            }
+
+            if correct > best.1 {
+                best = (*value, correct);
+            }
+        }
+
+        Ok(best.0)
+    }
 }
 
-impl<'a, A, L> Fit<'a, ArrayView2<'_, A>, L> for GaussianNbParams
+impl<'a, A, L, T> Fit<'a, ArrayView2<'_, A>, T> for GaussianNbParams<L>
 where
     A: Float,
-    L: Labels<Elem = usize>,
+    L: Label + Ord + 'a,
+    T: Labels<Elem = L>,
 {
-    type Object = Result<GaussianNb<A>>;
+    type Object = Result<GaussianNb<A, L>>;
 
     /// Fit the model
     ///
@@ -78,30 +334,92 @@ where
     /// # Ok(())
     /// # }
     /// ```
-    fn fit(&self, dataset: &'a Dataset<ArrayView2<A>, L>) -> Self::Object {
-        // We extract the unique classes in sorted order
-        let mut unique_classes = dataset.targets.labels();
-        unique_classes.sort_unstable();
+    fn fit(&self, dataset: &'a Dataset<ArrayView2<A>, T>) -> Self::Object {
+        let mut model: Option<GaussianNb<_, _>> = None;
+
+        // We train the model, possibly with the smoothing selected by
+        // cross-validation
+        model = match self.var_smoothing_search {
+            Some(_) => {
+                let params = GaussianNbParams {
+                    var_smoothing: self.select_var_smoothing(dataset)?,
+                    var_smoothing_search: None,
+                    ..self.clone()
+                };
+                params.fit_with(model, dataset)?
+            }
+            None => self.fit_with(model, dataset)?,
+        };
+        let model = model.unwrap();
+
+        // A model with a single observed class predicts this class for any
+        // sample
+        let nobserved = model
+            .class_info
+            .values()
+            .filter(|info| info.class_count > 0)
+            .count();
+        if nobserved == 1 && !self.allow_single_class {
+            return Err(BayesError::SingleClass);
+        }
+
+        Ok(model)
+    }
+}
 
-        let mut model: Option<GaussianNb<_>> = None;
+impl<'a, A, L, T> Fit<'a, Array2<A>, T> for GaussianNbParams<L>
+where
+    A: Float,
+    L: Label + Ord + 'a,
+    T: Labels<Elem = L>,
+{
+    type Object = Result<GaussianNb<A, L>>;
 
-        // We train the model
-        model = self.fit_with(model, dataset)?;
+    /// Fit the model on a dataset, which owns its records
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ndarray::array;
+    /// # use linfa::Dataset;
+    /// # use linfa_bayes::GaussianNbParams;
+    /// # use linfa::traits::{Fit, Predict};
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let x = array![[-2., -1.], [-1., -1.], [1., 1.], [2., 1.]];
+    /// let data = Dataset::from_arrays(x, array![1, 1, 2, 2]);
+    /// let model = GaussianNbParams::params().fit(&data)?;
+    ///
+    /// let (records, targets) = data.records_targets();
+    /// assert_eq!(model.predict(records).as_slice(), Some(targets));
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn fit(&self, dataset: &'a Dataset<Array2<A>, T>) -> Self::Object {
+        let (records, targets) = dataset.records_targets();
 
-        Ok(model.unwrap())
+        self.fit(&Dataset::new(records, targets))
     }
 }
 
-impl<A, L> IncrementalFit<'_, ArrayView2<'_, A>, L> for GaussianNbParams
+impl<'a, A, L, T> IncrementalFit<'a, ArrayView2<'_, A>, T> for GaussianNbParams<L>
 where
     A: Float,
-    L: Labels<Elem = usize>,
+    L: Label + Ord + 'a,
+    T: Labels<Elem = L>,
 {
-    type ObjectIn = Option<GaussianNb<A>>;
-    type ObjectOut = Result<Option<GaussianNb<A>>>;
+    type ObjectIn = Option<GaussianNb<A, L>>;
+    type ObjectOut = Result<Option<GaussianNb<A, L>>>;
 
     /// Incrementally fit on a batch of samples
     ///
+    /// The samples of each class in a batch are summarized by their count, mean and sum of
+    /// squared deviations, which are pooled exactly with the previous statistics. With
+    /// `lambda = 1` and `var_smoothing = 0` the fitted model therefore does not depend on the
+    /// order of the samples within or across batches, apart from rounding errors, and batches
+    /// sorted by class need no shuffling. A `var_smoothing` is derived from the variances of the
+    /// latest batch only.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -140,10 +458,29 @@ where
     fn fit_with(
         &self,
         model_in: Self::ObjectIn,
-        dataset: &Dataset<ArrayView2<A>, L>,
+        dataset: &'a Dataset<ArrayView2<A>, T>,
     ) -> Self::ObjectOut {
         let x = dataset.records();
-        let y = dataset.targets();
+        let y = dataset.targets().as_slice();
+
+        if x.nrows() == 0 {
+            return Err(BayesError::EmptyDataset);
+        }
+        if !(self.lambda > 0.0 && self.lambda <= 1.0) {
+            return Err(BayesError::InvalidParameter {
+                name: "lambda",
+                value: self.lambda,
+            });
+        }
+
+        // The statistics are accumulated for the transformed features
+        let transformed;
+        let x = if self.log_transform {
+            transformed = log_transform(x.view())?;
+            transformed.view()
+        } else {
+            x.view()
+        };
 
         // If the ratio of the variance between dimensions is too small, it will cause
         // numerical errors. We address this by artificially boosting the variance
@@ -151,286 +488,1348 @@ where
         let epsilon =
             A::from(self.var_smoothing).unwrap() * *x.var_axis(Axis(0), A::zero()).max()?;
 
+        let lambda = A::from(self.lambda).unwrap();
         let mut model = match model_in {
             Some(mut temp) => {
-                temp.class_info
-                    .values_mut()
-                    .for_each(|x| x.sigma -= epsilon);
+                temp.class_info.values_mut().for_each(|x| {
+                    x.weight *= self.lambda;
+                    x.m2 *= lambda;
+                });
                 temp
             }
             None => GaussianNb {
                 class_info: HashMap::new(),
+                classes: Vec::new(),
+                var_smoothing: self.var_smoothing,
+                ignore_missing: self.ignore_missing,
+                tie_break: self.tie_break,
+                log_transform: self.log_transform,
             },
         };
+        model.var_smoothing = self.var_smoothing;
+        model.ignore_missing = self.ignore_missing;
+        model.tie_break = self.tie_break;
+        model.log_transform = self.log_transform;
+
+        // We seed the model with all declared classes, such that classes
+        // missing in the first batches are known as well
+        if let Some(classes) = &self.classes {
+            if let Some(idx) = y.iter().position(|x| !classes.contains(x)) {
+                return Err(BayesError::UndeclaredClass(idx));
+            }
+
+            for class in classes {
+                let order = model.class_info.len();
+                model
+                    .class_info
+                    .entry(class.clone())
+                    .or_insert_with(|| ClassInfo {
+                        order,
+                        theta: Array1::zeros(x.ncols()),
+                        shift: Array1::zeros(x.ncols()),
+                        mean: Array1::zeros(x.ncols()),
+                        m2: Array1::zeros(x.ncols()),
+                        sigma: Array1::zeros(x.ncols()),
+                        ..ClassInfo::default()
+                    });
+            }
+        }
+
+        // We register new classes in the order of their first record
+        for class in y {
+            if !model.class_info.contains_key(class) {
+                let order = model.class_info.len();
+                model.class_info.insert(
+                    class.clone(),
+                    ClassInfo {
+                        order,
+                        ..ClassInfo::default()
+                    },
+                );
+            }
+        }
 
-        let yunique = y.labels();
+        // We visit the classes in sorted order, such that errors refer to
+        // the same index regardless of the order of the label set. A label
+        // set declared with `Dataset::with_labels` may contain classes
+        // without any records
+        let mut yunique = dataset.targets().labels();
+        yunique.sort_unstable();
 
-        for class in yunique.iter() {
+        for (idx, class) in yunique.iter().enumerate() {
             // We filter x for records that correspond to the current class
-            let xclass = Self::filter(&x, y.as_slice(), *class);
+            let xclass = filter(&x, y, class);
 
             // We count the number of occurances of the class
             let nclass = xclass.nrows();
+            if nclass == 0 {
+                return Err(BayesError::EmptyClass(idx));
+            }
 
-            // We compute the update of the gaussian mean and variance
+            // We compute the update of the gaussian mean and sum of squared
+            // deviations
             let mut class_info = model
                 .class_info
-                .entry(*class)
+                .entry(class.clone())
                 .or_insert_with(ClassInfo::default);
-            let (theta_new, sigma_new) = Self::update_mean_variance(
-                class_info.class_count,
-                &class_info.theta.view(),
-                &class_info.sigma.view(),
-                &xclass,
-            );
 
-            // We now update the mean, variance and class count
-            class_info.theta = theta_new;
-            class_info.sigma = sigma_new;
+            // The mean of the first batch of a class is the reference point of
+            // all later updates, which keeps the accumulated statistics small
+            // for data with a large offset
+            if class_info.weight == 0.0 {
+                let (theta, m2) = Self::update_mean_m2(
+                    A::zero(),
+                    &class_info.theta.view(),
+                    &class_info.m2.view(),
+                    &xclass,
+                );
+                class_info.mean = Array1::zeros(theta.len());
+                class_info.shift = theta.clone();
+                class_info.theta = theta;
+                class_info.m2 = m2;
+            } else {
+                let (mean, m2) = Self::update_mean_m2(
+                    A::from(class_info.weight).unwrap(),
+                    &class_info.mean.view(),
+                    &class_info.m2.view(),
+                    &(xclass - &class_info.shift),
+                );
+                class_info.theta = &class_info.shift + &mean;
+                class_info.mean = mean;
+                class_info.m2 = m2;
+            }
+
             class_info.class_count += nclass;
+            class_info.weight += nclass as f64;
         }
 
-        // We add back the epsilon previously subtracted for numerical
-        // calculation stability
+        // We derive the variances from the accumulated statistics and add the
+        // epsilon for numerical calculation stability
         model
             .class_info
             .values_mut()
-            .for_each(|x| x.sigma += epsilon);
+            .for_each(|x| x.sigma = x.variance() + epsilon);
 
-        // We update the priors
-        let class_count_sum = model
-            .class_info
-            .values()
-            .fold(0, |acc, x| acc + x.class_count);
+        // We update the priors with the decayed number of observations
+        let weight_sum = model.class_info.values().map(|x| x.weight).sum::<f64>();
         for info in model.class_info.values_mut() {
-            info.prior = A::from(info.class_count).unwrap() / A::from(class_count_sum).unwrap();
+            info.prior = A::from(info.weight / weight_sum).unwrap();
         }
 
+        // We replace the per-class variance with the pooled one, the priors are
+        // exactly the weights of each class
+        if self.shared_variance {
+            let mut pooled = Array1::zeros(x.ncols());
+            for info in model.class_info.values() {
+                pooled.scaled_add(info.prior, &info.sigma);
+            }
+            for info in model.class_info.values_mut() {
+                info.sigma.assign(&pooled);
+            }
+        }
+
+        // We floor the variances independently of the smoothing
+        if let Some(min_variance) = self.min_variance {
+            let min_variance = A::from(min_variance).unwrap();
+            for info in model.class_info.values_mut() {
+                info.sigma.mapv_inplace(|x| x.max(min_variance));
+            }
+        }
+
+        model.classes = sorted_classes(&model.class_info);
+
         Ok(Some(model))
     }
 }
 
-impl GaussianNbParams {
-    // Compute online update of gaussian mean and variance
-    fn update_mean_variance<A: Float>(
-        count_old: usize,
+impl<L> GaussianNbParams<L> {
+    // Compute online update of gaussian mean and sum of squared deviations
+    //
+    // The previous statistics are weighted with the possibly fractional number
+    // of observations `count_old`
+    fn update_mean_m2<A: Float>(
+        count_old: A,
         mu_old: &ArrayView1<A>,
-        var_old: &ArrayView1<A>,
+        m2_old: &ArrayView1<A>,
         x_new: &Array2<A>,
     ) -> (Array1<A>, Array1<A>) {
         // If incoming data is empty no updates required
         if x_new.nrows() == 0 {
-            return (mu_old.to_owned(), var_old.to_owned());
+            return (mu_old.to_owned(), m2_old.to_owned());
         }
 
-        let count_new = x_new.nrows();
+        let count_new = A::from(x_new.nrows()).unwrap();
 
         // unwrap is safe because None is returned only when number of records
         // along the specified axis is 0, we return early if we have o rows
         let mu_new = x_new.mean_axis(Axis(0)).unwrap();
 
-        let var_new = x_new.var_axis(Axis(0), A::zero());
+        // The variance with `n - 1` delta degrees of freedom is the plain sum
+        // of squared deviations, accumulated with Welford's algorithm
+        let m2_new = x_new.var_axis(Axis(0), count_new - A::one());
 
-        // If previous batch was empty, we send the new mean and variance calculated
-        if count_old == 0 {
-            return (mu_new, var_new);
+        // If previous batch was empty, we send the new mean and sum of squared
+        // deviations calculated
+        if count_old == A::zero() {
+            return (mu_new, m2_new);
         }
 
-        let count_total = count_old + count_new;
-
-        // Combine old and new mean, taking into consideration the number
-        // of observations
-        let mu_new_weighted = &mu_new * A::from(count_new).unwrap();
-        let mu_old_weighted = mu_old * A::from(count_old).unwrap();
-        let mu_weighted =
-            (mu_new_weighted + mu_old_weighted).mapv(|x| x / A::from(count_total).unwrap());
-
-        // Combine old and new variance, taking into consideration the number
-        // of observations. this is achieved by combining the sum of squared
-        // differences
-        let ssd_old = var_old * A::from(count_old).unwrap();
-        let ssd_new = var_new * A::from(count_new).unwrap();
-        let weight = A::from(count_new * count_old).unwrap() / A::from(count_total).unwrap();
-        let ssd_weighted = ssd_old + ssd_new + (mu_old - &mu_new).mapv(|x| weight * x.powi(2));
-        let var_weighted = ssd_weighted.mapv(|x| x / A::from(count_total).unwrap());
-
-        (mu_weighted, var_weighted)
+        combine_mean_m2(
+            count_old,
+            mu_old,
+            m2_old,
+            count_new,
+            &mu_new.view(),
+            &m2_new.view(),
+        )
     }
+}
 
-    // Returns a subset of x corresponding to the class specified by `ycondition`
-    fn filter<A: Float>(x: &ArrayView2<A>, y: &[usize], ycondition: usize) -> Array2<A> {
-        // We identify the row numbers corresponding to the class we are interested in
-        let index = y
-            .iter()
-            .enumerate()
-            .filter_map(|(i, y)| {
-                if ycondition == *y {
-                    return Some(i);
-                }
-                None
-            })
-            .collect::<Vec<_>>();
+// Pool the mean and sum of squared deviations of two disjoint sets of
+// observations
+//
+// Each set is described by its (possibly fractional) number of observations,
+// its mean and its sum of squared deviations from the mean. This is the
+// parallel variant of Welford's algorithm by Chan et al., which only shifts
+// the mean by a fraction of the difference and never rescales a variance
+fn combine_mean_m2<A: Float>(
+    count_old: A,
+    mu_old: &ArrayView1<A>,
+    m2_old: &ArrayView1<A>,
+    count_new: A,
+    mu_new: &ArrayView1<A>,
+    m2_new: &ArrayView1<A>,
+) -> (Array1<A>, Array1<A>) {
+    let count_total = count_old + count_new;
+    let delta = mu_new - mu_old;
 
-        // We subset x to only records corresponding to the class represented in `ycondition`
-        let mut xsubset = Array2::zeros((index.len(), x.ncols()));
-        index
-            .iter()
-            .enumerate()
-            .for_each(|(i, &r)| xsubset.row_mut(i).assign(&x.slice(s![r, ..])));
+    // Combine old and new mean, taking into consideration the number of
+    // observations
+    let mu = (mu_new * count_new + mu_old * count_old).mapv(|x| x / count_total);
 
-        xsubset
-    }
+    // Combine old and new sum of squared deviations, corrected by the
+    // distance between both means
+    let weight = count_new * count_old / count_total;
+    let m2 = m2_old + m2_new + delta.mapv(|x| weight * x.powi(2));
+
+    (mu, m2)
 }
 
 /// Fitted GaussianNB for predicting classes
+///
+/// The classes are of type `L`, which defaults to `usize` for integer encoded
+/// targets
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate"),
+    serde(bound(deserialize = "A: Deserialize<'de>, L: Deserialize<'de> + Label"))
+)]
 #[derive(Debug, Clone)]
-pub struct GaussianNb<A> {
-    class_info: HashMap<usize, ClassInfo<A>>,
+pub struct GaussianNb<A, L = usize> {
+    class_info: HashMap<L, ClassInfo<A>>,
+    // Classes in ascending order, the column order of all matrix outputs
+    classes: Vec<L>,
+    // Smoothing of the variance used in the last fit
+    var_smoothing: f64,
+    // Whether NaN features are skipped in predictions
+    ignore_missing: bool,
+    // Resolution of ties between classes in predictions
+    tie_break: TieBreak,
+    // Whether the features are transformed with `ln(1 + x)`
+    log_transform: bool,
 }
 
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
 #[derive(Debug, Default, Clone)]
 struct ClassInfo<A> {
+    // Position of the class in the order of declaration or first observation
+    order: usize,
     class_count: usize,
+    // Number of observations decayed by the forgetting factor, accumulated in
+    // double precision such that long streams of `f32` records do not lose
+    // observations beyond the exactly representable integers of `f32`
+    weight: f64,
     prior: A,
     theta: Array1<A>,
+    // Reference point of the accumulated statistics
+    shift: Array1<A>,
+    // Mean of the deviations from `shift`
+    mean: Array1<A>,
+    // Sum of squared deviations from `theta`, decayed like `weight`
+    m2: Array1<A>,
+    // Variance used for predictions, derived from `m2` after each update
     sigma: Array1<A>,
 }
 
-impl<A: Float> Predict<ArrayView2<'_, A>, Array1<usize>> for GaussianNb<A> {
+impl<A: Float> ClassInfo<A> {
+    // Maximum likelihood variance of the accumulated observations
+    fn variance(&self) -> Array1<A> {
+        if self.weight > 0.0 {
+            let weight = A::from(self.weight).unwrap();
+            self.m2.mapv(|x| x / weight)
+        } else {
+            Array1::zeros(self.m2.len())
+        }
+    }
+}
+
+impl<A: Float, L: Label + Ord> Predict<ArrayView2<'_, A>, Array1<L>> for GaussianNb<A, L> {
     /// Perform classification on incoming array
     ///
-    /// __Panics__ if the input is empty or if pairwise orderings are undefined
-    /// (this occurs in presence of NaN values)
-    fn predict(&self, x: ArrayView2<'_, A>) -> Array1<usize> {
-        let joint_log_likelihood = self.joint_log_likelihood(x);
+    /// __Panics__ if the input is empty, if the number of features differs
+    /// from the training data or if pairwise orderings are undefined (this
+    /// occurs in presence of NaN values), use `try_predict` to handle these
+    /// cases
+    fn predict(&self, x: ArrayView2<'_, A>) -> Array1<L> {
+        self.try_predict(x).unwrap()
+    }
+}
 
-        // We store the classes and likelihood info in an vec and matrix
-        // respectively for easier identification of the dominant class for
-        // each input
-        let nclasses = joint_log_likelihood.keys().len();
-        let n = x.nrows();
-        let mut classes = Vec::with_capacity(nclasses);
-        let mut likelihood = Array2::zeros((nclasses, n));
-        joint_log_likelihood
-            .iter()
-            .enumerate()
-            .for_each(|(i, (&&key, value))| {
-                classes.push(key);
-                likelihood.row_mut(i).assign(value);
-            });
+/// Evaluate the model with the metrics of `linfa`
+///
+/// The class probabilities are those of `predict_proba` with the columns
+/// ordered like `classes_sorted`.
+impl<A: Float, L: Label + Ord> Classifier<A, L> for GaussianNb<A, L> {
+    fn predict_labels(&self, x: ArrayView2<A>) -> Vec<L> {
+        self.predict(x).to_vec()
+    }
 
-        // Identify the class with the maximum log likelihood
-        likelihood.map_axis(Axis(0), |x| {
-            let i = x.argmax().unwrap();
-            *classes.get(i).unwrap()
-        })
+    fn predict_class_proba(&self, x: ArrayView2<A>) -> (Array2<A>, Vec<L>) {
+        (self.predict_proba(x), self.classes.clone())
     }
 }
 
-impl<A: Float> GaussianNb<A> {
-    // Compute unnormalized posterior log probability
-    fn joint_log_likelihood(&self, x: ArrayView2<A>) -> HashMap<&usize, Array1<A>> {
-        let mut joint_log_likelihood = HashMap::new();
+/// Transform non-negative features with `ln(1 + x)`
+fn log_transform<A: Float>(x: ArrayView2<A>) -> Result<Array2<A>> {
+    if x.iter().any(|x| *x < A::zero()) {
+        return Err(BayesError::NegativeValue);
+    }
 
-        for (class, info) in self.class_info.iter() {
-            let jointi = info.prior.ln();
+    Ok(x.mapv(|x| x.ln_1p()))
+}
 
-            let mut nij = info
-                .sigma
-                .mapv(|x| A::from(2. * std::f64::consts::PI).unwrap() * x)
-                .mapv(|x| x.ln())
-                .sum();
-            nij = A::from(-0.5).unwrap() * nij;
+/// Keys of the class map in ascending order
+fn sorted_classes<A, L: Label + Ord>(class_info: &HashMap<L, ClassInfo<A>>) -> Vec<L> {
+    let mut classes = class_info.keys().cloned().collect::<Vec<_>>();
+    classes.sort_unstable();
 
-            let nij = ((x.to_owned() - &info.theta).mapv(|x| x.powi(2)) / &info.sigma)
-                .sum_axis(Axis(1))
-                .mapv(|x| x * A::from(0.5).unwrap())
-                .mapv(|x| nij - x);
+    classes
+}
 
-            joint_log_likelihood.insert(class, nij + jointi);
-        }
+impl<A: Float, L: Label + Ord> GaussianNb<A, L> {
+    /// Classes seen during fitting in ascending order
+    ///
+    /// This is the column order of the matrices returned by `predict_proba`
+    /// and `predict_log_proba`.
+    pub fn classes(&self) -> Vec<L> {
+        self.classes.clone()
+    }
 
-        joint_log_likelihood
+    /// Classes seen during fitting in ascending order, without copying them
+    ///
+    /// The order does not depend on the order in which classes were
+    /// observed, such that two models fitted on the same data produce
+    /// identical matrix outputs.
+    pub fn classes_sorted(&self) -> &[L] {
+        &self.classes
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use approx::assert_abs_diff_eq;
-    use linfa::Dataset;
-    use ndarray::array;
+    /// Mean of each feature for the given class
+    pub fn theta(&self, class: &L) -> Option<ArrayView1<'_, A>> {
+        self.class_info.get(class).map(|info| info.theta.view())
+    }
 
-    #[test]
-    fn test_gaussian_nb() {
-        let x = array![
-            [-2., -1.],
-            [-1., -1.],
-            [-1., -2.],
-            [1., 1.],
-            [1., 2.],
-            [2., 1.]
-        ];
-        let y = array![1, 1, 1, 2, 2, 2];
+    /// Variance of each feature for the given class
+    pub fn sigma(&self, class: &L) -> Option<ArrayView1<'_, A>> {
+        self.class_info.get(class).map(|info| info.sigma.view())
+    }
 
-        let clf = GaussianNbParams::params();
-        let data = Dataset::new(x.view(), y.view());
-        let fitted_clf = clf.fit(&data).unwrap();
-        let pred = fitted_clf.predict(x.view());
-        assert_eq!(pred, y);
+    /// Portion of the largest feature variance added to all variances
+    ///
+    /// This is the value selected by `var_smoothing_search`, if candidates
+    /// were given.
+    pub fn var_smoothing(&self) -> f64 {
+        self.var_smoothing
+    }
 
-        let jll = fitted_clf.joint_log_likelihood(x.view());
-        let mut expected = HashMap::new();
-        expected.insert(
-            &1usize,
-            array![
-                -2.276946847943017,
-                -1.5269468546930165,
-                -2.276946847943017,
-                -25.52694663869301,
-                -38.27694652394301,
-                -38.27694652394301
-            ],
-        );
-        expected.insert(
-            &2usize,
-            array![
-                -38.27694652394301,
-                -25.52694663869301,
-                -38.27694652394301,
-                -1.5269468546930165,
-                -2.276946847943017,
-                -2.276946847943017
-            ],
-        );
+    /// Contribution of each feature of `x` to the log likelihood of the given class
+    ///
+    /// These are the terms `-(x_i - theta_i)^2 / (2 sigma_i)`, which are summed up in the joint
+    /// log likelihood. Adding the log prior and the normalization
+    /// `-0.5 * sum_i ln(2 pi sigma_i)` recovers the joint log likelihood of the sample.
+    /// Returns `None` for an unknown class. With the log transform enabled
+    /// the contributions are computed for the transformed features.
+    ///
+    /// __Panics__ if the number of features differs from the training data
+    pub fn feature_contributions(&self, x: ArrayView1<A>, class: &L) -> Option<Array1<A>> {
+        let x = if self.log_transform {
+            x.mapv(|x| x.ln_1p())
+        } else {
+            x.to_owned()
+        };
 
-        assert_eq!(jll, expected);
+        self.class_info.get(class).map(|info| {
+            (&x - &info.theta).mapv(|x| x.powi(2)) / &info.sigma * A::from(-0.5).unwrap()
+        })
     }
 
-    #[test]
-    fn test_gnb_fit_with() {
-        let x = array![
-            [-2., -1.],
-            [-1., -1.],
-            [-1., -2.],
-            [1., 1.],
-            [1., 2.],
-            [2., 1.]
-        ];
-        let y = array![1, 1, 1, 2, 2, 2];
+    /// Fisher score of each feature, the between-class variance of the means
+    /// `theta` over the pooled within-class variance `sigma`
+    ///
+    /// Both variances are weighted by the class priors and the within-class
+    /// variances include the smoothing. Features with a high score separate
+    /// the classes well, features whose means agree for all classes score
+    /// zero. Declared classes without observations are ignored.
+    pub fn feature_fisher_scores(&self) -> Array1<A> {
+        let observed = self
+            .class_info
+            .values()
+            .filter(|info| info.weight > 0.0)
+            .collect::<Vec<_>>();
+        let nfeatures = observed.first().map_or(0, |info| info.theta.len());
 
-        let clf = GaussianNbParams::params();
+        let mut mean = Array1::zeros(nfeatures);
+        let mut within = Array1::zeros(nfeatures);
+        for info in &observed {
+            mean.scaled_add(info.prior, &info.theta);
+            within.scaled_add(info.prior, &info.sigma);
+        }
 
-        let model = x
-            .axis_chunks_iter(Axis(0), 2)
-            .zip(y.axis_chunks_iter(Axis(0), 2))
-            .map(|(a, b)| Dataset::new(a, b))
-            .fold(None, |current, d| clf.fit_with(current, &d).unwrap())
-            .unwrap();
+        let mut between = Array1::zeros(nfeatures);
+        for info in &observed {
+            between.scaled_add(info.prior, &(&info.theta - &mean).mapv(|x| x * x));
+        }
 
-        let pred = model.predict(x.view());
+        between / within
+    }
 
-        assert_eq!(pred, y);
+    /// Prior probability of the given class
+    pub fn prior(&self, class: &L) -> Option<A> {
+        self.class_info.get(class).map(|info| info.prior)
+    }
+
+    /// Number of training samples observed for the given class
+    pub fn class_count(&self, class: &L) -> Option<usize> {
+        self.class_info.get(class).map(|info| info.class_count)
+    }
+
+    /// Number of training samples observed for each class
+    ///
+    /// The counts accumulate over incremental fits and are not decayed by
+    /// `lambda`, declared classes without observations have a count of zero.
+    pub fn class_counts(&self) -> HashMap<L, usize> {
+        self.class_info
+            .iter()
+            .map(|(class, info)| (class.clone(), info.class_count))
+            .collect()
+    }
+
+    /// Combine two models fitted on disjoint parts of a dataset
+    ///
+    /// The statistics of classes known to both models are pooled, classes
+    /// seen by only one of them are copied over, and the priors are recomputed
+    /// from the merged counts. The variances are pooled as stored, so both
+    /// models should use the same `var_smoothing` for the result to match a
+    /// single fit on the whole dataset.
+    ///
+    /// The merged model keeps `var_smoothing`, `ignore_missing`, the tie
+    /// break and the log transform of `self`, the settings of `other` are
+    /// ignored.
+    pub fn merge(&self, other: &GaussianNb<A, L>) -> GaussianNb<A, L> {
+        let mut class_info = self.class_info.clone();
+
+        // Classes only known to `other` are appended in their original order
+        let mut other_classes = other.class_info.iter().collect::<Vec<_>>();
+        other_classes.sort_by_key(|(_, info)| info.order);
+
+        for (class, info) in other_classes {
+            match class_info.get_mut(class) {
+                // A class without observations in `other` leaves the
+                // statistics unchanged
+                Some(_) if info.weight == 0.0 => {}
+                // A class without observations in this model takes the
+                // statistics of `other`, pooling them would divide by zero
+                Some(current) if current.weight == 0.0 => {
+                    *current = ClassInfo {
+                        order: current.order,
+                        ..info.clone()
+                    };
+                }
+                Some(current) => {
+                    let (current_weight, info_weight) = (
+                        A::from(current.weight).unwrap(),
+                        A::from(info.weight).unwrap(),
+                    );
+
+                    // The mean of `other` is moved to the reference point of
+                    // this model first
+                    let (mean, m2) = combine_mean_m2(
+                        current_weight,
+                        &current.mean.view(),
+                        &current.m2.view(),
+                        info_weight,
+                        &(&info.mean + &(&info.shift - &current.shift)).view(),
+                        &info.m2.view(),
+                    );
+
+                    // The variances used for predictions are pooled as
+                    // stored, which keeps their smoothing
+                    let (_, ssd) = combine_mean_m2(
+                        current_weight,
+                        &current.theta.view(),
+                        &(&current.sigma * current_weight).view(),
+                        info_weight,
+                        &info.theta.view(),
+                        &(&info.sigma * info_weight).view(),
+                    );
+                    let weight = current_weight + info_weight;
+
+                    current.theta = &current.shift + &mean;
+                    current.mean = mean;
+                    current.m2 = m2;
+                    current.sigma = ssd.mapv(|x| x / weight);
+                    current.class_count += info.class_count;
+                    current.weight += info.weight;
+                }
+                None => {
+                    let order = class_info.len();
+                    class_info.insert(
+                        class.clone(),
+                        ClassInfo {
+                            order,
+                            ..info.clone()
+                        },
+                    );
+                }
+            }
+        }
+
+        let weight_sum = class_info.values().map(|x| x.weight).sum::<f64>();
+        for info in class_info.values_mut() {
+            info.prior = A::from(info.weight / weight_sum).unwrap();
+        }
+
+        GaussianNb {
+            classes: sorted_classes(&class_info),
+            class_info,
+            var_smoothing: self.var_smoothing,
+            ignore_missing: self.ignore_missing,
+            tie_break: self.tie_break,
+            log_transform: self.log_transform,
+        }
+    }
+
+    /// Forget all classes and statistics, returning the model to its state before the first fit
+    ///
+    /// The model can then be passed to `fit_with` again, which behaves as if it was given `None`.
+    pub fn reset(&mut self) {
+        self.class_info.clear();
+        self.classes.clear();
+    }
+
+    /// Perform classification on incoming array
+    ///
+    /// Returns `BayesError::EmptyInput` if the input is empty,
+    /// `BayesError::FeatureCountMismatch` if the number of features differs
+    /// from the training data and `BayesError::InvalidValue` if pairwise
+    /// orderings are undefined (this occurs in presence of NaN values)
+    pub fn try_predict(&self, x: ArrayView2<A>) -> Result<Array1<L>> {
+        if x.nrows() == 0 {
+            return Err(BayesError::EmptyInput);
+        }
+        self.check_input(x)?;
+
+        self.predict_classes(x)
+    }
+
+    // Ensure that `x` has the number of features of the training data, a
+    // model without classes accepts any number, and that the features are
+    // non-negative for the log transform
+    fn check_input(&self, x: ArrayView2<A>) -> Result<()> {
+        match self.class_info.values().next() {
+            Some(info) if info.theta.len() != x.ncols() => Err(BayesError::FeatureCountMismatch {
+                expected: info.theta.len(),
+                found: x.ncols(),
+            }),
+            _ if self.log_transform && x.iter().any(|x| *x < A::zero()) => {
+                Err(BayesError::NegativeValue)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Perform classification on incoming array in batches of `batch_size` rows
+    ///
+    /// The predictions are identical to `predict`, but the likelihoods are
+    /// only kept for one batch at a time, which bounds the memory of large
+    /// inputs to `O(batch_size * nclasses)`.
+    ///
+    /// __Panics__ if `batch_size` is zero, and for the same reasons as
+    /// `predict`
+    pub fn predict_batched(&self, x: ArrayView2<A>, batch_size: usize) -> Array1<L> {
+        assert!(batch_size > 0, "Batch size has to be positive");
+
+        x.axis_chunks_iter(Axis(0), batch_size)
+            .flat_map(|batch| self.predict(batch).to_vec())
+            .collect()
+    }
+
+    /// Classify a single observation
+    ///
+    /// __Panics__ for the same reasons as `predict`
+    pub fn predict_one(&self, x: ArrayView1<A>) -> L {
+        self.predict(x.insert_axis(Axis(0)))[0].clone()
+    }
+
+    /// Fraction of correctly classified samples in the incoming array
+    ///
+    /// __Panics__ if the number of samples and targets differ, or for the same
+    /// reasons as `predict`
+    pub fn score(&self, x: ArrayView2<A>, y: &[L]) -> A {
+        assert_eq!(
+            x.nrows(),
+            y.len(),
+            "number of samples and targets have to be equal"
+        );
+
+        let pred = self.predict(x);
+        let correct = pred.iter().zip(y.iter()).filter(|(a, b)| a == b).count();
+
+        A::from(correct).unwrap() / A::from(y.len()).unwrap()
+    }
+
+    /// Log of the marginal likelihood `ln(sum_c P(x|c) P(c))` of each sample
+    ///
+    /// This is the log density of the fitted mixture of class Gaussians and
+    /// independent of the class assignment. Samples far from all classes have
+    /// a low likelihood, which makes it a score for anomalies.
+    pub fn log_likelihood(&self, x: ArrayView2<A>) -> Array1<A> {
+        let joint_log_likelihood = self.joint_log_likelihood(x);
+
+        let mut likelihood = Array2::zeros((x.nrows(), self.classes.len()));
+        for (i, class) in self.classes.iter().enumerate() {
+            likelihood
+                .column_mut(i)
+                .assign(joint_log_likelihood.get(class).unwrap());
+        }
+
+        log_sum_exp(likelihood.view())
+    }
+
+    /// Classify the incoming array, but abstain for uncertain samples
+    ///
+    /// Returns `None` for samples whose most probable class has a
+    /// probability below `min_prob`, and for samples with undefined
+    /// probabilities. Ties are resolved like in `predict`.
+    pub fn predict_with_threshold(&self, x: ArrayView2<A>, min_prob: A) -> Array1<Option<L>> {
+        self.predict_proba(x)
+            .outer_iter()
+            .map(|row| {
+                let classes = self.classes.iter().collect::<Vec<_>>();
+                let best = argmax_class(row, &classes, |a, b| self.wins_tie(a, b))?;
+
+                if row[best] >= min_prob {
+                    Some(self.classes[best].clone())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+impl<A: Float, L: Label + Ord> NaiveBayes<A, L> for GaussianNb<A, L> {
+    /// Ties are resolved with the tie-breaking strategy of the parameters
+    fn wins_tie(&self, a: &L, b: &L) -> bool {
+        let (a, b) = (&self.class_info[a], &self.class_info[b]);
+
+        match self.tie_break {
+            TieBreak::LowestClass => false,
+            TieBreak::HighestPrior => a.prior > b.prior,
+            TieBreak::First => a.order < b.order,
+        }
+    }
+
+    /// __Panics__ if the number of features differs from the training data,
+    /// or if a feature is negative while the log transform is enabled
+    fn joint_log_likelihood(&self, x: ArrayView2<A>) -> HashMap<&L, Array1<A>> {
+        if let Err(err) = self.check_input(x) {
+            panic!("{}", err);
+        }
+
+        let transformed;
+        let x = if self.log_transform {
+            transformed = x.mapv(|x| x.ln_1p());
+            transformed.view()
+        } else {
+            x.view()
+        };
+
+        let mut joint_log_likelihood = HashMap::new();
+
+        for (class, info) in self.class_info.iter() {
+            // A declared class without observations is impossible
+            if info.weight == 0.0 {
+                joint_log_likelihood.insert(class, Array1::from_elem(x.nrows(), A::neg_infinity()));
+                continue;
+            }
+
+            let jointi = info.prior.ln();
+
+            // Missing features are marginalized out, which drops their terms
+            // from both the normalization and the sum of squares
+            if self.ignore_missing {
+                let nij = x.map_axis(Axis(1), |row| {
+                    let sum = row
+                        .iter()
+                        .zip(info.theta.iter().zip(info.sigma.iter()))
+                        .filter(|(x, _)| !x.is_nan())
+                        .fold(A::zero(), |acc, (x, (theta, sigma))| {
+                            let norm = (A::from(2. * std::f64::consts::PI).unwrap() * *sigma).ln();
+                            acc + norm + (*x - *theta).powi(2) / *sigma
+                        });
+
+                    A::from(-0.5).unwrap() * sum
+                });
+
+                joint_log_likelihood.insert(class, nij + jointi);
+                continue;
+            }
+
+            let mut nij = info
+                .sigma
+                .mapv(|x| A::from(2. * std::f64::consts::PI).unwrap() * x)
+                .mapv(|x| x.ln())
+                .sum();
+            nij = A::from(-0.5).unwrap() * nij;
+
+            let nij = ((&x - &info.theta).mapv(|x| x.powi(2)) / &info.sigma)
+                .sum_axis(Axis(1))
+                .mapv(|x| x * A::from(0.5).unwrap())
+                .mapv(|x| nij - x);
+
+            joint_log_likelihood.insert(class, nij + jointi);
+        }
+
+        joint_log_likelihood
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use linfa::metrics::{log_loss, Accuracy, LogLoss, ToConfusionMatrix};
+    use linfa::Dataset;
+    use ndarray::{array, aview1, s};
+    use rand::{seq::SliceRandom, Rng, SeedableRng};
+    use rand_isaac::Isaac64Rng;
+
+    // Two classes of three records, which are symmetric around the origin
+    fn fixture() -> (Array2<f64>, Array1<usize>) {
+        let x = array![
+            [-2., -1.],
+            [-1., -1.],
+            [-1., -2.],
+            [1., 1.],
+            [1., 2.],
+            [2., 1.]
+        ];
+        let y = array![1, 1, 1, 2, 2, 2];
+
+        (x, y)
+    }
+
+    #[test]
+    fn test_gaussian_nb() {
+        let (x, y) = fixture();
+
+        let clf = GaussianNbParams::params();
+        let data = Dataset::new(x.view(), y.view());
+        let fitted_clf = clf.fit(&data).unwrap();
+        let pred = fitted_clf.predict(x.view());
+        assert_eq!(pred, y);
+
+        let jll = fitted_clf.joint_log_likelihood(x.view());
+        let mut expected = HashMap::new();
+        expected.insert(
+            &1usize,
+            array![
+                -2.276946847943017,
+                -1.5269468546930165,
+                -2.276946847943017,
+                -25.52694663869301,
+                -38.27694652394301,
+                -38.27694652394301
+            ],
+        );
+        expected.insert(
+            &2usize,
+            array![
+                -38.27694652394301,
+                -25.52694663869301,
+                -38.27694652394301,
+                -1.5269468546930165,
+                -2.276946847943017,
+                -2.276946847943017
+            ],
+        );
+
+        assert_eq!(jll, expected);
+    }
+
+    #[test]
+    fn test_gnb_params() {
+        let (x, _) = fixture();
+        let y = array![1, 1, 1, 1, 2, 2];
+
+        let data = Dataset::new(x.view(), y.view());
+        let model = GaussianNbParams::params()
+            .var_smoothing(0.0)
+            .fit(&data)
+            .unwrap();
+
+        assert_eq!(model.classes(), vec![1, 2]);
+        assert_eq!(model.class_count(&1), Some(4));
+        assert_eq!(model.class_count(&2), Some(2));
+        assert_abs_diff_eq!(model.prior(&1).unwrap(), 4. / 6.);
+        assert_abs_diff_eq!(model.prior(&2).unwrap(), 2. / 6.);
+
+        assert_abs_diff_eq!(model.theta(&1).unwrap(), array![-0.75, -0.75]);
+        assert_abs_diff_eq!(model.theta(&2).unwrap(), array![1.5, 1.5]);
+        assert_abs_diff_eq!(model.sigma(&1).unwrap(), array![1.1875, 1.1875]);
+        assert_abs_diff_eq!(model.sigma(&2).unwrap(), array![0.25, 0.25]);
+
+        assert!(model.theta(&3).is_none());
+        assert!(model.prior(&3).is_none());
+    }
+
+    #[test]
+    fn test_gnb_merge() {
+        let x = array![
+            [-2., -1.],
+            [1., 2.],
+            [-1., -1.],
+            [2., 1.],
+            [-1., -2.],
+            [-3., 0.],
+            [1., 1.],
+            [-2., -2.]
+        ];
+        let y = array![1, 2, 1, 2, 1, 3, 2, 1];
+        let params = GaussianNbParams::params().var_smoothing(0.0);
+
+        let full = params.fit(&Dataset::new(x.view(), y.view())).unwrap();
+
+        let (x1, x2) = x.view().split_at(Axis(0), 4);
+        let (y1, y2) = y.view().split_at(Axis(0), 4);
+        let first = params.fit(&Dataset::new(x1, y1)).unwrap();
+        let second = params.fit(&Dataset::new(x2, y2)).unwrap();
+        let merged = first.merge(&second);
+
+        assert_eq!(merged.classes(), full.classes());
+        for class in full.classes() {
+            assert_eq!(merged.class_count(&class), full.class_count(&class));
+            assert_abs_diff_eq!(
+                merged.prior(&class).unwrap(),
+                full.prior(&class).unwrap(),
+                epsilon = 1e-12
+            );
+            assert_abs_diff_eq!(
+                merged.theta(&class).unwrap(),
+                full.theta(&class).unwrap(),
+                epsilon = 1e-12
+            );
+            assert_abs_diff_eq!(
+                merged.sigma(&class).unwrap(),
+                full.sigma(&class).unwrap(),
+                epsilon = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn test_gnb_merge_unobserved_class() {
+        let x = array![[-2f64, -1.], [-1., -1.], [1., 2.], [2., 1.]];
+        let y = array![1, 1, 2, 2];
+        let params = GaussianNbParams::params()
+            .with_classes(vec![1, 2, 3])
+            .allow_single_class(true);
+
+        // class 3 is declared, but observed by neither model
+        let (x1, x2) = x.view().split_at(Axis(0), 2);
+        let (y1, y2) = y.view().split_at(Axis(0), 2);
+        let first = params.fit(&Dataset::new(x1, y1)).unwrap();
+        let second = params.fit(&Dataset::new(x2, y2)).unwrap();
+        let merged = first.merge(&second);
+
+        assert_eq!(merged.classes(), vec![1, 2, 3]);
+        assert_eq!(merged.class_count(&3), Some(0));
+        for class in &[1, 2, 3] {
+            assert!(merged.theta(class).unwrap().iter().all(|x| x.is_finite()));
+            assert!(merged.sigma(class).unwrap().iter().all(|x| x.is_finite()));
+        }
+
+        // the first observations of class 3 continue from the merged model
+        let x3 = array![[5f64, 5.], [6., 4.]];
+        let y3 = array![3, 3];
+        let model = params
+            .fit_with(Some(merged), &Dataset::new(x3.view(), y3.view()))
+            .unwrap()
+            .unwrap();
+
+        assert_abs_diff_eq!(model.theta(&3).unwrap(), array![5.5, 4.5]);
+        let predicted = model.predict(array![[-1.5, -1.], [1.5, 1.5], [5.5, 4.5]].view());
+        assert_eq!(predicted, array![1, 2, 3]);
+        let proba = model.predict_proba(x3.view());
+        assert!(proba.iter().all(|x| x.is_finite()));
+    }
+
+    #[test]
+    fn test_gnb_shared_variance() {
+        let x = array![
+            [-0.1],
+            [0.],
+            [0.1],
+            [0.05],
+            [-0.05],
+            [0.],
+            [6.],
+            [3.],
+            [1.],
+            [5.]
+        ];
+        let y = array![1, 1, 1, 1, 1, 2, 2, 2, 2, 2];
+        let data = Dataset::new(x.view(), y.view());
+
+        let separate = GaussianNbParams::params().fit(&data).unwrap();
+        let shared = GaussianNbParams::params()
+            .shared_variance(true)
+            .fit(&data)
+            .unwrap();
+
+        assert_abs_diff_eq!(shared.sigma(&1).unwrap(), shared.sigma(&2).unwrap());
+        assert_abs_diff_eq!(
+            shared.sigma(&1).unwrap()[0],
+            (separate.sigma(&1).unwrap()[0] + separate.sigma(&2).unwrap()[0]) / 2.,
+            epsilon = 1e-12
+        );
+
+        let test = array![[1.]];
+        assert_eq!(separate.predict(test.view()), array![2]);
+        assert_eq!(shared.predict(test.view()), array![1]);
+    }
+
+    #[test]
+    fn test_gnb_predict_one() {
+        let (x, y) = fixture();
+
+        let data = Dataset::new(x.view(), y.view());
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+        let pred = model.predict(x.view());
+        for (row, expected) in x.outer_iter().zip(pred.iter()) {
+            assert_eq!(model.predict_one(row), *expected);
+        }
+    }
+
+    #[test]
+    fn test_gnb_predict_batched() {
+        let (x, y) = fixture();
+
+        let data = Dataset::new(x.view(), y.view());
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let test = Array2::from_shape_simple_fn((50, 2), || rng.gen_range(-3., 3.));
+        let expected = model.predict(test.view());
+        for batch_size in &[1, 4, 7, 50, 100] {
+            assert_eq!(model.predict_batched(test.view(), *batch_size), expected);
+        }
+        assert_eq!(model.predict_batched(x.view(), 4), y);
+    }
+
+    #[test]
+    fn test_gnb_log_likelihood() {
+        let (x, y) = fixture();
+
+        let data = Dataset::new(x.view(), y.view());
+        let model: GaussianNb<f64, usize> = GaussianNbParams::params().fit(&data).unwrap();
+
+        // the marginal of the joint log likelihoods
+        let likelihood = model.log_likelihood(x.view());
+        let jll = model.joint_log_likelihood(x.view());
+        for i in 0..6 {
+            let expected = (jll[&1][i].exp() + jll[&2][i].exp()).ln();
+            assert_abs_diff_eq!(likelihood[i], expected, epsilon = 1e-12);
+        }
+
+        // an outlier far from both classes
+        let outlier = model.log_likelihood(array![[20., -20.]].view());
+        let min = likelihood.fold(f64::INFINITY, |acc, x| acc.min(*x));
+        assert!(outlier[0] < min - 100.);
+        assert!(outlier[0].is_finite());
+    }
+
+    #[test]
+    fn test_gnb_score_with() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // two overlapping classes, such that some samples are misclassified
+        let x = Array2::from_shape_fn((40, 2), |(i, _)| {
+            rng.gen_range(-1.0, 1.0) + if i < 20 { -0.5 } else { 0.5 }
+        });
+        let y = (0..40).map(|i| i / 20).collect::<Array1<usize>>();
+
+        let data = Dataset::new(x.view(), y.view());
+        let model: GaussianNb<f64, usize> = GaussianNbParams::params().fit(&data).unwrap();
+        let y = y.as_slice().unwrap();
+
+        let accuracy = model.score_with(x.view(), y, Accuracy);
+        let cm = model.predict(x.view()).confusion_matrix(aview1(y));
+        assert!(accuracy < 1.);
+        assert_abs_diff_eq!(accuracy, model.score(x.view(), y), epsilon = 1e-12);
+        assert_abs_diff_eq!(accuracy, cm.accuracy() as f64, epsilon = 1e-6);
+
+        let loss = model.score_with(x.view(), y, LogLoss);
+        let expected = log_loss(model.predict_proba(x.view()).view(), y, &[0, 1]);
+        assert_abs_diff_eq!(loss, expected, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_gnb_feature_fisher_scores() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // the first feature separates the classes, the second one is noise
+        let x = Array2::from_shape_fn((100, 2), |(i, j)| {
+            let noise = rng.gen_range(-1.0, 1.0);
+            match (j, i < 50) {
+                (0, true) => noise - 5.,
+                (0, false) => noise + 5.,
+                _ => noise,
+            }
+        });
+        let y = (0..100).map(|i| i / 50).collect::<Array1<usize>>();
+
+        let data = Dataset::new(x.view(), y.view());
+        let model: GaussianNb<f64, usize> = GaussianNbParams::params().fit(&data).unwrap();
+        let scores = model.feature_fisher_scores();
+
+        assert_eq!(scores.len(), 2);
+        assert!(scores[0] > 10.);
+        assert!(scores[0] > 1000. * scores[1]);
+
+        // the score of a single feature from the class statistics
+        let (theta0, theta1) = (model.theta(&0).unwrap(), model.theta(&1).unwrap());
+        let (sigma0, sigma1) = (model.sigma(&0).unwrap(), model.sigma(&1).unwrap());
+        let between = (theta0[0] - theta1[0]).powi(2) / 4.;
+        let within = (sigma0[0] + sigma1[0]) / 2.;
+        assert_abs_diff_eq!(scores[0], between / within, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_gnb_log_transform() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // log-normal features, the logs of both classes have the same mean
+        // but a different spread
+        let mut sample = || {
+            let mut class = |spread: f64| {
+                Array2::from_shape_fn((100, 2), |_| {
+                    let (u, v): (f64, f64) = (rng.gen_range(1e-12, 1.0), rng.gen());
+                    let normal = (-2. * u.ln()).sqrt() * (2. * std::f64::consts::PI * v).cos();
+                    (spread * normal).exp()
+                })
+            };
+            let (a, b) = (class(0.3), class(1.5));
+            ndarray::stack(Axis(0), &[a.view(), b.view()]).unwrap()
+        };
+        let (x_train, x_test) = (sample(), sample());
+        let y = (0..200).map(|i| i / 100).collect::<Array1<usize>>();
+
+        let data = Dataset::new(x_train.view(), y.view());
+        let raw: GaussianNb<f64, usize> = GaussianNbParams::params().fit(&data).unwrap();
+        let log: GaussianNb<f64, usize> = GaussianNbParams::params()
+            .log_transform(true)
+            .fit(&data)
+            .unwrap();
+
+        let y = y.as_slice().unwrap();
+        let (raw, log) = (raw.score(x_test.view(), y), log.score(x_test.view(), y));
+        assert!(log > raw + 0.05, "{} {}", raw, log);
+
+        // negative features are rejected
+        let negative = array![[1., -1.], [2., 3.]];
+        let res = GaussianNbParams::params()
+            .log_transform(true)
+            .fit(&Dataset::new(negative.view(), array![0, 1].view()));
+        assert!(matches!(res, Err(BayesError::NegativeValue)));
+    }
+
+    #[test]
+    fn test_gnb_predict_with_threshold() {
+        let (x, y) = fixture();
+
+        let data = Dataset::new(x.view(), y.view());
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+
+        // the origin lies between both class means, the others are close to one
+        let test = array![[0., 0.], [-1.5, -1.5], [1.5, 1.5]];
+        let pred = model.predict_with_threshold(test.view(), 0.9);
+        assert_eq!(pred, array![None, Some(1), Some(2)]);
+
+        // without a threshold every sample is classified like in `predict`
+        let pred = model.predict_with_threshold(test.view(), 0.0);
+        let expected = model.predict(test.view()).mapv(Some);
+        assert_eq!(pred, expected);
+    }
+
+    #[test]
+    fn test_gnb_tie_break() {
+        // mirrored classes, the origin has exactly the same likelihood for both
+        let x = array![[1.], [2.], [-2.], [-1.]];
+        let y = array![2, 2, 1, 1];
+        let data = Dataset::new(x.view(), y.view());
+        let test = array![[0.], [-1.5], [1.5]];
+
+        for _ in 0..10 {
+            let model = GaussianNbParams::params().fit(&data).unwrap();
+            let proba = model.predict_proba(test.slice(s![..1, ..]));
+            assert_eq!(proba[(0, 0)], proba[(0, 1)]);
+            assert_eq!(model.predict(test.view()), array![1, 1, 2]);
+
+            // the priors are equal as well
+            let model = GaussianNbParams::params()
+                .tie_break(TieBreak::HighestPrior)
+                .fit(&data)
+                .unwrap();
+            assert_eq!(model.predict(test.view()), array![1, 1, 2]);
+
+            // class 2 is observed first
+            let model = GaussianNbParams::params()
+                .tie_break(TieBreak::First)
+                .fit(&data)
+                .unwrap();
+            assert_eq!(model.predict(test.view()), array![2, 1, 2]);
+            assert_eq!(
+                model.predict_with_threshold(test.view(), 0.5),
+                array![Some(2), Some(1), Some(2)]
+            );
+
+            // declared classes take precedence over the order of the records
+            let model = GaussianNbParams::params()
+                .tie_break(TieBreak::First)
+                .with_classes(vec![1, 2])
+                .fit(&data)
+                .unwrap();
+            assert_eq!(model.predict(test.view()), array![1, 1, 2]);
+        }
+    }
+
+    #[test]
+    fn test_gnb_var_smoothing_search() {
+        // The second feature is constant within the first class, except for a
+        // single record. Once this record is held out, the variance of the
+        // first class collapses to the smoothing and the record is
+        // misclassified, unless the smoothing is large.
+        let x = array![
+            [-2.0, 0.],
+            [-1.8, 0.],
+            [-1.6, 0.],
+            [-1.4, 0.],
+            [-1.2, 0.],
+            [-1.0, 1.],
+            [1.0, 0.],
+            [1.2, 1.],
+            [1.4, 0.],
+            [1.6, 1.],
+            [1.8, 0.],
+            [2.0, 1.]
+        ];
+        let y = array![0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1];
+        let data = Dataset::new(x.view(), y.view());
+
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+        assert_abs_diff_eq!(model.var_smoothing(), 1e-9);
+
+        let model = GaussianNbParams::params()
+            .var_smoothing_search(&[1e-9, 1e-3, 1.0])
+            .seed(0)
+            .fit(&data)
+            .unwrap();
+        assert_abs_diff_eq!(model.var_smoothing(), 1.0);
+        assert_abs_diff_eq!(model.score(x.view(), y.as_slice().unwrap()), 1.0);
+    }
+
+    #[test]
+    fn test_gnb_seed() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::from_shape_fn((40, 2), |(i, j)| {
+            (i / 20) as f64 + j as f64 + rng.gen_range(-1., 1.)
+        });
+        let y = Array1::from_shape_fn(40, |i| i / 20);
+        let data = Dataset::new(x.view(), y.view());
+
+        // the seed shuffles the folds of the search, identical seeds yield
+        // identical models
+        let fit = |seed| {
+            GaussianNbParams::params()
+                .var_smoothing_search(&[1e-9, 1e-3, 1e-1, 1.0])
+                .seed(seed)
+                .fit(&data)
+                .unwrap()
+        };
+        for seed in 0..3 {
+            let (first, second) = (fit(seed), fit(seed));
+            assert_eq!(first.var_smoothing(), second.var_smoothing());
+            for class in &[0, 1] {
+                assert_eq!(first.theta(class), second.theta(class));
+                assert_eq!(first.sigma(class), second.sigma(class));
+            }
+        }
+    }
+
+    #[test]
+    fn test_gnb_score() {
+        let (x, y) = fixture();
+
+        let data = Dataset::new(x.view(), y.view());
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+        assert_abs_diff_eq!(model.score(x.view(), y.as_slice().unwrap()), 1.0);
+
+        let y_flipped = [1, 1, 2, 2, 1, 2];
+        assert_abs_diff_eq!(model.score(x.view(), &y_flipped), 4. / 6.);
+    }
+
+    #[test]
+    fn test_gnb_cross_validate() {
+        let (x, y) = fixture();
+        let data = Dataset::new(x, y);
+
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let scores = data
+            .cross_validate(3, &mut rng, &GaussianNbParams::params())
+            .unwrap();
+
+        assert_eq!(scores.len(), 3);
+        assert!(scores
+            .iter()
+            .all(|x: &f64| x.is_finite() && *x >= 0. && *x <= 1.));
+    }
+
+    #[test]
+    fn test_gnb_declared_classes() {
+        let (x, y) = fixture();
+        let clf = GaussianNbParams::params().with_classes(vec![1, 2]);
+
+        // the first batch only contains class 1
+        let first = Dataset::new(x.slice(s![..3, ..]), y.slice(s![..3]));
+        let model = clf.fit_with(None, &first).unwrap();
+        let model_ref = model.as_ref().unwrap();
+        assert_eq!(model_ref.classes(), vec![1, 2]);
+        assert_eq!(model_ref.class_count(&2), Some(0));
+        assert_abs_diff_eq!(model_ref.prior(&2).unwrap(), 0.);
+
+        let proba = model_ref.predict_proba(x.view());
+        assert_eq!(proba.ncols(), 2);
+        assert_abs_diff_eq!(proba.column(1).sum(), 0.);
+        assert_eq!(model_ref.predict(x.view()), array![1, 1, 1, 1, 1, 1]);
+
+        let second = Dataset::new(x.slice(s![3.., ..]), y.slice(s![3..]));
+        let model = clf.fit_with(model, &second).unwrap().unwrap();
+        assert_eq!(model.class_count(&2), Some(3));
+        assert_abs_diff_eq!(model.prior(&2).unwrap(), 0.5);
+        assert_eq!(model.predict(x.view()), y);
+
+        let undeclared = array![1, 3, 1];
+        let data = Dataset::new(x.slice(s![..3, ..]), undeclared.view());
+        assert!(matches!(
+            clf.fit_with(Some(model), &data),
+            Err(BayesError::UndeclaredClass(1))
+        ));
+    }
+
+    #[test]
+    fn test_gnb_forgetting() {
+        // two samples around each class mean
+        let batch = |mu1: f64, mu2: f64| {
+            let x = array![
+                [mu1 - 0.5, mu1 + 0.5],
+                [mu1 + 0.5, mu1 - 0.5],
+                [mu2 - 0.5, mu2 + 0.5],
+                [mu2 + 0.5, mu2 - 0.5]
+            ];
+            let y = array![1, 1, 2, 2];
+            (x, y)
+        };
+
+        let stream = (0..10)
+            .map(|_| batch(-1., 1.))
+            .chain((0..3).map(|_| batch(-5., 5.)))
+            .collect::<Vec<_>>();
+
+        let fit_stream = |clf: GaussianNbParams| {
+            stream
+                .iter()
+                .fold(None, |current, (x, y)| {
+                    clf.fit_with(current, &Dataset::new(x.view(), y.view()))
+                        .unwrap()
+                })
+                .unwrap()
+        };
+
+        let model = fit_stream(GaussianNbParams::params());
+        let decayed = fit_stream(GaussianNbParams::params().lambda(0.5));
+
+        // after the distribution shift the decayed means are closer to the new
+        // means of `-5` and `5`
+        let dist = |model: &GaussianNb<f64>, class: usize, mu: f64| {
+            model
+                .theta(&class)
+                .unwrap()
+                .iter()
+                .map(|x| (x - mu).abs())
+                .sum::<f64>()
+        };
+        assert!(dist(&decayed, 1, -5.) < dist(&model, 1, -5.));
+        assert!(dist(&decayed, 2, 5.) < dist(&model, 2, 5.));
+        assert!(dist(&decayed, 1, -5.) < 1.);
+
+        // without forgetting all batches are weighted equally
+        assert_abs_diff_eq!(model.theta(&1).unwrap()[0], -25. / 13., epsilon = 1e-10);
+        assert_eq!(model.class_count(&1), Some(26));
+        assert_eq!(decayed.class_count(&1), Some(26));
+
+        // the forgetting factor has to be in `(0, 1]`
+        let (x, y) = batch(-1., 1.);
+        for lambda in &[0., -0.5, 1.5, f64::NAN] {
+            let res = GaussianNbParams::params()
+                .lambda(*lambda)
+                .fit_with(None, &Dataset::new(x.view(), y.view()));
+            assert!(matches!(
+                res,
+                Err(BayesError::InvalidParameter { name: "lambda", .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_gnb_fit_with() {
+        let (x, y) = fixture();
+
+        let clf = GaussianNbParams::params();
+
+        let model = x
+            .axis_chunks_iter(Axis(0), 2)
+            .zip(y.axis_chunks_iter(Axis(0), 2))
+            .map(|(a, b)| Dataset::new(a, b))
+            .fold(None, |current, d| clf.fit_with(current, &d).unwrap())
+            .unwrap();
+
+        let pred = model.predict(x.view());
+
+        assert_eq!(pred, y);
 
         let jll = model.joint_log_likelihood(x.view());
 
@@ -462,4 +1861,529 @@ mod tests {
             assert_abs_diff_eq!(value, expected.get(key).unwrap(), epsilon = 1e-6);
         }
     }
+
+    #[test]
+    fn test_gnb_predict_proba() {
+        let (x, y) = fixture();
+
+        let data = Dataset::new(x.view(), y.view());
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+        assert_eq!(model.classes(), vec![1, 2]);
+
+        // samples far away from the training data would underflow without
+        // normalization in log space
+        let test = array![[-2., -1.], [0.1, 0.], [1., 1.], [100., 100.]];
+        let proba = model.predict_proba(test.view());
+        for row in proba.genrows() {
+            assert_abs_diff_eq!(row.sum(), 1.0, epsilon = 1e-6);
+        }
+
+        assert!(proba[(0, 0)] > 0.99);
+        assert!(proba[(2, 1)] > 0.99);
+        assert_abs_diff_eq!(proba[(3, 1)], 1.0, epsilon = 1e-6);
+
+        let log_proba = model.predict_log_proba(test.view());
+        assert_abs_diff_eq!(log_proba.mapv(|x: f64| x.exp()), proba, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_gnb_feature_contributions() {
+        let (x, y) = fixture();
+        let data = Dataset::new(x.view(), y.view());
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+
+        let jll = model.joint_log_likelihood(x.view());
+        for class in &[1, 2] {
+            let sigma = model.sigma(class).unwrap();
+            let norm = sigma.mapv(|x| (2. * std::f64::consts::PI * x).ln()).sum() * -0.5;
+            let prior = model.prior(class).unwrap().ln();
+
+            for (row, expected) in x.outer_iter().zip(jll[class].iter()) {
+                let contributions = model.feature_contributions(row, class).unwrap();
+                assert_eq!(contributions.len(), 2);
+                assert!(contributions.iter().all(|x| *x <= 0.));
+                assert_abs_diff_eq!(
+                    contributions.sum() + prior + norm,
+                    *expected,
+                    epsilon = 1e-10
+                );
+            }
+        }
+
+        assert!(model.feature_contributions(x.row(0), &3).is_none());
+    }
+
+    #[test]
+    fn test_gnb_ignore_missing() {
+        let (x, y) = fixture();
+        let data = Dataset::new(x.view(), y.view());
+        let model = GaussianNbParams::params()
+            .ignore_missing(true)
+            .fit(&data)
+            .unwrap();
+
+        let test = array![[f64::NAN, -1.5], [1.5, f64::NAN], [-1.5, -1.5]];
+        assert_eq!(model.predict(test.view()), array![1, 2, 1]);
+
+        // a missing feature is marginalized out, the probabilities match a
+        // model fitted on the observed feature only
+        let column = x.slice(s![.., 1..]).to_owned();
+        let marginal = GaussianNbParams::params()
+            .fit(&Dataset::new(column.view(), y.view()))
+            .unwrap();
+        assert_abs_diff_eq!(
+            model.predict_proba(test.slice(s![..1, ..])),
+            marginal.predict_proba(test.slice(s![..1, 1..])),
+            epsilon = 1e-10
+        );
+
+        // complete rows are not affected by the mode
+        let complete = GaussianNbParams::params().fit(&data).unwrap();
+        assert_abs_diff_eq!(
+            model.predict_proba(test.slice(s![2.., ..])),
+            complete.predict_proba(test.slice(s![2.., ..])),
+            epsilon = 1e-10
+        );
+        assert!(complete.try_predict(test.view()).is_err());
+    }
+
+    #[test]
+    fn test_gnb_owned_dataset() {
+        let (x, y) = fixture();
+        let data = Dataset::from_arrays(x, y);
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+
+        let (records, targets) = data.records_targets();
+        assert_eq!(model.predict(records).as_slice(), Some(targets));
+
+        // the owned dataset gives the same model as borrowed records and targets
+        let borrowed = GaussianNbParams::params()
+            .fit(&Dataset::new(records, targets))
+            .unwrap();
+        for class in &[1, 2] {
+            assert_eq!(model.theta(class), borrowed.theta(class));
+            assert_eq!(model.sigma(class), borrowed.sigma(class));
+        }
+    }
+
+    #[test]
+    fn test_gnb_reset() {
+        let (x, y) = fixture();
+        let clf = GaussianNbParams::params();
+
+        let mut model = clf
+            .fit_with(None, &Dataset::new(x.view(), y.view()))
+            .unwrap()
+            .unwrap();
+        model.reset();
+        assert!(model.classes_sorted().is_empty());
+        assert!(model.theta(&1).is_none());
+
+        // refit with shifted records and different labels
+        let x2 = &x + 10.;
+        let y2 = array![3, 3, 3, 4, 4, 4];
+        let model = clf
+            .fit_with(Some(model), &Dataset::new(x2.view(), y2.view()))
+            .unwrap()
+            .unwrap();
+        let expected = clf.fit(&Dataset::new(x2.view(), y2.view())).unwrap();
+
+        assert_eq!(model.classes_sorted(), &[3, 4]);
+        assert!(model.prior(&1).is_none());
+        for class in &[3, 4] {
+            assert_eq!(model.class_count(class), Some(3));
+            assert_abs_diff_eq!(model.prior(class).unwrap(), 0.5);
+            assert_abs_diff_eq!(
+                model.theta(class).unwrap(),
+                expected.theta(class).unwrap(),
+                epsilon = 1e-12
+            );
+            assert_abs_diff_eq!(
+                model.sigma(class).unwrap(),
+                expected.sigma(class).unwrap(),
+                epsilon = 1e-12
+            );
+        }
+        assert_eq!(model.predict(x2.view()), y2);
+    }
+
+    #[test]
+    fn test_gnb_stream_precision() {
+        // a large offset with a small spread, the deviations from the offset
+        // are exact and give the reference statistics
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::from_shape_simple_fn((1000, 2), || 1e6 + rng.gen_range(-1., 1.));
+        let y = Array1::from_shape_fn(1000, |i| i % 2);
+
+        let clf = GaussianNbParams::params().var_smoothing(0.);
+        let model = x
+            .axis_chunks_iter(Axis(0), 1)
+            .zip(y.axis_chunks_iter(Axis(0), 1))
+            .fold(None, |current, (x, y)| {
+                clf.fit_with(current, &Dataset::new(x, y)).unwrap()
+            })
+            .unwrap();
+        let single = clf.fit(&Dataset::new(x.view(), y.view())).unwrap();
+
+        for class in &[0, 1] {
+            let dev = x.slice(s![*class..;2, ..]).mapv(|x| x - 1e6);
+            let mean = dev.mean_axis(Axis(0)).unwrap();
+            let var = (&dev - &mean).mapv(|x| x * x).mean_axis(Axis(0)).unwrap();
+
+            // the spacing of floats around the offset is `1.2e-10`
+            assert_abs_diff_eq!(model.theta(class).unwrap(), mean + 1e6, epsilon = 1e-10);
+            assert_abs_diff_eq!(model.sigma(class).unwrap(), var, epsilon = 1e-13);
+
+            assert_abs_diff_eq!(
+                model.theta(class).unwrap(),
+                single.theta(class).unwrap(),
+                epsilon = 1e-8
+            );
+            assert_abs_diff_eq!(
+                model.sigma(class).unwrap(),
+                single.sigma(class).unwrap(),
+                epsilon = 1e-9
+            );
+        }
+    }
+
+    #[test]
+    fn test_gnb_class_counts() {
+        let x = array![
+            [-2., -1.],
+            [-1., -1.],
+            [-1., -2.],
+            [1., 1.],
+            [1., 2.],
+            [2., 1.],
+            [-2., -2.],
+            [2., 2.],
+            [3., 3.]
+        ];
+        let y = array![1, 1, 1, 2, 2, 2, 1, 2, 3];
+
+        let clf = GaussianNbParams::params().with_classes(vec![1, 2, 3, 4]);
+        let model = x
+            .axis_chunks_iter(Axis(0), 3)
+            .zip(y.axis_chunks_iter(Axis(0), 3))
+            .fold(None, |current, (x, y)| {
+                clf.fit_with(current, &Dataset::new(x, y)).unwrap()
+            })
+            .unwrap();
+
+        let counts = model.class_counts();
+        assert_eq!(counts.values().sum::<usize>(), 9);
+        assert_eq!(counts[&1], 4);
+        assert_eq!(counts[&2], 4);
+        assert_eq!(counts[&3], 1);
+        assert_eq!(counts[&4], 0);
+    }
+
+    #[test]
+    fn test_gnb_large_stream_prior() {
+        let x = array![[-1f32, -1.], [-2., -1.], [1., 1.], [2., 1.]];
+        let y = array![1, 1, 2, 2];
+
+        let clf = GaussianNbParams::params();
+        let mut model: GaussianNb<f32, usize> = clf.fit(&Dataset::new(x.view(), y.view())).unwrap();
+
+        // We simulate a long stream, whose counts are beyond the integers
+        // exactly representable by `f32`
+        let large = 1usize << 24;
+        for info in model.class_info.values_mut() {
+            info.class_count = large;
+            info.weight = large as f64;
+        }
+
+        // Single observations would be rounded away when added to an `f32`
+        // count of 2^24
+        let mut model = Some(model);
+        for _ in 0..16 {
+            let batch = Dataset::new(x.slice(s![..1, ..]), y.slice(s![..1]));
+            model = clf.fit_with(model, &batch).unwrap();
+        }
+        let model = model.unwrap();
+
+        assert_eq!(model.class_count(&1), Some(large + 16));
+        let expected = (large + 16) as f64 / (2 * large + 16) as f64;
+        assert_eq!(model.prior(&1).unwrap(), expected as f32);
+        assert!(model.prior(&1).unwrap() > 0.5);
+        assert!(model.prior(&2).unwrap() < 0.5);
+    }
+
+    #[test]
+    fn test_gnb_order_invariance() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // the records arrive sorted by class
+        let x = Array2::from_shape_fn((40, 2), |(i, j)| {
+            (i / 20) as f64 * 3. + j as f64 + rng.gen_range(-1., 1.)
+        });
+        let y = Array1::from_shape_fn(40, |i| i / 20);
+
+        let clf = GaussianNbParams::params().var_smoothing(0.);
+        let stream = |x: &Array2<f64>, y: &Array1<usize>| {
+            x.axis_chunks_iter(Axis(0), 3)
+                .zip(y.axis_chunks_iter(Axis(0), 3))
+                .fold(None, |current, (x, y)| {
+                    clf.fit_with(current, &Dataset::new(x, y)).unwrap()
+                })
+                .unwrap()
+        };
+
+        let sorted = stream(&x, &y);
+        let single = clf.fit(&Dataset::new(x.view(), y.view())).unwrap();
+        for _ in 0..5 {
+            let mut order = (0..40).collect::<Vec<_>>();
+            order.shuffle(&mut rng);
+            let model = stream(
+                &x.select(Axis(0), &order),
+                &order.iter().map(|i| y[*i]).collect(),
+            );
+
+            for class in &[0, 1] {
+                for reference in &[&sorted, &single] {
+                    assert_abs_diff_eq!(
+                        model.theta(class).unwrap(),
+                        reference.theta(class).unwrap(),
+                        epsilon = 1e-12
+                    );
+                    assert_abs_diff_eq!(
+                        model.sigma(class).unwrap(),
+                        reference.sigma(class).unwrap(),
+                        epsilon = 1e-12
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_gnb_min_variance() {
+        let (x, y) = fixture();
+        let data = Dataset::new(x.view(), y.view());
+
+        let extreme = |model: &GaussianNb<f64, usize>| {
+            model
+                .joint_log_likelihood(x.view())
+                .values()
+                .flat_map(|x| x.iter().cloned())
+                .fold(0f64, |acc, x| acc.max(x.abs()))
+        };
+
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+        let floored = GaussianNbParams::params()
+            .min_variance(1.0)
+            .fit(&data)
+            .unwrap();
+
+        for class in &[1, 2] {
+            assert!(floored.sigma(class).unwrap().iter().all(|x| *x >= 1.0));
+        }
+        assert!(extreme(&floored) < extreme(&model));
+        assert_eq!(floored.predict(x.view()), y);
+
+        // a floor below all variances has no effect
+        let unchanged = GaussianNbParams::params()
+            .min_variance(1e-3)
+            .fit(&data)
+            .unwrap();
+        assert_eq!(unchanged.sigma(&1), model.sigma(&1));
+    }
+
+    #[test]
+    fn test_gnb_class_order() {
+        // The same data with the classes observed in a different order
+        let x = array![[-1.], [-3.], [1.], [3.], [5.], [7.]];
+        let y = array![1, 1, 2, 2, 0, 0];
+        let data = Dataset::new(x.view(), y.view());
+        let first = GaussianNbParams::params().fit(&data).unwrap();
+
+        let x_rev = array![[7.], [5.], [3.], [1.], [-3.], [-1.]];
+        let y_rev = array![0, 0, 2, 2, 1, 1];
+        let data = Dataset::new(x_rev.view(), y_rev.view());
+        let second = GaussianNbParams::params().fit(&data).unwrap();
+
+        assert_eq!(first.classes_sorted(), &[0, 1, 2]);
+        assert_eq!(first.classes_sorted(), second.classes_sorted());
+        assert_eq!(
+            first.predict_proba(x.view()),
+            second.predict_proba(x.view())
+        );
+
+        // The point between the first two classes has exactly the same
+        // likelihood for both, the smaller class wins
+        let tie = array![[0.]];
+        for _ in 0..10 {
+            let model = GaussianNbParams::params()
+                .fit(&Dataset::new(x.view(), y.view()))
+                .unwrap();
+            assert_eq!(model.predict(tie.view()), array![1]);
+        }
+    }
+
+    #[test]
+    fn test_gnb_extreme_values() {
+        let x = array![
+            [-2f64, -1.],
+            [-1., -1.],
+            [-1., -2.],
+            [1., 1.],
+            [1., 2.],
+            [2., 1.]
+        ];
+        let y = array![1, 1, 1, 2, 2, 2];
+
+        let data = Dataset::new(x.view(), y.view());
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+
+        // the joint log likelihood of these samples is far below the range of `exp`
+        let test = array![[1e8, 1e8], [-1e8, -1e8], [1e8, -1e8]];
+        let proba = model.predict_proba(test.view());
+
+        assert!(proba.iter().all(|x| x.is_finite()));
+        for row in proba.genrows() {
+            assert_abs_diff_eq!(row.sum(), 1.0, epsilon = 1e-6);
+        }
+        assert_abs_diff_eq!(proba[(0, 1)], 1.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(proba[(1, 0)], 1.0, epsilon = 1e-6);
+        // equally unlikely under both classes
+        assert_abs_diff_eq!(proba[(2, 0)], 0.5, epsilon = 1e-6);
+
+        let log_proba = model.predict_log_proba(test.view());
+        assert!(log_proba.iter().all(|x| !x.is_nan() && *x <= 0.0));
+    }
+
+    #[test]
+    fn test_gnb_try_predict() {
+        let (x, y) = fixture();
+
+        let data = Dataset::new(x.view(), y.view());
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+        assert_eq!(model.try_predict(x.view()).unwrap(), y);
+
+        let test = array![[-2., -1.], [1., f64::NAN]];
+        assert!(matches!(
+            model.try_predict(test.view()),
+            Err(BayesError::InvalidValue)
+        ));
+
+        let test = Array2::<f64>::zeros((0, 2));
+        assert!(matches!(
+            model.try_predict(test.view()),
+            Err(BayesError::EmptyInput)
+        ));
+    }
+
+    #[test]
+    fn test_gnb_feature_count_mismatch() {
+        let x = array![[-2., -1.], [-1., -1.], [1., 1.], [2., 1.]];
+        let y = array![1, 1, 2, 2];
+
+        let data = Dataset::new(x.view(), y.view());
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+
+        for ncols in &[1, 3] {
+            let test = Array2::<f64>::zeros((2, *ncols));
+            match model.try_predict(test.view()) {
+                Err(BayesError::FeatureCountMismatch { expected, found }) => {
+                    assert_eq!((expected, found), (2, *ncols))
+                }
+                _ => panic!("Expected a feature count mismatch"),
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected 2 features as in the training data, found 3")]
+    fn test_gnb_feature_count_mismatch_panics() {
+        let x = array![[-2., -1.], [-1., -1.], [1., 1.], [2., 1.]];
+        let y = array![1, 1, 2, 2];
+
+        let data = Dataset::new(x.view(), y.view());
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+
+        model.predict_proba(Array2::<f64>::zeros((2, 3)).view());
+    }
+
+    #[test]
+    fn test_gnb_empty_dataset() {
+        let x = Array2::<f64>::zeros((0, 2));
+        let y = Array1::<usize>::zeros(0);
+
+        let data = Dataset::new(x.view(), y.view());
+        assert!(matches!(
+            GaussianNbParams::params().fit(&data),
+            Err(BayesError::EmptyDataset)
+        ));
+
+        // a class of the label set without records is rejected
+        let x = array![[-2., -1.], [-1., -1.], [1., 1.], [2., 1.]];
+        let y = array![1, 1, 3, 3];
+        let data = Dataset::new(x.view(), y.view()).with_labels(&[1, 2, 3]);
+        assert!(matches!(
+            GaussianNbParams::params().fit(&data),
+            Err(BayesError::EmptyClass(1))
+        ));
+    }
+
+    #[test]
+    fn test_gnb_single_class() {
+        let x = array![[-2., -1.], [-1., -1.], [-1., -2.]];
+        let y = array![1, 1, 1];
+
+        let data = Dataset::new(x.view(), y.view());
+        assert!(matches!(
+            GaussianNbParams::params().fit(&data),
+            Err(BayesError::SingleClass)
+        ));
+
+        // declared classes without observations are not counted
+        assert!(matches!(
+            GaussianNbParams::params()
+                .with_classes(vec![1, 2])
+                .fit(&data),
+            Err(BayesError::SingleClass)
+        ));
+
+        let model = GaussianNbParams::params()
+            .allow_single_class(true)
+            .fit(&data)
+            .unwrap();
+        assert_eq!(model.classes(), vec![1]);
+        assert_eq!(model.predict(array![[5., 5.]].view()), array![1]);
+
+        // incremental fits accept single-class batches
+        assert!(GaussianNbParams::params().fit_with(None, &data).is_ok());
+    }
+
+    #[test]
+    fn test_gnb_string_labels() {
+        let (x, _) = fixture();
+        let y = vec!["ham", "ham", "ham", "spam", "spam", "spam"];
+
+        let data = Dataset::new(x.view(), &y);
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+        assert_eq!(model.classes(), vec!["ham", "spam"]);
+
+        let pred = model.predict(x.view());
+        assert_eq!(pred.to_vec(), y);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_gnb_serde() {
+        let (x, y) = fixture();
+
+        let data = Dataset::new(x.view(), y.view());
+        let model = GaussianNbParams::params().fit(&data).unwrap();
+
+        let serialized = serde_json::to_string(&model).unwrap();
+        let deserialized: GaussianNb<f64> = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized.predict(x.view()), model.predict(x.view()));
+        assert_eq!(
+            deserialized.predict_proba(x.view()),
+            model.predict_proba(x.view())
+        );
+    }
 }
@@ -1,5 +1,15 @@
+mod base_nb;
+mod bernoulli_nb;
+mod categorical_nb;
+mod complement_nb;
 mod error;
 mod gaussian_nb;
+mod multinomial_nb;
 
+pub use base_nb::NaiveBayes;
+pub use bernoulli_nb::{BernoulliNb, BernoulliNbParams};
+pub use categorical_nb::{CategoricalNb, CategoricalNbParams};
+pub use complement_nb::{ComplementNb, ComplementNbParams};
 pub use error::BayesError;
-pub use gaussian_nb::GaussianNbParams;
+pub use gaussian_nb::{GaussianNb, GaussianNbParams, TieBreak};
+pub use multinomial_nb::{MultinomialNb, MultinomialNbParams};
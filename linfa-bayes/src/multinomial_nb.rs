@@ -0,0 +1,350 @@
+//! Multinomial Naive Bayes (MultinomialNB)
+//!
+//! Implements Multinomial Naive Bayes algorithm for classification of count
+//! data, for example word counts in text classification. The likelihood of the
+//! feature P(x_i | y) is assumed to follow a multinomial distribution, whose
+//! parameters are estimated with a smoothed version of maximum likelihood.
+
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2, Axis};
+use std::collections::HashMap;
+
+use crate::base_nb::{filter, NaiveBayes};
+use crate::error::Result;
+use linfa::dataset::{Dataset, Labels};
+use linfa::traits::{Fit, IncrementalFit, Predict};
+use linfa::Float;
+
+/// Multinomial Naive Bayes (MultinomialNB)
+#[derive(Debug)]
+pub struct MultinomialNbParams {
+    // Additive (Laplace/Lidstone) smoothing parameter
+    alpha: f64,
+}
+
+impl Default for MultinomialNbParams {
+    fn default() -> Self {
+        Self::params()
+    }
+}
+
+impl MultinomialNbParams {
+    /// Create new MultinomialNB model with default values for its parameters
+    pub fn params() -> Self {
+        MultinomialNbParams { alpha: 1.0 }
+    }
+
+    // Specifies the additive smoothing parameter, a value of `1.0` corresponds
+    // to Laplace smoothing, smaller values to Lidstone smoothing and `0.0`
+    // disables the smoothing
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+}
+
+impl<'a, A, L> Fit<'a, ArrayView2<'_, A>, L> for MultinomialNbParams
+where
+    A: Float,
+    L: Labels<Elem = usize>,
+{
+    type Object = Result<MultinomialNb<A>>;
+
+    /// Fit the model
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ndarray::array;
+    /// # use linfa::Dataset;
+    /// # use linfa_bayes::MultinomialNbParams;
+    /// # use linfa::traits::{Fit, Predict};
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let x = array![
+    ///     [1., 5.],
+    ///     [2., 4.],
+    ///     [0., 3.],
+    ///     [5., 1.],
+    ///     [4., 0.],
+    ///     [3., 1.]
+    /// ];
+    /// let y = vec![1, 1, 1, 2, 2, 2];
+    ///
+    /// let data = Dataset::new(x.view(), &y);
+    /// let model = MultinomialNbParams::params().fit(&data)?;
+    /// let pred = model.predict(x.view());
+    ///
+    /// assert_eq!(pred.to_vec(), y);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn fit(&self, dataset: &'a Dataset<ArrayView2<A>, L>) -> Self::Object {
+        let model = self.fit_with(None, dataset)?;
+
+        Ok(model.unwrap())
+    }
+}
+
+impl<A, L> IncrementalFit<'_, ArrayView2<'_, A>, L> for MultinomialNbParams
+where
+    A: Float,
+    L: Labels<Elem = usize>,
+{
+    type ObjectIn = Option<MultinomialNb<A>>;
+    type ObjectOut = Result<Option<MultinomialNb<A>>>;
+
+    /// Incrementally fit on a batch of samples
+    ///
+    /// The feature counts of each class are accumulated over all batches, which
+    /// makes it possible to train on streams of token-count batches.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ndarray::{array, Axis};
+    /// # use linfa::Dataset;
+    /// # use linfa_bayes::MultinomialNbParams;
+    /// # use linfa::traits::{Predict, IncrementalFit};
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let x = array![
+    ///     [1., 5.],
+    ///     [2., 4.],
+    ///     [0., 3.],
+    ///     [5., 1.],
+    ///     [4., 0.],
+    ///     [3., 1.]
+    /// ];
+    /// let y = array![1, 1, 1, 2, 2, 2];
+    ///
+    /// let clf = MultinomialNbParams::params();
+    /// let mut model = None;
+    ///
+    /// for (x, y) in x
+    ///     .axis_chunks_iter(Axis(0), 2)
+    ///     .zip(y.axis_chunks_iter(Axis(0), 2))
+    /// {
+    ///     model = clf.fit_with(model, &Dataset::new(x, y))?;
+    /// }
+    ///
+    /// let pred = model.as_ref().unwrap().predict(x.view());
+    ///
+    /// assert_eq!(pred, y);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn fit_with(
+        &self,
+        model_in: Self::ObjectIn,
+        dataset: &Dataset<ArrayView2<A>, L>,
+    ) -> Self::ObjectOut {
+        let x = dataset.records();
+        let y = dataset.targets();
+
+        let mut model = match model_in {
+            Some(temp) => temp,
+            None => MultinomialNb {
+                class_info: HashMap::new(),
+            },
+        };
+
+        let yunique = y.labels();
+
+        for class in yunique.iter() {
+            // We filter x for records that correspond to the current class
+            let xclass = filter(x, y.as_slice(), class);
+
+            // We count the number of occurances of the class
+            let nclass = xclass.nrows();
+
+            // We compute the update of the feature counts and log probabilities
+            let class_info = model
+                .class_info
+                .entry(*class)
+                .or_insert_with(ClassInfo::default);
+            let feature_count =
+                Self::update_feature_count(&class_info.feature_count.view(), &xclass);
+            let feature_log_prob = self.feature_log_prob(&feature_count.view());
+
+            // We now update the feature counts, log probabilities and class count
+            class_info.feature_count = feature_count;
+            class_info.feature_log_prob = feature_log_prob;
+            class_info.class_count += nclass;
+        }
+
+        // We update the priors
+        let class_count_sum = model
+            .class_info
+            .values()
+            .fold(0, |acc, x| acc + x.class_count);
+        for info in model.class_info.values_mut() {
+            info.prior = A::from(info.class_count).unwrap() / A::from(class_count_sum).unwrap();
+        }
+
+        Ok(Some(model))
+    }
+}
+
+impl MultinomialNbParams {
+    // Compute online update of the feature counts
+    fn update_feature_count<A: Float>(count_old: &ArrayView1<A>, x_new: &Array2<A>) -> Array1<A> {
+        let count_new = x_new.sum_axis(Axis(0));
+
+        // If previous batch was empty, we send the new counts
+        if count_old.is_empty() {
+            return count_new;
+        }
+
+        count_new + count_old
+    }
+
+    // Compute the smoothed feature log probabilities from the feature counts
+    fn feature_log_prob<A: Float>(&self, feature_count: &ArrayView1<A>) -> Array1<A> {
+        let alpha = A::from(self.alpha).unwrap();
+        let nfeatures = A::from(feature_count.len()).unwrap();
+
+        // Without smoothing a class with no counts at all has an undefined
+        // distribution, we assign the log probability of zero to each feature
+        // instead of `NaN`
+        let total = feature_count.sum() + alpha * nfeatures;
+        if total <= A::zero() {
+            return Array1::from_elem(feature_count.len(), A::neg_infinity());
+        }
+        let total = total.ln();
+
+        feature_count.mapv(|x| (x + alpha).ln() - total)
+    }
+}
+
+/// Fitted MultinomialNB for predicting classes
+#[derive(Debug, Clone)]
+pub struct MultinomialNb<A> {
+    class_info: HashMap<usize, ClassInfo<A>>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct ClassInfo<A> {
+    class_count: usize,
+    prior: A,
+    feature_count: Array1<A>,
+    feature_log_prob: Array1<A>,
+}
+
+impl<A: Float> Predict<ArrayView2<'_, A>, Array1<usize>> for MultinomialNb<A> {
+    /// Perform classification on incoming array
+    ///
+    /// Ties are resolved in favour of the smallest class. __Panics__ if
+    /// pairwise orderings are undefined (this occurs in presence of NaN
+    /// values), use `predict_classes` to handle this case
+    fn predict(&self, x: ArrayView2<'_, A>) -> Array1<usize> {
+        self.predict_classes(x).unwrap()
+    }
+}
+
+impl<A: Float> NaiveBayes<A, usize> for MultinomialNb<A> {
+    fn joint_log_likelihood(&self, x: ArrayView2<A>) -> HashMap<&usize, Array1<A>> {
+        let mut joint_log_likelihood = HashMap::new();
+
+        for (class, info) in self.class_info.iter() {
+            let jointi = info.prior.ln();
+
+            // Features which do not occur in a sample are skipped, because
+            // their log probability can be `-inf` without smoothing and
+            // `0 * -inf` would result in `NaN`
+            let nij = x.map_axis(Axis(1), |row| {
+                row.iter()
+                    .zip(info.feature_log_prob.iter())
+                    .filter(|(x, _)| **x != A::zero())
+                    .map(|(x, log_prob)| *x * *log_prob)
+                    .sum::<A>()
+            });
+
+            joint_log_likelihood.insert(class, nij + jointi);
+        }
+
+        joint_log_likelihood
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use linfa::Dataset;
+    use ndarray::array;
+
+    #[test]
+    fn test_multinomial_nb() {
+        let x = array![[1., 5.], [2., 4.], [0., 3.], [5., 1.], [4., 0.], [3., 1.]];
+        let y = array![1, 1, 1, 2, 2, 2];
+
+        let clf = MultinomialNbParams::params();
+        let data = Dataset::new(x.view(), y.view());
+        let fitted_clf = clf.fit(&data).unwrap();
+        let pred = fitted_clf.predict(x.view());
+        assert_eq!(pred, y);
+
+        // class 1 counts [3, 12], class 2 counts [12, 2] with Laplace smoothing
+        let jll = fitted_clf.joint_log_likelihood(array![[1., 1.]].view());
+        assert_abs_diff_eq!(
+            jll[&1usize][0],
+            0.5f64.ln() + (4. / 17f64).ln() + (13. / 17f64).ln(),
+            epsilon = 1e-10
+        );
+        assert_abs_diff_eq!(
+            jll[&2usize][0],
+            0.5f64.ln() + (13. / 16f64).ln() + (3. / 16f64).ln(),
+            epsilon = 1e-10
+        );
+    }
+
+    #[test]
+    fn test_mnb_fit_with() {
+        let x = array![[1., 5.], [2., 4.], [0., 3.], [5., 1.], [4., 0.], [3., 1.]];
+        let y = array![1, 1, 1, 2, 2, 2];
+
+        let clf = MultinomialNbParams::params();
+
+        let model = x
+            .axis_chunks_iter(Axis(0), 2)
+            .zip(y.axis_chunks_iter(Axis(0), 2))
+            .map(|(a, b)| Dataset::new(a, b))
+            .fold(None, |current, d| clf.fit_with(current, &d).unwrap())
+            .unwrap();
+
+        let pred = model.predict(x.view());
+        assert_eq!(pred, y);
+
+        // the accumulated model has to be identical to the one trained at once
+        let expected = clf.fit(&Dataset::new(x.view(), y.view())).unwrap();
+        let jll = model.joint_log_likelihood(x.view());
+        let jll_expected = expected.joint_log_likelihood(x.view());
+
+        for (key, value) in jll.iter() {
+            assert_abs_diff_eq!(value, jll_expected.get(key).unwrap(), epsilon = 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_mnb_zero_features() {
+        let x = array![[2., 0.], [3., 0.], [0., 2.], [0., 4.], [0., 1.]];
+        let y = array![1, 1, 2, 2, 2];
+
+        // without smoothing unobserved features have a log probability of `-inf`
+        let model = MultinomialNbParams::params()
+            .alpha(0.0)
+            .fit(&Dataset::new(x.view(), y.view()))
+            .unwrap();
+
+        let pred = model.predict(x.view());
+        assert_eq!(pred, y);
+
+        // an all-zero row is classified by the priors alone
+        let test = array![[0., 0.], [1., 0.], [0., 1.]];
+        let jll = model.joint_log_likelihood(test.view());
+        assert!(jll.values().all(|x| x.iter().all(|x: &f64| !x.is_nan())));
+
+        let pred = model.predict(test.view());
+        assert_eq!(pred, array![2, 1, 2]);
+    }
+}
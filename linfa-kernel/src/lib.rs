@@ -4,18 +4,24 @@ mod sparse;
 
 use ndarray::prelude::*;
 use ndarray::Data;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 #[cfg(feature = "serde")]
 use serde_crate::{Deserialize, Serialize};
 use sprs::CsMat;
+use std::fmt;
 use std::ops::Mul;
+use std::sync::Arc;
 
 use linfa::{dataset::Dataset, dataset::Records, dataset::Targets, traits::Transformer, Float};
 
-/// Kernel representation, can be either dense or sparse
+/// Kernel representation, can be either dense, sparse or lazy
 #[derive(Clone)]
 pub enum KernelType {
     Dense,
     Sparse(usize),
+    /// No matrix is stored, entries are evaluated whenever they are accessed
+    Lazy,
 }
 
 /// Storage for the kernel matrix
@@ -28,6 +34,7 @@ pub enum KernelType {
 pub enum KernelInner<F: Float> {
     Dense(Array2<F>),
     Sparse(CsMat<F>),
+    Lazy,
 }
 
 /// A generic kernel
@@ -72,6 +79,7 @@ impl<'a, F: Float> Kernel<ArrayView2<'a, F>> {
         let inner = match kind {
             KernelType::Dense => KernelInner::Dense(dense_from_fn(&dataset, &method)),
             KernelType::Sparse(k) => KernelInner::Sparse(sparse_from_fn(&dataset, k, &method)),
+            KernelType::Lazy => KernelInner::Lazy,
         };
 
         Kernel {
@@ -82,10 +90,35 @@ impl<'a, F: Float> Kernel<ArrayView2<'a, F>> {
         }
     }
 
+    /// Create a lazy kernel from a user supplied kernel function
+    ///
+    /// The function has to be symmetric and positive semi-definite for the kernel to be valid.
+    /// No kernel matrix is stored, columns are evaluated when they are accessed, for example by
+    /// the cache of the SVM solver. This allows domain specific kernels without precomputing the
+    /// whole matrix.
+    ///
+    /// ```
+    /// use linfa_kernel::Kernel;
+    /// use ndarray::array;
+    ///
+    /// let records = array![[0., 1.], [1., 0.], [2., 2.]];
+    /// let kernel = Kernel::from_fn(records.view(), |a, b| a.dot(&b));
+    ///
+    /// assert_eq!(kernel.column(2), vec![2., 2., 8.]);
+    /// ```
+    pub fn from_fn(
+        dataset: ArrayView2<'a, F>,
+        fnc: impl Fn(ArrayView1<F>, ArrayView1<F>) -> F + Send + Sync + 'static,
+    ) -> Kernel<ArrayView2<'a, F>> {
+        Kernel::new(dataset, KernelMethod::custom(fnc), KernelType::Lazy, false)
+    }
+
+    /// A lazy kernel evaluates all entries and behaves like a dense kernel
     pub fn dot(&self, rhs: &ArrayView2<F>) -> Array2<F> {
         match &self.inner {
             KernelInner::Dense(mat) => mat.mul(rhs),
             KernelInner::Sparse(mat) => mat.mul(rhs),
+            KernelInner::Lazy => dense_from_fn(&self.dataset, &self.method).mul(rhs),
         }
     }
 
@@ -101,6 +134,9 @@ impl<'a, F: Float> Kernel<ArrayView2<'a, F>> {
 
                 sum
             }
+            KernelInner::Lazy => (0..self.size())
+                .map(|i| self.column(i).into_iter().sum())
+                .collect(),
         }
     }
 
@@ -108,6 +144,7 @@ impl<'a, F: Float> Kernel<ArrayView2<'a, F>> {
         match &self.inner {
             KernelInner::Dense(mat) => mat.ncols(),
             KernelInner::Sparse(mat) => mat.cols(),
+            KernelInner::Lazy => self.dataset.nrows(),
         }
     }
 
@@ -125,13 +162,23 @@ impl<'a, F: Float> Kernel<ArrayView2<'a, F>> {
                     .map(|(_, val)| *val)
                     .collect()
             }
+            KernelInner::Lazy => {
+                let n = self.size();
+                (0..n)
+                    .flat_map(|row| (row + 1..n).map(move |col| (row, col)))
+                    .map(|(row, col)| {
+                        self.method
+                            .distance(self.dataset.row(row), self.dataset.row(col))
+                    })
+                    .collect()
+            }
         }
     }
 
     pub fn diagonal(&self) -> Array1<F> {
         match &self.inner {
             KernelInner::Dense(mat) => mat.diag().to_owned(),
-            KernelInner::Sparse(_) => self
+            KernelInner::Sparse(_) | KernelInner::Lazy => self
                 .dataset
                 .outer_iter()
                 .map(|x| self.method.distance(x.view(), x.view()))
@@ -139,12 +186,28 @@ impl<'a, F: Float> Kernel<ArrayView2<'a, F>> {
         }
     }
 
+    /// Returns the column `i` of the kernel matrix
+    ///
+    /// Entries of a sparse kernel outside of the neighbourhood graph are zero. The sparse matrix
+    /// is symmetric and only the stored entries of row `i` are visited. The entries of a lazy
+    /// kernel are evaluated in parallel with the `rayon` feature.
     pub fn column(&self, i: usize) -> Vec<F> {
         match &self.inner {
             KernelInner::Dense(mat) => mat.column(i).to_vec(),
-            KernelInner::Sparse(mat) => (0..self.size())
-                .map(|j| *mat.get(j, i).unwrap_or(&F::neg_zero()))
-                .collect::<Vec<_>>(),
+            KernelInner::Sparse(mat) => {
+                let mut column = vec![F::zero(); self.size()];
+                if let Some(row) = mat.outer_view(i) {
+                    for (j, val) in row.iter() {
+                        column[j] = *val;
+                    }
+                }
+
+                column
+            }
+            KernelInner::Lazy => evaluate(self.size(), |j| {
+                self.method
+                    .distance(self.dataset.row(j), self.dataset.row(i))
+            }),
         }
     }
 
@@ -156,6 +219,33 @@ impl<'a, F: Float> Kernel<ArrayView2<'a, F>> {
             .sum()
     }
 
+    /// Evaluate the kernel function between two arbitrary points
+    pub fn apply(&self, a: ArrayView1<F>, b: ArrayView1<F>) -> F {
+        self.method.distance(a, b)
+    }
+
+    /// Evaluate the kernel function between new observations and the records of the kernel
+    ///
+    /// The entry `(i, j)` of the returned matrix is `K(x_i, r_j)` for the row `x_i` of `x` and
+    /// the record `r_j`. All entries are computed, also for sparse kernels.
+    ///
+    /// # Panics
+    ///
+    /// If the number of features of `x` differs from the records
+    pub fn apply_rows(&self, x: ArrayView2<F>) -> Array2<F> {
+        assert_eq!(
+            x.ncols(),
+            self.dataset.ncols(),
+            "Expected {} features, got {}",
+            self.dataset.ncols(),
+            x.ncols()
+        );
+
+        Array2::from_shape_fn((x.nrows(), self.dataset.nrows()), |(i, j)| {
+            self.method.distance(x.row(i), self.dataset.row(j))
+        })
+    }
+
     pub fn is_linear(&self) -> bool {
         self.linear
     }
@@ -164,6 +254,7 @@ impl<'a, F: Float> Kernel<ArrayView2<'a, F>> {
         KernelParams {
             kind: KernelType::Dense,
             method: KernelMethod::Gaussian(F::from(0.5).unwrap()),
+            gamma: None,
         }
     }
 }
@@ -186,9 +277,50 @@ pub enum KernelMethod<F> {
     Gaussian(F),
     Linear,
     Polynomial(F, F),
+    /// Polynomial kernel `(gamma * <x, y> + coef0)^degree`
+    Poly {
+        degree: u32,
+        gamma: F,
+        coef0: F,
+    },
+    /// Sigmoid kernel `tanh(gamma * <x, y> + coef0)`
+    Sigmoid {
+        gamma: F,
+        coef0: F,
+    },
+    /// Cosine similarity `<x, y> / (||x|| ||y||)`, which is zero if either vector is zero
+    Cosine,
+    /// Additive chi-squared kernel `sum_i 2 x_i y_i / (x_i + y_i)` for non-negative features,
+    /// such as histograms. Features with `x_i + y_i = 0` do not contribute.
+    Chi2,
+    /// User supplied kernel function, which cannot be serialized
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(KernelFn<F>),
+}
+
+type DynKernelFn<F> = dyn Fn(ArrayView1<F>, ArrayView1<F>) -> F + Send + Sync;
+
+/// Kernel function supplied by the user, shared between clones of the kernel method
+pub struct KernelFn<F>(Arc<DynKernelFn<F>>);
+
+impl<F> Clone for KernelFn<F> {
+    fn clone(&self) -> Self {
+        KernelFn(self.0.clone())
+    }
+}
+
+impl<F> fmt::Debug for KernelFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KernelFn")
+    }
 }
 
 impl<F: Float> KernelMethod<F> {
+    /// Create a kernel method from a symmetric and positive semi-definite function
+    pub fn custom(fnc: impl Fn(ArrayView1<F>, ArrayView1<F>) -> F + Send + Sync + 'static) -> Self {
+        KernelMethod::Custom(KernelFn(Arc::new(fnc)))
+    }
+
     pub fn distance(&self, a: ArrayView1<F>, b: ArrayView1<F>) -> F {
         match *self {
             KernelMethod::Gaussian(eps) => {
@@ -202,6 +334,27 @@ impl<F: Float> KernelMethod<F> {
             }
             KernelMethod::Linear => a.mul(&b).sum(),
             KernelMethod::Polynomial(c, d) => (a.mul(&b).sum() + c).powf(d),
+            KernelMethod::Poly {
+                degree,
+                gamma,
+                coef0,
+            } => (gamma * a.mul(&b).sum() + coef0).powi(degree as i32),
+            KernelMethod::Sigmoid { gamma, coef0 } => (gamma * a.mul(&b).sum() + coef0).tanh(),
+            KernelMethod::Cosine => {
+                let norm = (a.dot(&a) * b.dot(&b)).sqrt();
+                if norm > F::zero() {
+                    a.dot(&b) / norm
+                } else {
+                    F::zero()
+                }
+            }
+            KernelMethod::Chi2 => a
+                .iter()
+                .zip(b.iter())
+                .filter(|(x, y)| **x + **y != F::zero())
+                .map(|(x, y)| F::from(2.0).unwrap() * *x * *y / (*x + *y))
+                .sum(),
+            KernelMethod::Custom(ref fnc) => (fnc.0)(a, b),
         }
     }
 
@@ -210,9 +363,78 @@ impl<F: Float> KernelMethod<F> {
     }
 }
 
+/// Compact description of the kernel function and its parameters
+///
+/// The width of the Gaussian kernel is reported both as `eps` and as `gamma = 1 / eps`.
+impl<F: Float> fmt::Display for KernelMethod<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KernelMethod::Gaussian(eps) => {
+                write!(f, "Gaussian(eps = {}, gamma = {})", eps, F::one() / *eps)
+            }
+            KernelMethod::Linear => write!(f, "Linear"),
+            KernelMethod::Polynomial(c, d) => write!(f, "Polynomial(c = {}, d = {})", c, d),
+            KernelMethod::Poly {
+                degree,
+                gamma,
+                coef0,
+            } => write!(
+                f,
+                "Poly(degree = {}, gamma = {}, coef0 = {})",
+                degree, gamma, coef0
+            ),
+            KernelMethod::Sigmoid { gamma, coef0 } => {
+                write!(f, "Sigmoid(gamma = {}, coef0 = {})", gamma, coef0)
+            }
+            KernelMethod::Cosine => write!(f, "Cosine"),
+            KernelMethod::Chi2 => write!(f, "Chi2"),
+            KernelMethod::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+/// Width `gamma` of the Gaussian kernel `exp(-gamma * ||x - y||^2)`
+///
+/// The heuristics are computed from the training data when the kernel is built and follow the
+/// defaults of scikit-learn.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Gamma<F> {
+    /// `1 / (n_features * X.var())`, with the variance over all entries of the records
+    Scale,
+    /// `1 / n_features`
+    Auto,
+    /// A fixed value
+    Value(F),
+}
+
+impl<F: Float> Gamma<F> {
+    /// Returns the value of gamma for a set of records
+    pub fn value(&self, dataset: &ArrayView2<F>) -> F {
+        let nfeatures = F::from(dataset.ncols()).unwrap();
+
+        match *self {
+            Gamma::Scale => {
+                let nentries = F::from(dataset.len()).unwrap();
+                let mean = dataset.sum() / nentries;
+                let var = dataset.iter().map(|x| (*x - mean) * (*x - mean)).sum::<F>() / nentries;
+
+                // constant records have no scale, fall back to `Auto`
+                if var > F::zero() {
+                    F::one() / (nfeatures * var)
+                } else {
+                    F::one() / nfeatures
+                }
+            }
+            Gamma::Auto => F::one() / nfeatures,
+            Gamma::Value(gamma) => gamma,
+        }
+    }
+}
+
 pub struct KernelParams<F> {
     kind: KernelType,
     method: KernelMethod<F>,
+    gamma: Option<Gamma<F>>,
 }
 
 impl<F: Float> KernelParams<F> {
@@ -227,13 +449,38 @@ impl<F: Float> KernelParams<F> {
 
         self
     }
+
+    /// Set the width of a Gaussian kernel, possibly estimated from the records
+    ///
+    /// This replaces the parameter of `KernelMethod::Gaussian` with `1 / gamma` and has no
+    /// effect on other kernel methods.
+    pub fn gamma(mut self, gamma: Gamma<F>) -> KernelParams<F> {
+        self.gamma = Some(gamma);
+
+        self
+    }
+
+    /// Returns the kernel method with the width of a Gaussian kernel resolved for `dataset`
+    fn method_for(&self, dataset: &ArrayView2<F>) -> KernelMethod<F> {
+        match (&self.method, &self.gamma) {
+            (KernelMethod::Gaussian(_), Some(gamma)) => {
+                KernelMethod::Gaussian(F::one() / gamma.value(dataset))
+            }
+            (method, _) => method.clone(),
+        }
+    }
 }
 
 impl<'a, F: Float> Transformer<&'a Array2<F>, Kernel<ArrayView2<'a, F>>> for KernelParams<F> {
     fn transform(&self, x: &'a Array2<F>) -> Kernel<ArrayView2<'a, F>> {
         let is_linear = self.method.is_linear();
 
-        Kernel::new(x.view(), self.method.clone(), self.kind.clone(), is_linear)
+        Kernel::new(
+            x.view(),
+            self.method_for(&x.view()),
+            self.kind.clone(),
+            is_linear,
+        )
     }
 }
 
@@ -241,7 +488,7 @@ impl<'a, F: Float> Transformer<ArrayView2<'a, F>, Kernel<ArrayView2<'a, F>>> for
     fn transform(&self, x: ArrayView2<'a, F>) -> Kernel<ArrayView2<'a, F>> {
         let is_linear = self.method.is_linear();
 
-        Kernel::new(x, self.method.clone(), self.kind.clone(), is_linear)
+        Kernel::new(x, self.method_for(&x), self.kind.clone(), is_linear)
     }
 }
 
@@ -254,7 +501,7 @@ impl<'a, F: Float, T: Targets>
 
         let kernel = Kernel::new(
             x.records.view(),
-            self.method.clone(),
+            self.method_for(&x.records.view()),
             self.kind.clone(),
             is_linear,
         );
@@ -275,29 +522,44 @@ impl<'a, F: Float, T: Targets>
     ) -> Dataset<Kernel<ArrayView2<'a, F>>, &'a [T::Elem]> {
         let is_linear = self.method.is_linear();
 
-        let kernel = Kernel::new(x.records, self.method.clone(), self.kind.clone(), is_linear);
+        let kernel = Kernel::new(
+            x.records,
+            self.method_for(&x.records),
+            self.kind.clone(),
+            is_linear,
+        );
 
         Dataset::new(kernel, x.targets.as_slice())
     }
 }
 
-fn dense_from_fn<F: Float, D: Data<Elem = F>>(
+/// Evaluate the entries `0..length` of a kernel column or matrix
+///
+/// With the `rayon` feature the entries are evaluated in parallel. Every entry only depends on its
+/// own position, the result is therefore identical with and without the feature.
+#[cfg(feature = "rayon")]
+fn evaluate<F: Float>(length: usize, entry: impl Fn(usize) -> F + Sync + Send) -> Vec<F> {
+    (0..length).into_par_iter().map(entry).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn evaluate<F: Float>(length: usize, entry: impl Fn(usize) -> F) -> Vec<F> {
+    (0..length).map(entry).collect()
+}
+
+fn dense_from_fn<F: Float, D: Data<Elem = F> + Sync>(
     dataset: &ArrayBase<D, Ix2>,
     method: &KernelMethod<F>,
 ) -> Array2<F> {
     let n_observations = dataset.len_of(Axis(0));
-    let mut similarity = Array2::eye(n_observations);
-
-    for i in 0..n_observations {
-        for j in 0..n_observations {
-            let a = dataset.row(i);
-            let b = dataset.row(j);
+    let similarity = evaluate(n_observations * n_observations, |k| {
+        let a = dataset.row(k / n_observations);
+        let b = dataset.row(k % n_observations);
 
-            similarity[(i, j)] = method.distance(a, b);
-        }
-    }
+        method.distance(a, b)
+    });
 
-    similarity
+    Array2::from_shape_vec((n_observations, n_observations), similarity).unwrap()
 }
 
 fn sparse_from_fn<F: Float, D: Data<Elem = F>>(
@@ -318,3 +580,130 @@ fn sparse_from_fn<F: Float, D: Data<Elem = F>>(
 
     data
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Gamma, Kernel, KernelMethod};
+    use linfa::traits::Transformer;
+    use ndarray::{array, Array2};
+
+    #[test]
+    fn test_gamma_heuristics() {
+        // the entries have mean 2 and variance 4
+        let records = array![[0., 4.], [4., 0.]];
+
+        assert_eq!(Gamma::Scale.value(&records.view()), 1. / 8.);
+        assert_eq!(Gamma::Auto.value(&records.view()), 1. / 2.);
+        assert_eq!(Gamma::Value(0.3).value(&records.view()), 0.3);
+
+        // constant records fall back to `Auto`
+        let constant = array![[1., 1.], [1., 1.]];
+        assert_eq!(Gamma::Scale.value(&constant.view()), 1. / 2.);
+
+        let kernel = Kernel::params()
+            .method(KernelMethod::Gaussian(1.0))
+            .gamma(Gamma::Scale)
+            .transform(&records);
+
+        match kernel.method {
+            KernelMethod::Gaussian(eps) => assert_eq!(eps, 8.),
+            _ => panic!("Expected Gaussian kernel"),
+        }
+        // the squared distance between the records is 32
+        assert!((kernel.column(0)[1] - (-4f64).exp()).abs() < 1e-12);
+
+        // other kernel methods are not affected
+        let kernel = Kernel::params()
+            .method(KernelMethod::Linear)
+            .gamma(Gamma::Auto)
+            .transform(&records);
+        assert!(kernel.is_linear());
+    }
+
+    #[test]
+    fn test_apply() {
+        let records = array![[0f64, 1.], [1., 0.], [2., 2.]];
+        let kernel = Kernel::params()
+            .method(KernelMethod::Gaussian(2.0))
+            .transform(&records);
+
+        // a Gaussian kernel is one for identical points
+        let point = array![0.3, -1.5];
+        assert_eq!(kernel.apply(point.view(), point.view()), 1.0);
+        assert!((kernel.apply(records.row(0), records.row(1)) - (-1f64).exp()).abs() < 1e-12);
+
+        // applied to the records themselves this is the kernel matrix
+        let matrix = kernel.apply_rows(records.view());
+        for i in 0..3 {
+            assert_eq!(matrix.column(i).to_vec(), kernel.column(i));
+        }
+
+        let points = array![[0.3, -1.5], [1., 1.]];
+        let matrix = kernel.apply_rows(points.view());
+        assert_eq!(matrix.dim(), (2, 3));
+        assert_eq!(matrix[(1, 2)], kernel.apply(points.row(1), records.row(2)));
+    }
+
+    #[test]
+    fn test_from_fn() {
+        let records = array![[0f64, 1.], [1., 0.], [2., 2.], [-1., 3.]];
+        let dense = Kernel::params()
+            .method(KernelMethod::Linear)
+            .transform(&records);
+        let lazy = Kernel::from_fn(records.view(), |a, b| a.dot(&b));
+
+        assert!(!lazy.is_linear());
+        assert_eq!(lazy.size(), 4);
+        assert_eq!(lazy.diagonal(), dense.diagonal());
+        assert_eq!(lazy.sum(), dense.sum());
+        assert_eq!(lazy.to_upper_triangle(), dense.to_upper_triangle());
+        let rhs = Array2::from_shape_fn((4, 4), |(i, j)| (i * 4 + j) as f64);
+        assert_eq!(lazy.dot(&rhs.view()), dense.dot(&rhs.view()));
+        for i in 0..4 {
+            assert_eq!(lazy.column(i), dense.column(i));
+        }
+
+        // the function is kept by clones of the method
+        let method = lazy.method.clone();
+        assert_eq!(method.distance(records.row(2), records.row(3)), 4.);
+        assert_eq!(method.to_string(), "Custom");
+    }
+
+    #[test]
+    fn test_poly_sigmoid() {
+        let a = array![1f64, 2.];
+        let b = array![3., -1.];
+
+        let poly = KernelMethod::Poly {
+            degree: 3,
+            gamma: 0.5,
+            coef0: 1.,
+        };
+        assert!((poly.distance(a.view(), b.view()) - 3.375).abs() < 1e-12);
+
+        let sigmoid = KernelMethod::Sigmoid {
+            gamma: 0.5,
+            coef0: 1.,
+        };
+        assert!((sigmoid.distance(a.view(), b.view()) - 1.5f64.tanh()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_cosine_chi2() {
+        let a = array![3f64, 4.];
+        let b = array![4., 3.];
+        let zero = array![0., 0.];
+
+        let cosine = KernelMethod::Cosine;
+        assert!((cosine.distance(a.view(), b.view()) - 24. / 25.).abs() < 1e-12);
+        assert!((cosine.distance(a.view(), a.view()) - 1.).abs() < 1e-12);
+        assert_eq!(cosine.distance(a.view(), zero.view()), 0.);
+
+        // features which are zero in both vectors are skipped instead of dividing by zero
+        let chi2 = KernelMethod::Chi2;
+        let c = array![0f64, 2., 0.];
+        let d = array![1., 2., 0.];
+        assert!((chi2.distance(c.view(), d.view()) - 2.).abs() < 1e-12);
+        assert_eq!(chi2.distance(zero.view(), zero.view()), 0.);
+    }
+}
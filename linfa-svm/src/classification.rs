@@ -1,12 +1,59 @@
-use linfa::{dataset::Dataset, dataset::Pr, dataset::Targets, traits::Fit, traits::Predict};
-use ndarray::{Array1, Array2, ArrayBase, ArrayView2, Data, Ix2};
+use linfa::{
+    dataset::Dataset, dataset::Pr, dataset::Targets, metrics::Classifier, traits::Fit,
+    traits::Predict, traits::Transformer,
+};
+use ndarray::{Array1, Array2, ArrayBase, ArrayView1, ArrayView2, Axis, Data, Ix2};
+use sprs::CsMat;
 use std::cmp::Ordering;
-use std::ops::Mul;
 
-use super::permutable_kernel::{Kernel, PermutableKernel, PermutableKernelOneClass};
+use super::error::{Result, SvmError};
+use super::isotonic::IsotonicCalibration;
+use super::permutable_kernel::{
+    sparse_dot, Kernel, Permutable, PermutableKernel, PermutableKernelOneClass, PrecomputedKernel,
+    SparseLinearKernel,
+};
+use super::platt_scaling;
 use super::solver_smo::SolverState;
 use super::SolverParams;
-use super::{Float, Svm, SvmParams};
+use super::{Calibration, Calibrator, Float, KernelCentering, Svm, SvmParams};
+
+/// Number of rows of a precomputed kernel matrix, which are at least validated
+const VALIDATED_ROWS: usize = 1000;
+
+/// Check that a precomputed kernel matrix is symmetric and warn about negative diagonal entries
+///
+/// Checking a row against the corresponding column covers all pairs of a sample. Large matrices
+/// are only checked on evenly spaced rows, between `VALIDATED_ROWS` and twice as many, which
+/// keeps the cost linear in the number of samples. The diagonal is always checked in full.
+fn validate_gram<F: Float>(gram: &Array2<F>) -> Result<()> {
+    let nrows = gram.nrows();
+    let step = usize::max(nrows / VALIDATED_ROWS, 1);
+
+    // allow for small rounding errors in externally computed kernels
+    let tol = F::from(1e-8).unwrap();
+    for i in (0..nrows).step_by(step) {
+        let is_symmetric = gram
+            .row(i)
+            .iter()
+            .zip(gram.column(i).iter())
+            .all(|(a, b)| (*a - *b).abs() <= tol * F::max(F::one(), a.abs()));
+        if !is_symmetric {
+            return Err(SvmError::AsymmetricKernel);
+        }
+    }
+
+    // a negative diagonal entry cannot stem from a positive semi-definite kernel, the curvature
+    // of the dual problem then degenerates and the solver falls back to its minimal step size
+    let negative = gram.diag().iter().filter(|x| **x < F::zero()).count();
+    if negative > 0 {
+        log::warn!(
+            "Kernel matrix has {} negative diagonal entries and is not positive semi-definite",
+            negative
+        );
+    }
+
+    Ok(())
+}
 
 /// Support Vector Classification with C-penalizing parameter
 ///
@@ -27,19 +74,87 @@ use super::{Float, Svm, SvmParams};
 pub fn fit_c<'a, A: Float>(
     params: SolverParams<A>,
     kernel: &'a Kernel<'a, A>,
-    targets: &'a [bool],
+    targets: &[bool],
     cpos: A,
     cneg: A,
-) -> Svm<'a, A, Pr> {
-    let bounds = targets
-        .iter()
-        .map(|x| if *x { cpos } else { cneg })
-        .collect::<Vec<_>>();
+) -> Svm<A, Pr> {
+    fit_c_from(
+        params,
+        kernel,
+        targets,
+        c_bounds(targets, cpos, cneg, None),
+        vec![A::zero(); targets.len()],
+    )
+}
+
+/// Upper bounds `C_i` of the dual coefficients, scaled by the weight of each sample
+fn c_bounds<A: Float>(targets: &[bool], cpos: A, cneg: A, sample_weight: Option<&[A]>) -> Vec<A> {
+    let bounds = targets.iter().map(|x| if *x { cpos } else { cneg });
+
+    match sample_weight {
+        Some(weights) => {
+            assert_eq!(
+                weights.len(),
+                targets.len(),
+                "There are {} sample weights, but {} samples",
+                weights.len(),
+                targets.len()
+            );
+
+            bounds.zip(weights.iter()).map(|(c, w)| c * *w).collect()
+        }
+        None => bounds.collect(),
+    }
+}
+
+/// Binary C-SVC or Nu-SVC problem, which is independent of the kernel and the solver
+pub(crate) enum BinaryProblem<A> {
+    /// Upper bounds and initial unsigned coefficients of the C-SVC problem
+    C { bounds: Vec<A>, alpha: Vec<A> },
+    /// Nu penalizing term of the Nu-SVC problem
+    Nu(A),
+}
+
+impl<A: Float> BinaryProblem<A> {
+    /// Solve the problem on a kernel matrix
+    pub(crate) fn solve<'a>(
+        self,
+        params: SolverParams<A>,
+        kernel: &'a Kernel<'a, A>,
+        targets: &[bool],
+    ) -> Svm<A, Pr> {
+        match self {
+            BinaryProblem::C { bounds, alpha } => {
+                fit_c_from(params, kernel, targets, bounds, alpha)
+            }
+            BinaryProblem::Nu(nu) => fit_nu(params, kernel, targets, nu),
+        }
+    }
+}
+
+/// Fit the C-SVC problem starting from the unsigned coefficients `alpha`
+fn fit_c_from<'a, A: Float>(
+    params: SolverParams<A>,
+    kernel: &'a Kernel<'a, A>,
+    targets: &[bool],
+    bounds: Vec<A>,
+    alpha: Vec<A>,
+) -> Svm<A, Pr> {
+    let permutable = PermutableKernel::new(kernel, targets.to_vec(), params.cache_size);
 
-    let kernel = PermutableKernel::new(kernel, targets.to_vec());
+    solve_c(params, permutable, targets, bounds, alpha).with_support_vectors(kernel)
+}
 
+/// Solve the C-SVC problem with upper bounds `bounds` for any permutable kernel
+fn solve_c<'a, A: Float, K: 'a + Permutable<'a, A>>(
+    params: SolverParams<A>,
+    kernel: K,
+    targets: &[bool],
+    bounds: Vec<A>,
+    alpha: Vec<A>,
+) -> Svm<A, Pr> {
     let solver = SolverState::new(
-        vec![A::zero(); targets.len()],
+        alpha,
         vec![-A::one(); targets.len()],
         targets.to_vec(),
         kernel,
@@ -60,6 +175,55 @@ pub fn fit_c<'a, A: Float>(
     res.with_phantom()
 }
 
+/// Convert the dual coefficients of a previous model to a feasible starting point
+///
+/// The coefficients are clamped to the box constraints `0 <= a_i <= C_i`. Because clamping can
+/// violate the equality constraint `y^T a = 0`, the coefficients of the class with the larger
+/// sum are scaled down afterwards.
+fn warm_start_alpha<A: Float>(alpha: &[A], targets: &[bool], bounds: &[A]) -> Vec<A> {
+    assert_eq!(
+        alpha.len(),
+        targets.len(),
+        "Warm start has {} coefficients, but there are {} samples",
+        alpha.len(),
+        targets.len()
+    );
+
+    let mut alpha = alpha
+        .iter()
+        .zip(bounds.iter())
+        .map(|(a, c)| a.abs().min(*c))
+        .collect::<Vec<_>>();
+
+    let (sum_pos, sum_neg) =
+        alpha
+            .iter()
+            .zip(targets.iter())
+            .fold((A::zero(), A::zero()), |(pos, neg), (a, t)| {
+                if *t {
+                    (pos + *a, neg)
+                } else {
+                    (pos, neg + *a)
+                }
+            });
+
+    if sum_pos != sum_neg {
+        let (scale, scaled_class) = if sum_pos > sum_neg {
+            (sum_neg / sum_pos, true)
+        } else {
+            (sum_pos / sum_neg, false)
+        };
+
+        for (a, t) in alpha.iter_mut().zip(targets.iter()) {
+            if *t == scaled_class {
+                *a *= scale;
+            }
+        }
+    }
+
+    alpha
+}
+
 /// Support Vector Classification with Nu-penalizing term
 ///
 /// This methods solves a binary SVC problem with a penalizing parameter nu between (0, 1). The
@@ -78,9 +242,21 @@ pub fn fit_c<'a, A: Float>(
 pub fn fit_nu<'a, A: Float>(
     params: SolverParams<A>,
     kernel: &'a Kernel<'a, A>,
-    targets: &'a [bool],
+    targets: &[bool],
     nu: A,
-) -> Svm<'a, A, Pr> {
+) -> Svm<A, Pr> {
+    let permutable = PermutableKernel::new(kernel, targets.to_vec(), params.cache_size);
+
+    solve_nu(params, permutable, targets, nu).with_support_vectors(kernel)
+}
+
+/// Solve the Nu-SVC problem for any permutable kernel
+fn solve_nu<'a, A: Float, K: 'a + Permutable<'a, A>>(
+    params: SolverParams<A>,
+    kernel: K,
+    targets: &[bool],
+    nu: A,
+) -> Svm<A, Pr> {
     let mut sum_pos = nu * A::from(targets.len()).unwrap() / A::from(2.0).unwrap();
     let mut sum_neg = nu * A::from(targets.len()).unwrap() / A::from(2.0).unwrap();
     let init_alpha = targets
@@ -98,8 +274,6 @@ pub fn fit_nu<'a, A: Float>(
         })
         .collect::<Vec<_>>();
 
-    let kernel = PermutableKernel::new(kernel, targets.to_vec());
-
     let solver = SolverState::new(
         init_alpha,
         vec![A::zero(); targets.len()],
@@ -130,18 +304,24 @@ pub fn fit_nu<'a, A: Float>(
 /// Support Vector Classification for one-class problems
 ///
 /// This methods solves a binary SVC, when there are no targets available. This can, for example be
-/// useful, when outliers should be rejected.
+/// useful, when outliers should be rejected. The dual problem has the form
+/// ```ignore
+/// min_a 1/2*a^tQ a s.t. e^t a = nu*l, 0 <= a_i <= 1
+/// ```
+/// with `Q_ij = K(x_i, x_j)` the kernel matrix. Following LIBSVM the first `nu*l` variables are
+/// initialized at the upper bound.
 ///
 /// # Parameters
 ///
 /// * `params` - Solver parameters (threshold etc.)
 /// * `kernel` - the kernel matrix `Q`
-/// * `nu` - Nu penalizing term
+/// * `nu` - Nu penalizing term, clamped to the interval (0, 1]
 pub fn fit_one_class<'a, A: Float + num_traits::ToPrimitive>(
     params: SolverParams<A>,
     kernel: &'a Kernel<'a, A>,
     nu: A,
-) -> Svm<'a, A, Pr> {
+) -> Svm<A, Pr> {
+    let nu = nu.min(A::one()).max(A::epsilon());
     let size = kernel.size();
     let n = (nu * A::from(size).unwrap()).to_usize().unwrap();
 
@@ -153,13 +333,13 @@ pub fn fit_one_class<'a, A: Float + num_traits::ToPrimitive>(
         })
         .collect::<Vec<_>>();
 
-    let kernel = PermutableKernelOneClass::new(kernel);
+    let permutable = PermutableKernelOneClass::new(kernel, params.cache_size);
 
     let solver = SolverState::new(
         init_alpha,
         vec![A::zero(); size],
         vec![true; size],
-        kernel,
+        permutable,
         vec![A::one(); size],
         params,
         false,
@@ -167,79 +347,316 @@ pub fn fit_one_class<'a, A: Float + num_traits::ToPrimitive>(
 
     let res = solver.solve();
 
-    res.with_phantom()
+    res.with_support_vectors(kernel).with_phantom()
 }
 
-impl<'a, F: Float> Fit<'a, Kernel<'a, F>, Vec<bool>> for SvmParams<F, Pr> {
-    type Object = Svm<'a, F, Pr>;
+impl<F: Float> SvmParams<F, Pr> {
+    /// Fit a binary model with either the C or Nu penalizing parameter
+    ///
+    /// The C-SVC problem starts from the coefficients `warm_start`, if given, and the penalty of
+    /// each sample is scaled by `sample_weight`, if given.
+    pub(crate) fn fit_kernel<'a>(
+        &self,
+        kernel: &'a Kernel<'a, F>,
+        targets: &[bool],
+        warm_start: Option<&[F]>,
+        sample_weight: Option<&[F]>,
+    ) -> Svm<F, Pr> {
+        self.binary_problem(targets, warm_start, sample_weight)
+            .solve(self.solver_params.clone(), kernel, targets)
+    }
 
-    fn fit(&self, dataset: &'a Dataset<Kernel<'a, F>, Vec<bool>>) -> Self::Object {
-        match (self.c, self.nu) {
-            (Some((c_p, c_n)), _) => fit_c(
-                self.solver_params.clone(),
-                &dataset.records,
-                dataset.targets(),
-                c_p,
-                c_n,
-            ),
-            (None, Some((nu, _))) => fit_nu(
-                self.solver_params.clone(),
+    /// Set up the bounds and initial coefficients of a binary problem
+    pub(crate) fn binary_problem(
+        &self,
+        targets: &[bool],
+        warm_start: Option<&[F]>,
+        sample_weight: Option<&[F]>,
+    ) -> BinaryProblem<F> {
+        match (self.weighted_c(targets), self.nu) {
+            (Some((c_p, c_n)), _) => {
+                let bounds = c_bounds(targets, c_p, c_n, sample_weight);
+                let alpha = match warm_start {
+                    Some(alpha) => warm_start_alpha(alpha, targets, &bounds),
+                    None => vec![F::zero(); targets.len()],
+                };
+
+                BinaryProblem::C { bounds, alpha }
+            }
+            (None, Some((nu, _))) => BinaryProblem::Nu(nu),
+            _ => panic!("Set either C value or Nu value"),
+        }
+    }
+
+    /// Fit a binary model and estimate the Platt scaling parameters, if enabled
+    fn fit_with_probability<'a>(&self, kernel: &'a Kernel<'a, F>, targets: &[bool]) -> Svm<F, Pr> {
+        let mut model = self.fit_kernel(
+            kernel,
+            targets,
+            self.warm_start.as_deref(),
+            self.sample_weight.as_deref(),
+        );
+
+        if self.probability {
+            let decision_values =
+                platt_scaling::cross_validate(kernel, targets, self.seed, |kernel, targets| {
+                    self.fit_kernel(kernel, targets, None, None)
+                });
+
+            model.calibrator = Some(match self.calibration {
+                Calibration::Platt => {
+                    let (a, b) = platt_scaling::platt_newton_method(&decision_values, targets);
+                    Calibrator::Platt(a, b)
+                }
+                Calibration::Isotonic => {
+                    Calibrator::Isotonic(IsotonicCalibration::fit(&decision_values, targets))
+                }
+            });
+        }
+
+        model
+    }
+
+    /// Fit a binary model for each penalty in `c_values`, warm starting from the previous one
+    ///
+    /// This is available as `Svm::params().regularization_path(..)`. The values of `C` are
+    /// visited in ascending order. Increasing `C` only relaxes the upper bounds of the dual
+    /// coefficients, the solution of the previous `C` scaled by the ratio of both penalties is
+    /// therefore feasible. Scaling moves coefficients at the previous upper bound to the new
+    /// one, where most of them stay for overlapping classes. How much work the warm start saves
+    /// depends on the problem, it is most effective for a fine grid of penalties and may even
+    /// take more iterations for large steps. The same `C` is used for both classes, scaled by
+    /// the class and sample weights. Probability estimates are not fitted.
+    ///
+    /// Returns the models in the order of `c_values`.
+    pub fn regularization_path<'a, T: Targets<Elem = bool>>(
+        &self,
+        dataset: &'a Dataset<Kernel<'a, F>, T>,
+        c_values: &[F],
+    ) -> Vec<Svm<F, Pr>> {
+        let targets = dataset.targets().as_slice();
+
+        let mut order = (0..c_values.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            c_values[*a]
+                .partial_cmp(&c_values[*b])
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let mut models: Vec<Option<Svm<F, Pr>>> = (0..c_values.len()).map(|_| None).collect();
+        let mut previous: Option<usize> = None;
+        for idx in order {
+            let warm_start = previous.map(|x| {
+                let ratio = c_values[idx] / c_values[x];
+                models[x]
+                    .as_ref()
+                    .unwrap()
+                    .alpha
+                    .iter()
+                    .map(|a| *a * ratio)
+                    .collect::<Vec<_>>()
+            });
+            let model = self.clone().c(c_values[idx]).fit_kernel(
                 &dataset.records,
-                dataset.targets(),
-                nu,
-            ),
+                targets,
+                warm_start.as_deref(),
+                self.sample_weight.as_deref(),
+            );
+
+            models[idx] = Some(model);
+            previous = Some(idx);
+        }
+
+        models.into_iter().map(|x| x.unwrap()).collect()
+    }
+
+    /// Fit a binary model on records with boolean targets
+    ///
+    /// `true` marks the positive class, whose samples have positive decision values, and `false`
+    /// the negative class. The kernel matrix is built with the method set by
+    /// [kernel](struct.SvmParams.html#method.kernel). This is a shortcut for fitting a `Dataset`
+    /// of the records and targets and returns `SvmError::TargetsMismatch` if the number of
+    /// targets differs from the number of records.
+    pub fn fit_binary(&self, records: ArrayView2<F>, targets: &[bool]) -> Result<Svm<F, Pr>> {
+        self.fit(&Dataset::new(records, targets))
+    }
+
+    /// Fit a binary model on records with integer labels
+    ///
+    /// The samples of the [positive label](struct.SvmParams.html#method.positive_label), by
+    /// default the larger of the two labels, form the positive class and have positive decision
+    /// values. Returns `SvmError::TooManyLabels` for more than two distinct labels,
+    /// `SvmError::UnknownLabel` if the positive label does not occur and
+    /// `SvmError::TargetsMismatch` if the number of labels differs from the number of records.
+    pub fn fit_labels(&self, records: ArrayView2<F>, labels: &[usize]) -> Result<Svm<F, Pr>> {
+        if labels.len() != records.nrows() {
+            return Err(SvmError::TargetsMismatch(records.nrows(), labels.len()));
+        }
+
+        let mut classes = labels.to_vec();
+        classes.sort_unstable();
+        classes.dedup();
+        if classes.len() > 2 {
+            return Err(SvmError::TooManyLabels(classes.len()));
+        }
+
+        let positive = match self.positive_label {
+            Some(label) if !classes.contains(&label) => return Err(SvmError::UnknownLabel(label)),
+            Some(label) => label,
+            None => classes.last().copied().unwrap_or_default(),
+        };
+        let targets = labels.iter().map(|x| *x == positive).collect::<Vec<_>>();
+
+        self.fit_binary(records, &targets)
+    }
+
+    /// Fit a binary model on a precomputed kernel matrix
+    ///
+    /// The Gram matrix `gram` contains the kernel values `K(x_i, x_j)` of all pairs of training
+    /// samples and has to be square and symmetric. Symmetry is validated unless disabled with
+    /// [validate_kernel](struct.SvmParams.html#method.validate_kernel), the matrix is centered
+    /// if enabled with [center_kernel](struct.SvmParams.html#method.center_kernel). Because the
+    /// model has no access to the records, it can only predict with
+    /// [predict_precomputed](struct.Svm.html#method.predict_precomputed). Probability estimates
+    /// are not supported for precomputed kernels.
+    pub fn fit_precomputed(&self, gram: &Array2<F>, targets: &[bool]) -> Result<Svm<F, Pr>> {
+        let (nrows, ncols) = gram.dim();
+        if nrows != ncols {
+            return Err(SvmError::NonSquareKernel(nrows, ncols));
+        }
+        if targets.len() != nrows {
+            return Err(SvmError::TargetsMismatch(nrows, targets.len()));
+        }
+
+        if self.validate_kernel {
+            validate_gram(gram)?;
+        }
+
+        if self.center_kernel {
+            // the diagonal of the centered matrix is used as self distance by the solver
+            let centering = KernelCentering::new(gram);
+            let centered = centering.center(gram.view());
+            let kernel = PrecomputedKernel::new(&centered, targets.to_vec());
+
+            let mut model = self.solve_binary(kernel, targets);
+            model.kernel_centering = Some(centering);
+
+            return Ok(model);
+        }
+
+        let kernel = PrecomputedKernel::new(gram, targets.to_vec());
+
+        Ok(self.solve_binary(kernel, targets))
+    }
+
+    /// Fit a binary model with a linear kernel on sparse records
+    ///
+    /// The records have to be stored in CSR format with one row per training sample. Kernel
+    /// values are computed from the non-zero entries only, which saves memory and time for
+    /// high-dimensional sparse data like bag-of-words features. The normal vector of the
+    /// hyperplane is accumulated from the sparse rows, the model can therefore predict on dense
+    /// records as well as on sparse records with
+    /// [predict_sparse](struct.Svm.html#method.predict_sparse). Probability estimates are not
+    /// supported for sparse records.
+    pub fn fit_sparse(&self, records: &CsMat<F>, targets: &[bool]) -> Result<Svm<F, Pr>> {
+        if !records.is_csr() {
+            return Err(SvmError::NotCsr);
+        }
+        if targets.len() != records.rows() {
+            return Err(SvmError::TargetsMismatch(records.rows(), targets.len()));
+        }
+
+        let kernel =
+            SparseLinearKernel::new(records, targets.to_vec(), self.solver_params.cache_size);
+        let mut model = self.solve_binary(kernel, targets);
+
+        // the dual coefficients are signed, the normal vector is `sum_i alpha_i x_i`
+        let mut linear_decision = Array1::zeros(records.cols());
+        for (row, alpha) in records.outer_iterator().zip(model.alpha.iter()) {
+            for (j, val) in row.iter() {
+                linear_decision[j] += *alpha * *val;
+            }
+        }
+        model.linear_decision = Some(linear_decision);
+
+        Ok(model)
+    }
+
+    /// Solve the binary problem selected by the parameters for any permutable kernel
+    fn solve_binary<'a, K: 'a + Permutable<'a, F>>(
+        &self,
+        kernel: K,
+        targets: &[bool],
+    ) -> Svm<F, Pr> {
+        match (self.weighted_c(targets), self.nu) {
+            (Some((c_p, c_n)), _) => {
+                let bounds = c_bounds(targets, c_p, c_n, self.sample_weight.as_deref());
+                let alpha = match &self.warm_start {
+                    Some(alpha) => warm_start_alpha(alpha, targets, &bounds),
+                    None => vec![F::zero(); targets.len()],
+                };
+
+                solve_c(self.solver_params.clone(), kernel, targets, bounds, alpha)
+            }
+            (None, Some((nu, _))) => solve_nu(self.solver_params.clone(), kernel, targets, nu),
             _ => panic!("Set either C value or Nu value"),
         }
     }
 }
 
+impl<'a, F: Float> Fit<'a, Kernel<'a, F>, Vec<bool>> for SvmParams<F, Pr> {
+    type Object = Svm<F, Pr>;
+
+    fn fit(&self, dataset: &'a Dataset<Kernel<'a, F>, Vec<bool>>) -> Self::Object {
+        self.fit_with_probability(&dataset.records, dataset.targets())
+    }
+}
+
 impl<'a, F: Float> Fit<'a, Kernel<'a, F>, &Vec<bool>> for SvmParams<F, Pr> {
-    type Object = Svm<'a, F, Pr>;
+    type Object = Svm<F, Pr>;
 
     fn fit(&self, dataset: &'a Dataset<Kernel<'a, F>, &Vec<bool>>) -> Self::Object {
-        match (self.c, self.nu) {
-            (Some((c_p, c_n)), _) => fit_c(
-                self.solver_params.clone(),
-                &dataset.records,
-                dataset.targets(),
-                c_p,
-                c_n,
-            ),
-            (None, Some((nu, _))) => fit_nu(
-                self.solver_params.clone(),
-                &dataset.records,
-                dataset.targets(),
-                nu,
-            ),
-            _ => panic!("Set either C value or Nu value"),
-        }
+        self.fit_with_probability(&dataset.records, dataset.targets())
     }
 }
 
 impl<'a, F: Float> Fit<'a, Kernel<'a, F>, &[bool]> for SvmParams<F, Pr> {
-    type Object = Svm<'a, F, Pr>;
+    type Object = Svm<F, Pr>;
 
     fn fit(&self, dataset: &'a Dataset<Kernel<'a, F>, &[bool]>) -> Self::Object {
-        match (self.c, self.nu) {
-            (Some((c_p, c_n)), _) => fit_c(
-                self.solver_params.clone(),
-                &dataset.records,
-                dataset.targets(),
-                c_p,
-                c_n,
-            ),
-            (None, Some((nu, _))) => fit_nu(
-                self.solver_params.clone(),
-                &dataset.records,
-                dataset.targets(),
-                nu,
-            ),
-            _ => panic!("Set either C value or Nu value"),
+        self.fit_with_probability(&dataset.records, dataset.targets())
+    }
+}
+
+impl<'a, F: Float, D: Data<Elem = F>, T: Targets<Elem = bool>> Fit<'a, ArrayBase<D, Ix2>, T>
+    for SvmParams<F, Pr>
+{
+    type Object = Result<Svm<F, Pr>>;
+
+    /// Build the kernel matrix of the records with the configured kernel method and fit a binary
+    /// model on it
+    ///
+    /// Returns `SvmError::TargetsMismatch` if the number of targets differs from the number of
+    /// records.
+    fn fit(&self, dataset: &'a Dataset<ArrayBase<D, Ix2>, T>) -> Self::Object {
+        let targets = dataset.targets.as_slice();
+        if targets.len() != dataset.records.nrows() {
+            return Err(SvmError::TargetsMismatch(
+                dataset.records.nrows(),
+                targets.len(),
+            ));
         }
+
+        let kernel = Kernel::params()
+            .method(self.kernel.clone())
+            .transform(dataset.records.view());
+
+        Ok(self.fit_with_probability(&kernel, targets))
     }
 }
+
 impl<'a, F: Float> Fit<'a, Kernel<'a, F>, &()> for SvmParams<F, Pr> {
-    type Object = Svm<'a, F, Pr>;
+    type Object = Svm<F, Pr>;
 
     fn fit(&self, dataset: &'a Dataset<Kernel<'a, F>, &()>) -> Self::Object {
         match self.nu {
@@ -249,13 +666,120 @@ impl<'a, F: Float> Fit<'a, Kernel<'a, F>, &()> for SvmParams<F, Pr> {
     }
 }
 
+impl<F: Float> Svm<F, Pr> {
+    /// Predict whether a set of observations are inliers
+    ///
+    /// This is useful for one-class models, trained with
+    /// [fit_one_class](SVClassify/fn.fit_one_class.html),
+    /// where a negative decision value marks an observation as an outlier.
+    pub fn predict_inlier<D: Data<Elem = F>>(&self, data: ArrayBase<D, Ix2>) -> Vec<bool> {
+        let predicted: Vec<Pr> = self.predict(data);
+
+        predicted.into_iter().map(|x| *x > 0.0).collect()
+    }
+
+    /// Predict the decision value of a single observation
+    pub fn predict_one(&self, sample: ArrayView1<F>) -> Pr {
+        let predicted: Vec<Pr> = self.predict(sample.insert_axis(Axis(0)));
+
+        predicted[0]
+    }
+
+    /// Predict the probability of the positive class for a set of observations
+    ///
+    /// The decision values are mapped to probabilities with the calibration fitted during
+    /// training, either the sigmoid of Platt scaling or the isotonic step function.
+    ///
+    /// # Panics
+    ///
+    /// If the model was not trained with `probability(true)`.
+    pub fn predict_proba<D: Data<Elem = F>>(&self, data: ArrayBase<D, Ix2>) -> Array1<F> {
+        let calibrator = self
+            .calibrator
+            .as_ref()
+            .expect("Model was not trained with probability estimates");
+
+        data.outer_iter()
+            .map(|data| {
+                let val = self.decision_value(data);
+                match calibrator {
+                    Calibrator::Platt(a, b) => platt_scaling::platt_predict(val, *a, *b),
+                    Calibrator::Isotonic(isotonic) => isotonic.predict(val),
+                }
+            })
+            .collect()
+    }
+
+    /// Predict decision values for sparse records in CSR format
+    ///
+    /// The decision values are computed with sparse dot products against the normal vector of
+    /// the hyperplane, only the non-zero entries of each record are visited.
+    ///
+    /// # Panics
+    ///
+    /// If the model has no linear decision function, because it was not fitted with a linear
+    /// kernel or [fit_sparse](struct.SvmParams.html#method.fit_sparse), or if the records are not
+    /// stored in CSR format.
+    pub fn predict_sparse(&self, records: &CsMat<F>) -> Vec<Pr> {
+        let linear_decision = self
+            .linear_decision
+            .as_ref()
+            .expect("Sparse prediction requires a model with a linear kernel");
+        assert!(records.is_csr(), "Sparse records have to be in CSR format");
+
+        records
+            .outer_iterator()
+            .map(|row| {
+                let val = sparse_dot(row, linear_decision.view()) - self.rho;
+
+                // this is safe because `F` is only implemented for `f32` and `f64`
+                Pr(val.to_f32().unwrap())
+            })
+            .collect()
+    }
+
+    /// Predict decision values from precomputed kernel values
+    ///
+    /// Each row of `kernel_values` contains the kernel values `K(x, x_i)` between an observation
+    /// `x` and all training samples `x_i`, in the order of the training set. Only the columns of
+    /// support vectors contribute to the decision value. If the training kernel was centered,
+    /// the kernel values are centered with the statistics of the training kernel first.
+    pub fn predict_precomputed<D: Data<Elem = F>>(
+        &self,
+        kernel_values: ArrayBase<D, Ix2>,
+    ) -> Vec<Pr> {
+        assert_eq!(
+            kernel_values.ncols(),
+            self.alpha.len(),
+            "Expected kernel values for all training samples"
+        );
+
+        let kernel_values = match &self.kernel_centering {
+            Some(centering) => centering.center(kernel_values.view()),
+            None => kernel_values.to_owned(),
+        };
+
+        kernel_values
+            .outer_iter()
+            .map(|row| {
+                let val = row
+                    .iter()
+                    .zip(self.alpha.iter())
+                    .map(|(k, a)| *k * *a)
+                    .sum::<F>()
+                    - self.rho;
+
+                // this is safe because `F` is only implemented for `f32` and `f64`
+                Pr(val.to_f32().unwrap())
+            })
+            .collect()
+    }
+}
+
 /// Predict a probability with a feature vector
-impl<'a, F: Float> Predict<Array1<F>, Pr> for Svm<'a, F, Pr> {
+impl<F: Float> Predict<Array1<F>, Pr> for Svm<F, Pr> {
     fn predict(&self, data: Array1<F>) -> Pr {
-        let val = match self.linear_decision {
-            Some(ref x) => x.mul(&data).sum() - self.rho,
-            None => self.kernel.weighted_sum(&self.alpha, data.view()) - self.rho,
-        };
+        let val = self.decision_value(data.view());
 
         // this is safe because `F` is only implemented for `f32` and `f64`
         Pr(val.to_f32().unwrap())
@@ -263,14 +787,11 @@ impl<'a, F: Float> Predict<Array1<F>, Pr> for Svm<'a, F, Pr> {
 }
 
 /// Predict a probability with a set of observations
-impl<'a, F: Float, D: Data<Elem = F>> Predict<ArrayBase<D, Ix2>, Vec<Pr>> for Svm<'a, F, Pr> {
+impl<F: Float, D: Data<Elem = F>> Predict<ArrayBase<D, Ix2>, Vec<Pr>> for Svm<F, Pr> {
     fn predict(&self, data: ArrayBase<D, Ix2>) -> Vec<Pr> {
         data.outer_iter()
             .map(|data| {
-                let val = match self.linear_decision {
-                    Some(ref x) => x.mul(&data).sum() - self.rho,
-                    None => self.kernel.weighted_sum(&self.alpha, data.view()) - self.rho,
-                };
+                let val = self.decision_value(data);
 
                 // this is safe because `F` is only implemented for `f32` and `f64`
                 Pr(val.to_f32().unwrap())
@@ -279,8 +800,8 @@ impl<'a, F: Float, D: Data<Elem = F>> Predict<ArrayBase<D, Ix2>, Vec<Pr>> for Sv
     }
 }
 
-impl<'a, F: Float, T: Targets> Predict<Dataset<Array2<F>, T>, Dataset<Array2<F>, Vec<Pr>>>
-    for Svm<'a, F, Pr>
+impl<F: Float, T: Targets> Predict<Dataset<Array2<F>, T>, Dataset<Array2<F>, Vec<Pr>>>
+    for Svm<F, Pr>
 {
     fn predict(&self, data: Dataset<Array2<F>, T>) -> Dataset<Array2<F>, Vec<Pr>> {
         let Dataset { records, .. } = data;
@@ -291,8 +812,7 @@ impl<'a, F: Float, T: Targets> Predict<Dataset<Array2<F>, T>, Dataset<Array2<F>,
 }
 
 impl<'a, F: Float, T: Targets, D: Data<Elem = F>>
-    Predict<&'a Dataset<ArrayBase<D, Ix2>, T>, Dataset<ArrayView2<'a, F>, Vec<Pr>>>
-    for Svm<'a, F, Pr>
+    Predict<&'a Dataset<ArrayBase<D, Ix2>, T>, Dataset<ArrayView2<'a, F>, Vec<Pr>>> for Svm<F, Pr>
 {
     fn predict(
         &self,
@@ -304,19 +824,50 @@ impl<'a, F: Float, T: Targets, D: Data<Elem = F>>
     }
 }
 
+/// Evaluate a binary model with the metrics of `linfa`
+///
+/// Samples with a positive decision value are labeled `true`. The class probabilities are the
+/// calibrated estimates of [predict_proba](struct.Svm.html#method.predict_proba), which panics if
+/// the model was not trained with `probability(true)`.
+impl<F: Float> Classifier<F, bool> for Svm<F, Pr> {
+    fn predict_labels(&self, x: ArrayView2<F>) -> Vec<bool> {
+        x.outer_iter()
+            .map(|x| self.decision_value(x) > F::zero())
+            .collect()
+    }
+
+    fn predict_class_proba(&self, x: ArrayView2<F>) -> (Array2<F>, Vec<bool>) {
+        let positive = self.predict_proba(x);
+        let mut proba = Array2::zeros((x.nrows(), 2));
+        proba.column_mut(0).assign(&positive.mapv(|p| F::one() - p));
+        proba.column_mut(1).assign(&positive);
+
+        (proba, vec![false, true])
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Svm;
-    use linfa::dataset::Dataset;
-    use linfa::metrics::ToConfusionMatrix;
+    use crate::permutable_kernel::{Permutable, PermutableKernel};
+    use crate::solver_smo::SolverState;
+    use crate::{Calibration, ClassWeight, ExitReason, SvmError};
+    use linfa::dataset::{Dataset, Pr, Resample};
+    use linfa::metrics::{Accuracy, Classifier, LogLoss, ToConfusionMatrix};
     use linfa::traits::{Fit, Predict, Transformer};
-    use linfa_kernel::{Kernel, KernelMethod};
+    use linfa_kernel::{Kernel, KernelMethod, KernelType};
 
-    use ndarray::{Array, Array2, Axis};
+    use ndarray::{array, s, Array, Array1, Array2, ArrayView2, Axis};
     use ndarray_rand::rand::SeedableRng;
     use ndarray_rand::rand_distr::Uniform;
     use ndarray_rand::RandomExt;
     use rand_isaac::Isaac64Rng;
+    use sprs::CsMat;
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+    use std::time::Duration;
 
     pub fn generate_convoluted_rings(n_points: usize) -> Array2<f64> {
         let mut out = Array::random((n_points * 2, 2), Uniform::new(0f64, 1.));
@@ -374,65 +925,1664 @@ mod tests {
     }
 
     #[test]
-    fn test_polynomial_classification() {
+    fn test_custom_kernel() {
         let mut rng = Isaac64Rng::seed_from_u64(42);
-        // construct parabolica and classify middle area as positive and borders as negative
-        let records = Array::random_using((40, 1), Uniform::new(-2f64, 2.), &mut rng);
-        let targets = records.map_axis(Axis(1), |x| x[0] * x[0] < 0.5).to_vec();
-        let dataset = Dataset::new(records.clone(), targets);
+        let records = Array::random_using((40, 3), Uniform::new(-1., 1.), &mut rng);
+        let targets = records
+            .outer_iter()
+            .map(|x| x[0] - x[1] + 0.5 * x[2] > 0.)
+            .collect::<Vec<_>>();
+
+        let linear = Dataset::new(records.clone(), targets.clone());
+        let linear = Kernel::params()
+            .method(KernelMethod::Linear)
+            .transform(&linear);
+        let custom = Dataset::new(Kernel::from_fn(records.view(), |a, b| a.dot(&b)), targets);
+
+        let params = Svm::<f64, Pr>::params().pos_neg_weights(1.0, 1.0);
+        let (linear, custom) = (params.fit(&linear), params.fit(&custom));
+
+        let (a, b) = (
+            linear.decision_function(records.view()),
+            custom.decision_function(records.view()),
+        );
+        for (a, b) in a.iter().zip(b.iter()) {
+            assert!((a - b).abs() < 1e-8);
+        }
+        assert!(custom.export_linear().is_none());
+    }
+
+    #[test]
+    fn test_prune() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // two overlapping clusters
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((50, 2), Uniform::new(-1.5, 0.5), &mut rng).view(),
+                Array::random_using((50, 2), Uniform::new(-0.5, 1.5), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..100).map(|x| x < 50).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries.clone(), targets);
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(1.0))
+            .transform(&dataset);
+
+        let mut model = Svm::<f64, Pr>::params()
+            .pos_neg_weights(1.0, 1.0)
+            .fit(&dataset);
+        let (nsupport, sparsity) = (model.nsupport(), model.sparsity());
+        let before = model.decision_function(entries.view());
+        assert!((sparsity - (100 - nsupport) as f64 / 100.).abs() < 1e-12);
+
+        // place the threshold between the two smallest coefficients, which exceed the support
+        // vector cutoff, so that exactly the smallest one is dropped
+        let mut coefficients = model
+            .alpha
+            .iter()
+            .map(|x| x.abs())
+            .filter(|x| *x > 1e-5)
+            .collect::<Vec<_>>();
+        coefficients.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        coefficients.dedup();
+        let threshold = (coefficients[0] + coefficients[1]) / 2.;
+        let dropped = model
+            .alpha
+            .iter()
+            .filter(|x| x.abs() > 1e-5 && x.abs() < threshold)
+            .count();
+        assert!(dropped > 0);
+
+        model.prune(threshold);
+        let after = model.decision_function(entries.view());
+
+        assert_eq!(model.nsupport(), nsupport - dropped);
+        assert!(model.sparsity() > sparsity);
+        assert_eq!(model.export_support_vectors().0.nrows(), model.nsupport());
+        // the Gaussian kernel is bounded by one
+        let bound = threshold * dropped as f64;
+        for (a, b) in before.iter().zip(after.iter()) {
+            assert!((a - b).abs() <= bound);
+            if a.abs() > bound {
+                assert_eq!(*a > 0., *b > 0.);
+            }
+        }
+    }
 
+    #[test]
+    fn test_precompute_linear() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = Array::random_using((40, 5), Uniform::new(-1., 1.), &mut rng);
+        let targets = records
+            .outer_iter()
+            .map(|x| x[0] + x[1] - x[2] > 0.)
+            .collect::<Vec<_>>();
+        let dataset = Dataset::new(records.clone(), targets);
         let dataset = Kernel::params()
-            .method(KernelMethod::Polynomial(0.0, 2.0))
+            .method(KernelMethod::Linear)
             .transform(&dataset);
 
-        // train model with positive and negative weight
-        let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+        let params = Svm::<f64, Pr>::params().pos_neg_weights(1.0, 1.0);
+        let precomputed = params.clone().fit(&dataset);
+        let on_demand = params.precompute_linear(false).fit(&dataset);
 
-        //println!("{:?}", model.predict(Dataset::from(records.clone())).targets());
+        assert!(precomputed.export_linear().is_some());
+        assert!(on_demand.export_linear().is_none());
+        assert_eq!(precomputed.alpha, on_demand.alpha);
 
-        let valid = model
-            .predict(Dataset::from(records))
-            .map_targets(|x| **x > 0.0);
+        let (a, b) = (
+            precomputed.decision_function(records.view()),
+            on_demand.decision_function(records.view()),
+        );
+        for (a, b) in a.iter().zip(b.iter()) {
+            assert!((a - b).abs() < 1e-10);
+        }
 
-        let cm = valid.confusion_matrix(&dataset);
-        assert!(cm.accuracy() > 0.9);
+        let a: Vec<Pr> = precomputed.predict(records.view());
+        let b: Vec<Pr> = on_demand.predict(records.view());
+        for (a, b) in a.iter().zip(b.iter()) {
+            assert_eq!(**a > 0.0, **b > 0.0);
+        }
     }
 
     #[test]
-    fn test_convoluted_rings_classification() {
+    fn test_fit_records() {
         let records = generate_convoluted_rings(10);
         let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
-        let dataset = Dataset::new(records.clone(), targets);
+        let dataset = Dataset::new(records, targets);
 
-        let dataset = Kernel::params()
+        let params = Svm::params().c(10.0).kernel(KernelMethod::Gaussian(50.0));
+        let model = params.fit(&dataset).unwrap();
+
+        // identical to a fit on the kernel dataset
+        let kernel = Kernel::params()
             .method(KernelMethod::Gaussian(50.0))
             .transform(&dataset);
+        let expected = params.fit(&kernel);
+        assert_eq!(model.alpha, expected.alpha);
+        assert_eq!(model.rho, expected.rho);
 
-        // train model with positive and negative weight
-        let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+        let mismatch = Dataset::new(dataset.records.clone(), vec![true; 3]);
+        assert!(params.fit(&mismatch).is_err());
+    }
 
-        let valid = model
-            .predict(Dataset::from(records))
-            .map_targets(|x| **x > 0.0);
+    #[test]
+    fn test_support_vectors() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((10, 2), Uniform::new(-1., -0.5), &mut rng).view(),
+                Array::random_using((10, 2), Uniform::new(0.5, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries, targets.clone());
 
-        let cm = valid.confusion_matrix(&dataset);
-        assert!(cm.accuracy() > 0.9);
+        let dataset = Kernel::params()
+            .method(KernelMethod::Linear)
+            .transform(&dataset);
 
-        // train model with Nu parameter
-        let model = Svm::params().nu_weight(0.01).fit(&dataset);
+        let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
 
-        let valid = model.predict(valid).map_targets(|x| **x > 0.0);
+        let indices = model.support_vector_indices();
+        assert_eq!(indices.len(), model.nsupport());
+        assert!(!indices.is_empty());
 
-        let cm = valid.confusion_matrix(&dataset);
-        assert!(cm.accuracy() > 0.9);
+        // the coefficients carry the sign of the target and fulfill `y^T a = 0`
+        let coefs: Array1<f64> = model.dual_coefficients();
+        assert_eq!(coefs.len(), targets.len());
+        for idx in indices {
+            assert_eq!(coefs[idx] > 0.0, targets[idx]);
+        }
+        assert!(coefs.sum().abs() < 1e-6);
     }
 
     #[test]
-    fn test_reject_classification() {
-        // generate two clusters with 100 samples each
-        let entries = Array::random((100, 2), Uniform::new(-4., 4.));
-        let dataset = Dataset::new(entries.clone(), ());
-
+    fn test_fit_summary() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((10, 2), Uniform::new(-1., -0.5), &mut rng).view(),
+                Array::random_using((10, 2), Uniform::new(0.5, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries, targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Linear)
+            .transform(&dataset);
+
+        let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+        let summary = model.fit_summary();
+
+        let nonzero = model.alpha.iter().filter(|x| **x != 0.0).count();
+        assert_eq!(summary.n_support, nonzero);
+        assert_eq!(summary.rho, model.rho);
+        assert!(summary.converged);
+        assert!(summary.to_string().starts_with("Converged after"));
+    }
+
+    #[test]
+    fn test_probability_estimates() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // two overlapping clusters
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((30, 2), Uniform::new(-1.5, 0.5), &mut rng).view(),
+                Array::random_using((30, 2), Uniform::new(-0.5, 1.5), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..60).map(|x| x < 30).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries.clone(), targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(1.0))
+            .transform(&dataset);
+
+        let model = Svm::params()
+            .pos_neg_weights(1.0, 1.0)
+            .probability(true)
+            .fit(&dataset);
+
+        let probs = model.predict_proba(entries.view());
+
+        assert!(probs.iter().all(|p| *p > 0.0 && *p < 1.0));
+
+        // the probabilities are monotonic in the decision value
+        let mut pairs = entries
+            .outer_iter()
+            .zip(probs.iter())
+            .map(|(x, p)| (model.decision_value(x), *p))
+            .collect::<Vec<_>>();
+        pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        for window in pairs.windows(2) {
+            assert!(window[0].1 <= window[1].1);
+        }
+    }
+
+    #[test]
+    fn test_score_with() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // two overlapping clusters
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((30, 2), Uniform::new(-1.5, 0.5), &mut rng).view(),
+                Array::random_using((30, 2), Uniform::new(-0.5, 1.5), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..60).map(|x| x < 30).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries.clone(), targets.clone());
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(1.0))
+            .transform(&dataset);
+
+        let model = Svm::<f64, Pr>::params()
+            .pos_neg_weights(1.0, 1.0)
+            .probability(true)
+            .fit(&dataset);
+
+        let predicted = model
+            .decision_function(entries.view())
+            .mapv(|x| x > 0.0)
+            .to_vec();
+        let cm = predicted.as_slice().confusion_matrix(targets.as_slice());
+        let accuracy = model.score_with(entries.view(), &targets, Accuracy);
+        assert!((accuracy - cm.accuracy() as f64).abs() < 1e-6);
+
+        let proba = model.predict_proba(entries.view());
+        let expected = -targets
+            .iter()
+            .zip(proba.iter())
+            .map(|(t, p)| if *t { p.ln() } else { (1.0 - p).ln() })
+            .sum::<f64>()
+            / 60.0;
+        let loss = model.score_with(entries.view(), &targets, LogLoss);
+        assert!((loss - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_seed() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // two overlapping clusters of different size
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((15, 2), Uniform::new(-1.5, 0.5), &mut rng).view(),
+                Array::random_using((45, 2), Uniform::new(-0.5, 1.5), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..60).map(|x| x < 15).collect::<Vec<_>>();
+        let dataset =
+            Dataset::new(entries.clone(), targets).resample_balanced(Resample::OverSample, 42);
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(1.0))
+            .transform(&dataset);
+
+        let params = Svm::<f64, Pr>::params()
+            .pos_neg_weights(1.0, 1.0)
+            .probability(true);
+        let fit = |seed| params.clone().seed(seed).fit(&dataset);
+        let (first, second, other) = (fit(7), fit(7), fit(8));
+
+        assert_eq!(first.alpha, second.alpha);
+        assert_eq!(first.rho, second.rho);
+        assert_eq!(
+            first.predict_proba(entries.view()),
+            second.predict_proba(entries.view())
+        );
+
+        // the seed only changes the folds of the calibration
+        assert_eq!(first.alpha, other.alpha);
+        assert_ne!(
+            first.predict_proba(entries.view()),
+            other.predict_proba(entries.view())
+        );
+    }
+
+    #[test]
+    fn test_isotonic_probability_estimates() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((30, 2), Uniform::new(-1.5, 0.5), &mut rng).view(),
+                Array::random_using((30, 2), Uniform::new(-0.5, 1.5), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..60).map(|x| x < 30).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries.clone(), targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(1.0))
+            .transform(&dataset);
+
+        let model = Svm::params()
+            .pos_neg_weights(1.0, 1.0)
+            .probability(true)
+            .calibration(Calibration::Isotonic)
+            .fit(&dataset);
+
+        let probs = model.predict_proba(entries.view());
+        assert!(probs.iter().all(|p| *p >= 0.0 && *p <= 1.0));
+
+        // the probabilities are monotonic in the decision value
+        let mut pairs = entries
+            .outer_iter()
+            .zip(probs.iter())
+            .map(|(x, p)| (model.decision_value(x), *p))
+            .collect::<Vec<_>>();
+        pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        for window in pairs.windows(2) {
+            assert!(window[0].1 <= window[1].1);
+        }
+        assert!(pairs[0].1 < 0.5 && pairs[59].1 > 0.5);
+    }
+
+    #[test]
+    fn test_sparse_kernel() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = Array::random_using((30, 2), Uniform::new(-1., 1.), &mut rng);
+        let targets = records
+            .outer_iter()
+            .map(|x| x[0] + x[1] > 0.)
+            .collect::<Vec<_>>();
+        let dataset = Dataset::new(records.clone(), targets);
+
+        for method in &[KernelMethod::Linear, KernelMethod::Gaussian(0.5)] {
+            let dense = Kernel::params().method(method.clone()).transform(&dataset);
+            // with all other samples as neighbours the sparse kernel stores every entry
+            let sparse = Kernel::params()
+                .method(method.clone())
+                .kind(KernelType::Sparse(29))
+                .transform(&dataset);
+
+            let dense: Svm<f64, Pr> = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dense);
+            let sparse: Svm<f64, Pr> = Svm::params().pos_neg_weights(1.0, 1.0).fit(&sparse);
+
+            assert!((dense.rho - sparse.rho).abs() < 1e-10);
+            for (a, b) in dense.alpha.iter().zip(sparse.alpha.iter()) {
+                assert!((a - b).abs() < 1e-10);
+            }
+
+            let (dense, sparse) = (
+                dense.decision_function(records.view()),
+                sparse.decision_function(records.view()),
+            );
+            for (a, b) in dense.iter().zip(sparse.iter()) {
+                assert!((a - b).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cosine_kernel() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = Array::random_using((30, 3), Uniform::new(-1f64, 1.), &mut rng);
+        let targets = records
+            .outer_iter()
+            .map(|x| x[0] > x[1])
+            .collect::<Vec<_>>();
+
+        // the cosine kernel coincides with the linear kernel on L2-normalized records
+        let norms = records.map_axis(Axis(1), |x| x.dot(&x).sqrt());
+        let records = &records / &norms.insert_axis(Axis(1));
+        let dataset = Dataset::new(records.clone(), targets);
+
+        let fit = |method: KernelMethod<f64>| {
+            let kernel = Kernel::params().method(method).transform(&dataset);
+            let model: Svm<f64, Pr> = Svm::params().pos_neg_weights(1.0, 1.0).fit(&kernel);
+
+            model.decision_function(records.view())
+        };
+
+        // both solutions agree up to the stopping threshold of the solver
+        let (linear, cosine) = (fit(KernelMethod::Linear), fit(KernelMethod::Cosine));
+        for (a, b) in linear.iter().zip(cosine.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_precomputed_kernel() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((10, 2), Uniform::new(-1., 0.2), &mut rng).view(),
+                Array::random_using((10, 2), Uniform::new(-0.2, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+
+        // compute the RBF kernel matrix outside of linfa
+        let method = KernelMethod::Gaussian(1.0f64);
+        let gram: Array2<f64> = Array2::from_shape_fn((20, 20), |(i, j)| {
+            method.distance(entries.row(i), entries.row(j))
+        });
+
+        let params = Svm::params().pos_neg_weights(1.0, 1.0);
+        let precomputed = params.fit_precomputed(&gram, &targets).unwrap();
+
+        let dataset = Dataset::new(entries.clone(), targets.clone());
+        let dataset = Kernel::params().method(method).transform(&dataset);
+        let model = params.fit(&dataset);
+
+        assert!((precomputed.rho - model.rho).abs() < 1e-6);
+        for (a, b) in precomputed.alpha.iter().zip(model.alpha.iter()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+
+        let pred_precomputed = precomputed.predict_precomputed(gram.view());
+        let pred: Vec<Pr> = model.predict(entries.view());
+        for (a, b) in pred_precomputed.iter().zip(pred.iter()) {
+            assert!((**a - **b).abs() < 1e-4);
+        }
+
+        // the Gram matrix has to be square and symmetric
+        let mut asymmetric = gram.clone();
+        asymmetric[(0, 1)] += 1.0;
+        assert!(params.fit_precomputed(&asymmetric, &targets).is_err());
+        assert!(params
+            .fit_precomputed(&gram.slice(s![.., ..19]).to_owned(), &targets)
+            .is_err());
+    }
+
+    #[test]
+    fn test_center_kernel() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((15, 2), Uniform::new(-1., 0.2), &mut rng).view(),
+                Array::random_using((15, 2), Uniform::new(-0.2, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..30).map(|x| x < 15).collect::<Vec<_>>();
+        let centered = &records - &records.mean_axis(Axis(0)).unwrap();
+
+        // the linear kernel of centered records is already centered
+        let gram = centered.dot(&centered.t());
+        let params = Svm::<f64, Pr>::params().pos_neg_weights(1.0, 1.0);
+        let plain = params.fit_precomputed(&gram, &targets).unwrap();
+        let model = params
+            .clone()
+            .center_kernel(true)
+            .fit_precomputed(&gram, &targets)
+            .unwrap();
+
+        for (a, b) in model.alpha.iter().zip(plain.alpha.iter()) {
+            assert!((a - b).abs() < 1e-10);
+        }
+        assert!((model.rho - plain.rho).abs() < 1e-10);
+
+        // shifting the records is undone by the centering, also for new observations
+        let shifted = &records + 5.0;
+        let model = params
+            .clone()
+            .center_kernel(true)
+            .fit_precomputed(&shifted.dot(&shifted.t()), &targets)
+            .unwrap();
+        assert!((model.rho - plain.rho).abs() < 1e-6);
+
+        let test = array![[0.5, 0.5], [-0.5, -0.5]];
+        let pred = model.predict_precomputed((&test + 5.0).dot(&shifted.t()));
+        let pred_plain = plain
+            .predict_precomputed((&test - &records.mean_axis(Axis(0)).unwrap()).dot(&centered.t()));
+        for (a, b) in pred.iter().zip(pred_plain.iter()) {
+            assert!((**a - **b).abs() < 1e-4);
+        }
+        assert!(*pred[0] < 0.0 && *pred[1] > 0.0);
+    }
+
+    #[test]
+    fn test_convergence_history() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // two overlapping classes need many iterations for a large penalty
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((50, 2), Uniform::new(-1., 0.3), &mut rng).view(),
+                Array::random_using((50, 2), Uniform::new(-0.3, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..100).map(|x| x < 50).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries, targets);
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(0.1))
+            .transform(&dataset);
+
+        let params = Svm::params().pos_neg_weights(100.0, 100.0);
+        let model: Svm<f64, Pr> = params.fit(&dataset);
+        assert!(model.convergence_history().is_none());
+
+        // without shrinking the gap of the maximal violating pair is taken over all variables
+        let model: Svm<f64, Pr> = params.record_history(true).fit(&dataset);
+        let history = model.convergence_history().unwrap();
+        assert!(model.converged());
+        assert!(history.len() > 2);
+        for gap in history.windows(2) {
+            assert!(
+                gap[1] <= gap[0],
+                "gap increased from {} to {}",
+                gap[0],
+                gap[1]
+            );
+        }
+        assert!(model.kkt_violation() <= history[history.len() - 1]);
+    }
+
+    #[test]
+    fn test_fit_binary() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = Array::random_using((40, 2), Uniform::new(-1., 1.), &mut rng);
+        let labels = records
+            .outer_iter()
+            .map(|x| if x[0] + x[1] > 0. { 1 } else { 0 })
+            .collect::<Vec<usize>>();
+        let targets = labels.iter().map(|x| *x == 1).collect::<Vec<_>>();
+
+        let params = Svm::<f64, Pr>::params().kernel(KernelMethod::Gaussian(1.0));
+        let model = params.fit_binary(records.view(), &targets).unwrap();
+
+        // integer labels, where `1` is mapped to the positive class
+        let dataset = Dataset::new(records.view(), labels).map_targets(|x| *x == 1);
+        let expected = params.fit(&dataset).unwrap();
+
+        assert_eq!(model.alpha, expected.alpha);
+        assert_eq!(model.rho, expected.rho);
+
+        let predicted = model.predict(records.view());
+        let correct = predicted
+            .iter()
+            .zip(targets.iter())
+            .filter(|(p, t)| (***p > 0.0) == **t)
+            .count();
+        assert!(correct > 35);
+
+        match params.fit_binary(records.view(), &targets[1..]) {
+            Err(SvmError::TargetsMismatch(40, 39)) => {}
+            _ => panic!("Expected a targets mismatch error"),
+        }
+    }
+
+    #[test]
+    fn test_objective() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = Array::random_using((40, 2), Uniform::new(-1., 1.), &mut rng);
+        let targets = records
+            .outer_iter()
+            .map(|x| x[0] * x[1] > 0.)
+            .collect::<Vec<_>>();
+        let dataset = Dataset::new(records, targets.clone());
+        let kernel = Kernel::params()
+            .method(KernelMethod::Gaussian(0.5))
+            .transform(&dataset);
+
+        let params = Svm::<f64, Pr>::params().pos_neg_weights(10.0, 10.0);
+        let model = params.fit(&kernel);
+
+        // a fresh state of the same problem, the solver permuted the variables of its own state
+        let permutable = PermutableKernel::new(&kernel.records, targets.clone(), 100);
+        let state = SolverState::new(
+            vec![0.0; 40],
+            vec![-1.0; 40],
+            targets,
+            permutable,
+            vec![10.0; 40],
+            params.solver_params.clone(),
+            false,
+        );
+
+        // the fitted coefficients are signed by the targets
+        let alpha = model.alpha.iter().map(|x| x.abs()).collect::<Vec<_>>();
+        let objective = state.objective(&alpha);
+        assert!(
+            (objective - model.obj).abs() < 1e-8 * model.obj.abs(),
+            "{} != {}",
+            objective,
+            model.obj
+        );
+        assert_eq!(state.objective(&[0.0; 40]), 0.0);
+    }
+
+    #[test]
+    fn test_positive_label() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = Array::random_using((40, 2), Uniform::new(-1., 1.), &mut rng);
+        let labels = records
+            .outer_iter()
+            .map(|x| if x[0] - x[1] > 0. { 7 } else { 3 })
+            .collect::<Vec<usize>>();
+
+        let params = Svm::<f64, Pr>::params().kernel(KernelMethod::Gaussian(1.0));
+        let default = params.fit_labels(records.view(), &labels).unwrap();
+        let seven = params
+            .clone()
+            .positive_label(7)
+            .fit_labels(records.view(), &labels)
+            .unwrap();
+        let three = params
+            .clone()
+            .positive_label(3)
+            .fit_labels(records.view(), &labels)
+            .unwrap();
+
+        // the larger label is positive by default
+        assert_eq!(default.alpha, seven.alpha);
+
+        let (seven, three) = (
+            seven.decision_function(records.view()),
+            three.decision_function(records.view()),
+        );
+        for (a, b) in seven.iter().zip(three.iter()) {
+            assert!((a + b).abs() < 1e-6);
+            assert!(*a != 0.0);
+
+            // both models assign the same labels
+            let label_seven = if *a > 0.0 { 7 } else { 3 };
+            let label_three = if *b > 0.0 { 3 } else { 7 };
+            assert_eq!(label_seven, label_three);
+        }
+
+        match params
+            .clone()
+            .positive_label(5)
+            .fit_labels(records.view(), &labels)
+        {
+            Err(SvmError::UnknownLabel(5)) => {}
+            _ => panic!("Expected an unknown label error"),
+        }
+        let mut labels = labels;
+        labels[0] = 5;
+        match params.fit_labels(records.view(), &labels) {
+            Err(SvmError::TooManyLabels(3)) => {}
+            _ => panic!("Expected too many labels"),
+        }
+    }
+
+    #[test]
+    fn test_validate_kernel() {
+        let records = Array::random_using(
+            (30, 2),
+            Uniform::new(-1., 1.),
+            &mut Isaac64Rng::seed_from_u64(42),
+        );
+        let targets = records.outer_iter().map(|x| x[0] > 0.).collect::<Vec<_>>();
+        let mut gram = records.dot(&records.t());
+        gram[(3, 17)] += 0.5;
+
+        let params = Svm::params().pos_neg_weights(1.0, 1.0);
+        match params.fit_precomputed(&gram, &targets) {
+            Err(SvmError::AsymmetricKernel) => {}
+            _ => panic!("Expected an asymmetric kernel error"),
+        }
+
+        // the validation can be skipped, the solver then works on the given matrix
+        let model = params
+            .validate_kernel(false)
+            .fit_precomputed(&gram, &targets)
+            .unwrap();
+        assert_eq!(model.alpha.len(), 30);
+    }
+
+    #[test]
+    fn test_sparse_linear() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // bag-of-words like records, where most entries are zero
+        let entries = Array::random_using((40, 30), Uniform::new(0., 1.), &mut rng)
+            .mapv(|x: f64| if x > 0.8 { x } else { 0.0 });
+        let targets = entries
+            .outer_iter()
+            .map(|x| x.slice(s![..15]).sum() > x.slice(s![15..]).sum())
+            .collect::<Vec<_>>();
+        let sparse = CsMat::csr_from_dense(entries.view(), 0.0);
+
+        let params = Svm::params().pos_neg_weights(1.0, 1.0);
+        let sparse_model = params.fit_sparse(&sparse, &targets).unwrap();
+
+        let dataset = Dataset::new(entries.clone(), targets.clone());
+        let dataset = Kernel::params()
+            .method(KernelMethod::Linear)
+            .transform(&dataset);
+        let model = params.fit(&dataset);
+
+        assert!((sparse_model.rho - model.rho).abs() < 1e-10);
+        for (a, b) in sparse_model.alpha.iter().zip(model.alpha.iter()) {
+            assert!((a - b).abs() < 1e-10);
+        }
+
+        let pred_sparse = sparse_model.predict_sparse(&sparse);
+        let pred: Vec<Pr> = model.predict(entries.view());
+        for (a, b) in pred_sparse.iter().zip(pred.iter()) {
+            assert!((**a - **b).abs() < 1e-4);
+        }
+
+        // the records have to be in CSR format and match the targets
+        assert!(params.fit_sparse(&sparse.to_csc(), &targets).is_err());
+        assert!(params.fit_sparse(&sparse, &targets[..39]).is_err());
+    }
+
+    #[test]
+    fn test_max_iter() {
+        let records = generate_convoluted_rings(10);
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(records, targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(50.0))
+            .transform(&dataset);
+
+        let model = Svm::params()
+            .pos_neg_weights(1.0, 1.0)
+            .max_iter(1)
+            .fit(&dataset);
+
+        assert_eq!(model.exit_reason, ExitReason::ReachedIterations);
+        assert!(!model.converged());
+
+        let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+
+        assert_eq!(model.exit_reason, ExitReason::ReachedThreshold);
+        assert!(model.converged());
+    }
+
+    #[test]
+    fn test_exit_reason() {
+        // two well separated points converge in a single step
+        let records = array![[-1., -1.], [1., 1.]];
+        let dataset = Dataset::new(records, vec![false, true]);
+        let dataset = Kernel::params()
+            .method(KernelMethod::Linear)
+            .transform(&dataset);
+
+        let model: Svm<f64, Pr> = Svm::params().fit(&dataset);
+        assert_eq!(model.exit_reason(), ExitReason::ReachedThreshold);
+        assert_eq!(model.exit_reason().to_string(), "reached threshold");
+        assert_eq!(
+            ExitReason::ReachedIterations.to_string(),
+            "reached maximal iterations"
+        );
+    }
+
+    #[test]
+    fn test_single_precision() {
+        let records = generate_convoluted_rings(20);
+        let targets = (0..40).map(|x| x < 20).collect::<Vec<_>>();
+
+        let dataset = Dataset::new(records.clone(), targets.clone());
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(50.0))
+            .transform(&dataset);
+        let model = Svm::params().pos_neg_weights(10.0, 10.0).fit(&dataset);
+        let valid = model
+            .predict(Dataset::from(records.clone()))
+            .map_targets(|x| **x > 0.0);
+        let accuracy = valid.confusion_matrix(&dataset).accuracy();
+
+        let records_f32 = records.mapv(|x| x as f32);
+        let dataset_f32 = Dataset::new(records_f32.clone(), targets);
+        let dataset_f32 = Kernel::params()
+            .method(KernelMethod::Gaussian(50.0))
+            .transform(&dataset_f32);
+        let model_f32 = Svm::params()
+            .pos_neg_weights(10.0f32, 10.0)
+            .fit(&dataset_f32);
+        let valid_f32 = model_f32
+            .predict(Dataset::from(records_f32))
+            .map_targets(|x| **x > 0.0);
+        let accuracy_f32 = valid_f32.confusion_matrix(&dataset_f32).accuracy();
+
+        // the stopping threshold is relaxed to the precision of `f32`
+        assert!(model_f32.converged());
+        assert!(model_f32.rho.is_finite());
+        assert_eq!(accuracy, 1.0);
+        assert_eq!(accuracy_f32, accuracy);
+    }
+
+    #[test]
+    fn test_plateau() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // overlapping classes and a large penalty, the objective converges long before the
+        // KKT conditions are met
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((100, 2), Uniform::new(-1., 0.3), &mut rng).view(),
+                Array::random_using((100, 2), Uniform::new(-0.3, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..200).map(|x| x < 100).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries.clone(), targets);
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(0.1))
+            .transform(&dataset);
+
+        let full = Svm::params().pos_neg_weights(100.0, 100.0).fit(&dataset);
+        let early = Svm::params()
+            .pos_neg_weights(100.0, 100.0)
+            .plateau_tol(1e-4, 3)
+            .fit(&dataset);
+
+        assert_eq!(early.exit_reason, ExitReason::ReachedThreshold);
+        assert!(early.iterations < full.iterations);
+
+        let (full, early): (Array1<f64>, Array1<f64>) = (
+            full.decision_function(entries.view()),
+            early.decision_function(entries.view()),
+        );
+        // the decision values are close to the ones of the full solve
+        for (a, b) in full.iter().zip(early.iter()) {
+            assert!((a - b).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn test_sample_weight() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((20, 2), Uniform::new(-1., 0.3), &mut rng).view(),
+                Array::random_using((20, 2), Uniform::new(-0.3, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..40).map(|x| x < 20).collect::<Vec<_>>();
+        let method = KernelMethod::Gaussian(1.0);
+
+        let dataset = Dataset::new(records.clone(), targets.clone());
+        let kernel = Kernel::params().method(method.clone()).transform(&dataset);
+        let unweighted: Svm<f64, Pr> = Svm::params().pos_neg_weights(1.0, 1.0).fit(&kernel);
+
+        // the weight of a support vector is zero
+        let idx = unweighted.alpha.iter().position(|a| *a != 0.0).unwrap();
+        let mut weights = vec![1.0; 40];
+        weights[idx] = 0.0;
+        let weighted: Svm<f64, Pr> = Svm::params()
+            .pos_neg_weights(1.0, 1.0)
+            .sample_weight(weights)
+            .fit(&kernel);
+        assert_eq!(weighted.alpha[idx], 0.0);
+
+        // the support vector is removed
+        let indices = (0..40).filter(|i| *i != idx).collect::<Vec<_>>();
+        let reduced = Dataset::new(
+            records.select(Axis(0), &indices),
+            indices.iter().map(|i| targets[*i]).collect::<Vec<_>>(),
+        );
+        let kernel = Kernel::params().method(method).transform(&reduced);
+        let removed: Svm<f64, Pr> = Svm::params().pos_neg_weights(1.0, 1.0).fit(&kernel);
+
+        for (i, b) in indices.iter().zip(removed.alpha.iter()) {
+            assert!((weighted.alpha[*i] - b).abs() < 1e-5);
+        }
+        assert!((weighted.rho - removed.rho).abs() < 1e-5);
+
+        let (weighted, removed): (Array1<f64>, Array1<f64>) = (
+            weighted.decision_function(records.view()),
+            removed.decision_function(records.view()),
+        );
+        for (a, b) in weighted.iter().zip(removed.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sample_weight_negative() {
+        Svm::<f64, Pr>::params().sample_weight(vec![1.0, -1.0]);
+    }
+
+    /// Counts the warnings about solvers, which did not converge
+    struct ConvergenceWarnings;
+
+    static CONVERGENCE_WARNINGS: AtomicUsize = AtomicUsize::new(0);
+
+    impl log::Log for ConvergenceWarnings {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= log::Level::Warn
+        }
+
+        fn log(&self, record: &log::Record) {
+            if record.level() == log::Level::Warn
+                && record.args().to_string().contains("did not converge")
+            {
+                CONVERGENCE_WARNINGS.fetch_add(1, AtomicOrdering::SeqCst);
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_non_convergence_warning() {
+        static LOGGER: ConvergenceWarnings = ConvergenceWarnings;
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Warn);
+
+        let records = generate_convoluted_rings(10);
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(records, targets);
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(50.0))
+            .transform(&dataset);
+
+        let converged = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+        assert!(converged.kkt_violation() <= 1e-7);
+
+        let warnings = CONVERGENCE_WARNINGS.load(AtomicOrdering::SeqCst);
+        let model = Svm::params()
+            .pos_neg_weights(1.0, 1.0)
+            .max_iter(2)
+            .fit(&dataset);
+
+        assert_eq!(model.iterations(), 2);
+        assert!(!model.converged());
+        assert!(model.kkt_violation() > converged.kkt_violation());
+        assert!(CONVERGENCE_WARNINGS.load(AtomicOrdering::SeqCst) > warnings);
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // random labels, which need many iterations to be separated
+        let entries = Array::random_using((1000, 2), Uniform::new(-1f64, 1.), &mut rng);
+        let targets = (0..1000).map(|x| x < 500).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries, targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(0.1))
+            .transform(&dataset);
+
+        let model = Svm::params()
+            .pos_neg_weights(100.0, 100.0)
+            .time_limit(Duration::from_nanos(1))
+            .fit(&dataset);
+
+        assert_eq!(model.exit_reason, ExitReason::ReachedTimeLimit);
+        assert!(!model.converged());
+    }
+
+    #[test]
+    fn test_callback() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // random labels, which need many iterations to be separated
+        let entries = Array::random_using((1000, 2), Uniform::new(-1f64, 1.), &mut rng);
+        let targets = (0..1000).map(|x| x < 500).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries, targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(0.1))
+            .transform(&dataset);
+
+        let calls = Rc::new(Cell::new(0));
+        let calls_callback = calls.clone();
+        let model = Svm::params()
+            .pos_neg_weights(1.0, 1.0)
+            .callback(move |_, _| {
+                calls_callback.set(calls_callback.get() + 1);
+                true
+            })
+            .fit(&dataset);
+
+        assert!(calls.get() > 0);
+        assert!(model.converged());
+
+        let model = Svm::params()
+            .pos_neg_weights(1.0, 1.0)
+            .callback(|iter, _| iter < 1000)
+            .fit(&dataset);
+
+        assert_eq!(model.exit_reason, ExitReason::Aborted);
+        assert!(model.iterations < 2000);
+    }
+
+    #[test]
+    fn test_warm_start() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // two overlapping clusters
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((100, 2), Uniform::new(-1f64, 0.3), &mut rng).view(),
+                Array::random_using((100, 2), Uniform::new(-0.3, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..200).map(|x| x < 100).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries, targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(0.5))
+            .transform(&dataset);
+
+        let previous = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+
+        let cold = Svm::params().pos_neg_weights(1.2, 1.2).fit(&dataset);
+        let warm = Svm::params()
+            .pos_neg_weights(1.2, 1.2)
+            .warm_start(previous.alpha.clone())
+            .fit(&dataset);
+
+        assert!(warm.converged());
+        assert!(warm.iterations < cold.iterations);
+        assert!((warm.obj - cold.obj).abs() < 1e-3 * cold.obj.abs());
+
+        // clamping to a smaller C still yields a valid solution
+        let smaller = Svm::params()
+            .pos_neg_weights(0.5, 0.5)
+            .warm_start(previous.alpha)
+            .fit(&dataset);
+        let reference = Svm::params().pos_neg_weights(0.5, 0.5).fit(&dataset);
+
+        assert!((smaller.obj - reference.obj).abs() < 1e-3 * reference.obj.abs());
+    }
+
+    #[test]
+    fn test_class_weight() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // overlapping clusters with a minority of positive samples
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((10, 2), Uniform::new(-0.5, 1.5), &mut rng).view(),
+                Array::random_using((90, 2), Uniform::new(-1.5, 0.5), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..100).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries.clone(), targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Linear)
+            .transform(&dataset);
+
+        let unweighted = Svm::params().pos_neg_weights(0.1, 0.1).fit(&dataset);
+        let weighted = Svm::params()
+            .pos_neg_weights(0.1, 0.1)
+            .class_weight(ClassWeight::Auto)
+            .fit(&dataset);
+
+        // the boundary moves toward the negative class, more samples are classified positive
+        let npos = |model: &Svm<f64, Pr>| {
+            model
+                .decision_function(entries.view())
+                .iter()
+                .filter(|x| **x > 0.0)
+                .count()
+        };
+        assert!(npos(&weighted) > npos(&unweighted));
+
+        let mut weights = HashMap::new();
+        weights.insert(true, 5.0);
+        let custom = Svm::params()
+            .pos_neg_weights(0.1, 0.1)
+            .class_weight(ClassWeight::Custom(weights))
+            .fit(&dataset);
+        let explicit = Svm::params().pos_neg_weights(0.5, 0.1).fit(&dataset);
+
+        assert!((custom.rho - explicit.rho).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_regularization_path() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // two overlapping clusters
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((50, 2), Uniform::new(-1f64, 0.3), &mut rng).view(),
+                Array::random_using((50, 2), Uniform::new(-0.3, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..100).map(|x| x < 50).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries.clone(), targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(0.5))
+            .transform(&dataset);
+
+        let c_values = [10.0, 0.1, 1.0];
+        let path = Svm::params().regularization_path(&dataset, &c_values);
+        assert_eq!(path.len(), 3);
+
+        for (c, model) in c_values.iter().zip(path.iter()) {
+            let cold = Svm::params().pos_neg_weights(*c, *c).fit(&dataset);
+
+            assert!(model.converged());
+            assert!((model.obj - cold.obj).abs() < 1e-3 * cold.obj.abs());
+            assert!(model.alpha.iter().all(|x| x.abs() <= *c + 1e-12));
+        }
+
+        // the final model of the path predicts like a cold start at the largest C
+        let cold = Svm::params().pos_neg_weights(10.0, 10.0).fit(&dataset);
+        let pred: Vec<Pr> = path[0].predict(entries.view());
+        let pred_cold: Vec<Pr> = cold.predict(entries.view());
+        assert_eq!(
+            pred.iter().map(|x| **x > 0.0).collect::<Vec<_>>(),
+            pred_cold.iter().map(|x| **x > 0.0).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_warm_start_length() {
+        let records = generate_convoluted_rings(10);
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(records, targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(50.0))
+            .transform(&dataset);
+
+        Svm::params()
+            .pos_neg_weights(1.0, 1.0)
+            .warm_start(vec![0.0; 5])
+            .fit(&dataset);
+    }
+
+    #[test]
+    fn test_kernel_cache() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // two overlapping clusters with a few thousand points
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((1000, 2), Uniform::new(-1f64, 0.3), &mut rng).view(),
+                Array::random_using((1000, 2), Uniform::new(-0.3, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..2000).map(|x| x < 1000).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries, targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(0.5))
+            .transform(&dataset);
+
+        let uncached = Svm::params()
+            .pos_neg_weights(1.0, 1.0)
+            .cache_size(0)
+            .fit(&dataset);
+        let cached = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+
+        // the cache changes the number of kernel evaluations, but not the solution
+        assert_eq!(cached.iterations, uncached.iterations);
+        assert!((cached.rho - uncached.rho).abs() < 1e-10);
+        assert!(cached.kernel_evaluations() * 2 < uncached.kernel_evaluations());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_kernel_columns() {
+        use rayon_crate::ThreadPoolBuilder;
+
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((300, 2), Uniform::new(-1f64, 0.3), &mut rng).view(),
+                Array::random_using((300, 2), Uniform::new(-0.3, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..600).map(|x| x < 300).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries.clone(), targets.clone());
+        let sparse = CsMat::csr_from_dense(entries.view(), 0.0);
+
+        // the lazy kernel evaluates every requested column in the solver
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(0.5))
+            .kind(KernelType::Lazy)
+            .transform(&dataset);
+
+        let fit = |threads| {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+
+            pool.install(|| {
+                let params = Svm::<f64, Pr>::params()
+                    .pos_neg_weights(1.0, 1.0)
+                    .cache_size(0);
+
+                (
+                    params.fit(&dataset),
+                    params.fit_sparse(&sparse, &targets).unwrap(),
+                )
+            })
+        };
+        let (serial, parallel) = (fit(1), fit(4));
+
+        // the parallel evaluation has to give bitwise identical solutions
+        for (serial, parallel) in &[(serial.0, parallel.0), (serial.1, parallel.1)] {
+            assert_eq!(serial.iterations, parallel.iterations);
+            assert_eq!(serial.rho.to_bits(), parallel.rho.to_bits());
+            assert_eq!(serial.alpha.len(), parallel.alpha.len());
+            assert!(serial
+                .alpha
+                .iter()
+                .zip(parallel.alpha.iter())
+                .all(|(a, b)| a.to_bits() == b.to_bits()));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((10, 2), Uniform::new(-1., -0.5), &mut rng).view(),
+                Array::random_using((10, 2), Uniform::new(0.5, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries.clone(), targets);
+
+        for method in &[KernelMethod::Linear, KernelMethod::Gaussian(1.0)] {
+            let dataset = Kernel::params().method(method.clone()).transform(&dataset);
+            let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+
+            let serialized = bincode::serialize(&model).unwrap();
+            let loaded: Svm<f64, Pr> = bincode::deserialize(&serialized).unwrap();
+
+            let pred: Vec<Pr> = model.predict(entries.view());
+            let pred_loaded: Vec<Pr> = loaded.predict(entries.view());
+            assert_eq!(pred, pred_loaded);
+            assert_eq!(model.alpha, loaded.alpha);
+        }
+    }
+
+    #[test]
+    fn test_decision_function() {
+        let records = generate_convoluted_rings(10);
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(records.clone(), targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(50.0))
+            .transform(&dataset);
+
+        let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+
+        let decision = model.decision_function(records.view());
+        let predicted: Vec<Pr> = model.predict(records.view());
+
+        assert_eq!(decision.len(), predicted.len());
+        for (val, pred) in decision.iter().zip(predicted.iter()) {
+            assert_eq!(*val > 0.0, **pred > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_rho_and_r() {
+        let records = generate_convoluted_rings(10);
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(records.clone(), targets);
+
+        let method = KernelMethod::Gaussian(50.0);
+        let dataset = Kernel::params().method(method.clone()).transform(&dataset);
+
+        let c_model: Svm<f64, Pr> = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+        let nu_model: Svm<f64, Pr> = Svm::params().nu_weight(0.5).fit(&dataset);
+        assert!(c_model.r().is_none());
+        assert!(nu_model.r().unwrap() > 0.0);
+
+        // the decision function evaluated from the dual coefficients and the offset
+        for model in &[c_model, nu_model] {
+            let coefs = model.dual_coefficients();
+            let expected = model.decision_function(records.view());
+
+            for (x, expected) in records.outer_iter().zip(expected.iter()) {
+                let value = records
+                    .outer_iter()
+                    .zip(coefs.iter())
+                    .map(|(sv, coef)| coef * method.distance(sv, x))
+                    .sum::<f64>()
+                    - model.rho();
+
+                assert!((value - expected).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decision_cache() {
+        let records = generate_convoluted_rings(10);
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(records.clone(), targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(50.0))
+            .transform(&dataset);
+
+        let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+
+        let cache = model.decision_cache(records.view());
+        let decision = model.decision_function(records.view());
+        assert_eq!(cache.values(), decision.view());
+
+        for cutoff in &[-1.0, -0.1, 0.0, 0.3, 1.0] {
+            let expected = decision.iter().map(|x| x > cutoff).collect::<Vec<_>>();
+            assert_eq!(cache.threshold(*cutoff), expected);
+        }
+
+        let predicted: Vec<Pr> = model.predict(records.view());
+        let predicted = predicted.iter().map(|x| **x > 0.0).collect::<Vec<_>>();
+        assert_eq!(cache.threshold(0.0), predicted);
+    }
+
+    #[test]
+    fn test_model_description() {
+        let records = generate_convoluted_rings(10);
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(records, targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(4.0))
+            .transform(&dataset);
+        let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+
+        match model.kernel_method() {
+            Some(KernelMethod::Gaussian(eps)) => assert_eq!(*eps, 4.0),
+            method => panic!("Expected a Gaussian kernel, got {:?}", method),
+        }
+
+        let description = model.to_string();
+        assert!(description.contains("kernel Gaussian(eps = 4, gamma = 0.25)"));
+        assert!(description.contains(&format!("{} support vectors", model.nsupport())));
+        assert!(description.contains(&format!("rho = {}", model.rho)));
+
+        // the kernel of a precomputed matrix is unknown
+        let columns = (0..20)
+            .map(|i| dataset.records.column(i))
+            .collect::<Vec<_>>();
+        let gram = Array2::from_shape_fn((20, 20), |(i, j)| columns[j][i]);
+        let precomputed = Svm::params()
+            .pos_neg_weights(1.0, 1.0)
+            .fit_precomputed(&gram, dataset.targets())
+            .unwrap();
+        assert!(precomputed.kernel_method().is_none());
+        assert!(!precomputed.to_string().contains("kernel"));
+    }
+
+    #[test]
+    fn test_export() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((10, 2), Uniform::new(-1., -0.5), &mut rng).view(),
+                Array::random_using((10, 2), Uniform::new(0.5, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(records.clone(), targets);
+
+        let linear = Kernel::params()
+            .method(KernelMethod::Linear)
+            .transform(&dataset);
+        let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&linear);
+
+        let (weights, rho) = model.export_linear().unwrap();
+        let decision = model.decision_function(records.view());
+        for (x, val) in records.outer_iter().zip(decision.iter()) {
+            assert_eq!((&x * &weights).sum() - rho, *val);
+        }
+
+        let gaussian = Kernel::params()
+            .method(KernelMethod::Gaussian(1.0))
+            .transform(&dataset);
+        let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&gaussian);
+        assert!(model.export_linear().is_none());
+
+        let (support_vectors, coefficients, rho) = model.export_support_vectors();
+        assert_eq!(support_vectors.nrows(), coefficients.len());
+
+        let method = KernelMethod::Gaussian(1.0);
+        let decision = model.decision_function(records.view());
+        for (x, val) in records.outer_iter().zip(decision.iter()) {
+            let exported = support_vectors
+                .outer_iter()
+                .zip(coefficients.iter())
+                .map(|(sv, c)| method.distance(sv, x) * c)
+                .sum::<f64>()
+                - rho;
+            assert!((exported - val).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_predict_one() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((10, 2), Uniform::new(-1., -0.5), &mut rng).view(),
+                Array::random_using((10, 2), Uniform::new(0.5, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(records.clone(), targets);
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(1.0))
+            .transform(&dataset);
+        let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+
+        let predicted: Vec<Pr> = model.predict(records.view());
+        for (row, expected) in records.outer_iter().zip(predicted.iter()) {
+            assert_eq!(*model.predict_one(row), **expected);
+        }
+    }
+
+    /// Kernel counting the swaps of the shrinking heuristic
+    struct CountSwaps<'a> {
+        kernel: PermutableKernel<'a, f64>,
+        swaps: Rc<Cell<usize>>,
+    }
+
+    impl<'a> Permutable<'a, f64> for CountSwaps<'a> {
+        fn swap_indices(&mut self, i: usize, j: usize) {
+            self.swaps.set(self.swaps.get() + 1);
+            self.kernel.swap_indices(i, j);
+        }
+
+        fn distances(&self, idx: usize, length: usize) -> Vec<f64> {
+            self.kernel.distances(idx, length)
+        }
+
+        fn self_distance(&self, idx: usize) -> f64 {
+            self.kernel.self_distance(idx)
+        }
+
+        fn inner(&self) -> Option<&'a Kernel<ArrayView2<'a, f64>>> {
+            self.kernel.inner()
+        }
+
+        fn kernel_evaluations(&self) -> usize {
+            self.kernel.kernel_evaluations()
+        }
+    }
+
+    #[test]
+    fn test_shrinking_reproducible() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // two overlapping clusters, which need more iterations than samples
+        let records = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((50, 2), Uniform::new(-1., 0.6), &mut rng).view(),
+                Array::random_using((50, 2), Uniform::new(-0.6, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..100).map(|x| x < 50).collect::<Vec<_>>();
+        let dataset = Dataset::new(records, targets.clone());
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(0.5))
+            .transform(&dataset);
+
+        // without shrinking the solver is deterministic
+        let params = Svm::<f64, Pr>::params()
+            .pos_neg_weights(100.0, 100.0)
+            .eps(1e-8)
+            .shrinking(false);
+        let first = params.fit(&dataset);
+        let second = params.fit(&dataset);
+        assert_eq!(first.alpha, second.alpha);
+        assert_eq!(first.rho, second.rho);
+        assert_eq!(first.iterations, second.iterations);
+
+        // shrinking is first attempted after `nsamples` iterations
+        assert!(first.iterations > 100, "{}", first.iterations);
+        let swaps = Rc::new(Cell::new(0));
+        let kernel = CountSwaps {
+            kernel: PermutableKernel::new(&dataset.records, targets.clone(), 100),
+            swaps: swaps.clone(),
+        };
+        let shrunk = SolverState::new(
+            vec![0.0; 100],
+            vec![-1.0; 100],
+            targets,
+            kernel,
+            vec![100.0; 100],
+            params.clone().shrinking(true).solver_params,
+            false,
+        )
+        .solve();
+
+        // the variables were reordered, but the solution is mapped back to the order of the
+        // samples and coincides up to the tolerance of the solver
+        assert!(swaps.get() > 0);
+        assert!((first.rho - shrunk.rho).abs() < 1e-6);
+        for (a, b) in first.alpha.iter().zip(shrunk.alpha.iter()) {
+            assert!((a.abs() - b).abs() < 1e-5, "{} != {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_polynomial_classification() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // construct parabolica and classify middle area as positive and borders as negative
+        let records = Array::random_using((40, 1), Uniform::new(-2f64, 2.), &mut rng);
+        let targets = records.map_axis(Axis(1), |x| x[0] * x[0] < 0.5).to_vec();
+        let dataset = Dataset::new(records.clone(), targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Polynomial(0.0, 2.0))
+            .transform(&dataset);
+
+        // train model with positive and negative weight
+        let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+
+        //println!("{:?}", model.predict(Dataset::from(records.clone())).targets());
+
+        let valid = model
+            .predict(Dataset::from(records))
+            .map_targets(|x| **x > 0.0);
+
+        let cm = valid.confusion_matrix(&dataset);
+        assert!(cm.accuracy() > 0.9);
+    }
+
+    #[test]
+    fn test_poly_degree_one_is_linear() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // two overlapping clusters
+        let records = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((20, 2), Uniform::new(-1.5f64, 0.5), &mut rng).view(),
+                Array::random_using((20, 2), Uniform::new(-0.5, 1.5), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..40).map(|x| x < 20).collect::<Vec<_>>();
+        let dataset = Dataset::new(records.clone(), targets);
+
+        let linear = Kernel::params()
+            .method(KernelMethod::Linear)
+            .transform(&dataset);
+        let poly = Kernel::params()
+            .method(KernelMethod::Poly {
+                degree: 1,
+                gamma: 1.0,
+                coef0: 0.0,
+            })
+            .transform(&dataset);
+
+        let linear = Svm::params().pos_neg_weights(1.0, 1.0).fit(&linear);
+        let poly = Svm::params().pos_neg_weights(1.0, 1.0).fit(&poly);
+
+        let linear = linear.decision_function(records.view());
+        let poly = poly.decision_function(records.view());
+
+        for (a, b) in linear.iter().zip(poly.iter()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_convoluted_rings_classification() {
+        let records = generate_convoluted_rings(10);
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dataset = Dataset::new(records.clone(), targets);
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(50.0))
+            .transform(&dataset);
+
+        // train model with positive and negative weight
+        let model = Svm::params().pos_neg_weights(1.0, 1.0).fit(&dataset);
+
+        let valid = model
+            .predict(Dataset::from(records))
+            .map_targets(|x| **x > 0.0);
+
+        let cm = valid.confusion_matrix(&dataset);
+        assert!(cm.accuracy() > 0.9);
+
+        // train model with Nu parameter
+        let model = Svm::params().nu_weight(0.01).fit(&dataset);
+
+        let valid = model.predict(valid).map_targets(|x| **x > 0.0);
+
+        let cm = valid.confusion_matrix(&dataset);
+        assert!(cm.accuracy() > 0.9);
+    }
+
+    #[test]
+    fn test_reject_classification() {
+        // generate two clusters with 100 samples each
+        let entries = Array::random((100, 2), Uniform::new(-4., 4.));
+        let dataset = Dataset::new(entries.clone(), ());
+
         let dataset = Kernel::params()
             .method(KernelMethod::Gaussian(100.0))
             .transform(&dataset);
@@ -459,4 +2609,27 @@ mod tests {
         // at least 95% should be correctly rejected
         assert!((rejected as f32) / (total as f32) > 0.95);
     }
+
+    #[test]
+    fn test_one_class_outlier() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // a single cluster around the origin
+        let entries = Array::random_using((50, 2), Uniform::new(-1., 1.), &mut rng);
+        let dataset = Dataset::new(entries, ());
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(1.0))
+            .transform(&dataset);
+
+        let model = Svm::params().nu_weight(0.1).fit(&dataset);
+
+        // the center is an inlier, a clearly distant point an outlier
+        let pred = model.predict_inlier(ndarray::array![[0., 0.], [8., 8.]]);
+        assert_eq!(pred, vec![true, false]);
+
+        // a large nu is clamped to one and still results in a valid model
+        let model = Svm::params().nu_weight(2.0).fit(&dataset);
+        let pred = model.predict_inlier(ndarray::array![[8., 8.]]);
+        assert_eq!(pred, vec![false]);
+    }
 }
@@ -0,0 +1,149 @@
+//! Epsilon-Support Vector Regression
+//!
+//! The Epsilon-SVR fits a function which deviates at most `epsilon` from the targets, while
+//! being as flat as possible. Deviations larger than `epsilon` are penalized with the penalty `C`.
+use linfa::{traits::Fit, Dataset};
+
+use super::permutable_kernel::Kernel;
+use super::{Float, Svm, SvmParams};
+
+/// Epsilon-Support Vector Regression
+///
+/// Samples inside the tube of width `epsilon` around the regression function do not contribute
+/// to the loss and are no support vectors. The fitted model is a plain [Svm](struct.Svm.html).
+pub struct EpsilonSvr;
+
+impl EpsilonSvr {
+    pub fn params<F: Float>() -> EpsilonSvrParams<F> {
+        let c = F::one();
+        let epsilon = F::from(0.1).unwrap();
+
+        EpsilonSvrParams {
+            params: Svm::params().c_eps(c, epsilon),
+            c,
+            epsilon,
+        }
+    }
+}
+
+/// Hyperparameters of the Epsilon-SVR
+pub struct EpsilonSvrParams<F: Float> {
+    params: SvmParams<F, F>,
+    c: F,
+    epsilon: F,
+}
+
+impl<F: Float> EpsilonSvrParams<F> {
+    /// Set the penalty of samples outside of the epsilon tube
+    ///
+    /// # Panics
+    ///
+    /// If `c` is not positive
+    pub fn c(mut self, c: F) -> Self {
+        assert!(c > F::zero(), "C has to be positive, got {}", c);
+        self.c = c;
+        self.params = self.params.c_eps(self.c, self.epsilon);
+
+        self
+    }
+
+    /// Set the width of the tube in which deviations are not penalized
+    ///
+    /// # Panics
+    ///
+    /// If `epsilon` is negative
+    pub fn epsilon(mut self, epsilon: F) -> Self {
+        assert!(
+            epsilon >= F::zero(),
+            "Epsilon has to be non-negative, got {}",
+            epsilon
+        );
+        self.epsilon = epsilon;
+        self.params = self.params.c_eps(self.c, self.epsilon);
+
+        self
+    }
+
+    /// Set stopping condition
+    pub fn eps(mut self, eps: F) -> Self {
+        self.params = self.params.eps(eps);
+
+        self
+    }
+
+    /// Set the maximal number of iterations of the solver
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.params = self.params.max_iter(max_iter);
+
+        self
+    }
+
+    /// Enable or disable the shrinking heuristic of the solver
+    pub fn shrinking(mut self, shrinking: bool) -> Self {
+        self.params = self.params.shrinking(shrinking);
+
+        self
+    }
+}
+
+impl<'a, F: Float> Fit<'a, Kernel<'a, F>, Vec<F>> for EpsilonSvrParams<F> {
+    type Object = Svm<F, F>;
+
+    fn fit(&self, dataset: &'a Dataset<Kernel<'a, F>, Vec<F>>) -> Self::Object {
+        self.params.fit(dataset)
+    }
+}
+
+impl<'a, F: Float> Fit<'a, Kernel<'a, F>, &Vec<F>> for EpsilonSvrParams<F> {
+    type Object = Svm<F, F>;
+
+    fn fit(&self, dataset: &'a Dataset<Kernel<'a, F>, &Vec<F>>) -> Self::Object {
+        self.params.fit(dataset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EpsilonSvr;
+    use linfa::dataset::Dataset;
+    use linfa::traits::{Fit, Predict, Transformer};
+    use linfa_kernel::{Kernel, KernelMethod};
+    use ndarray::{array, Array};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Uniform;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn test_epsilon_svr() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let epsilon = 0.5;
+
+        // noisy linear function `y = 2x + 1` with noise smaller than the tube
+        let records = Array::linspace(0f64, 10., 50).into_shape((50, 1)).unwrap();
+        let noise = Array::random_using(50, Uniform::new(-0.2, 0.2), &mut rng);
+        let target = (records.column(0).mapv(|x| 2. * x + 1.) + noise).to_vec();
+
+        let kernel = Kernel::params()
+            .method(KernelMethod::Linear)
+            .transform(&records);
+        let dataset = Dataset::new(kernel, target.clone());
+
+        let model = EpsilonSvr::params().c(10.).epsilon(epsilon).fit(&dataset);
+
+        // all predictions should lie within the epsilon tube around the targets
+        let predicted = model.predict(records.view());
+        for (p, t) in predicted.iter().zip(target.iter()) {
+            assert!((p - t).abs() <= epsilon + 1e-3);
+        }
+
+        let slope = model.predict(array![[1.]]).remove(0) - model.predict(array![[0.]]).remove(0);
+        assert!((slope - 2.).abs() < 0.1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_epsilon_svr_range() {
+        EpsilonSvr::params::<f64>().epsilon(-1.0);
+    }
+}
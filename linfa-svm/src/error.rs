@@ -0,0 +1,46 @@
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, SvmError>;
+
+#[derive(Debug)]
+pub enum SvmError {
+    /// Error when the precomputed kernel matrix is not square
+    NonSquareKernel(usize, usize),
+    /// Error when the precomputed kernel matrix is not symmetric
+    AsymmetricKernel,
+    /// Error when the number of targets differs from the number of samples
+    TargetsMismatch(usize, usize),
+    /// Error when sparse records are not stored in CSR format
+    NotCsr,
+    /// Error when the labels of a binary model contain more than two classes
+    TooManyLabels(usize),
+    /// Error when the positive label does not occur in the labels
+    UnknownLabel(usize),
+}
+
+impl fmt::Display for SvmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NonSquareKernel(rows, cols) => write!(
+                f,
+                "Kernel matrix is not square, got {} rows and {} columns",
+                rows, cols
+            ),
+            Self::AsymmetricKernel => write!(f, "Kernel matrix is not symmetric"),
+            Self::TargetsMismatch(samples, targets) => write!(
+                f,
+                "Expected {} targets for the kernel matrix, got {}",
+                samples, targets
+            ),
+            Self::NotCsr => write!(f, "Sparse records have to be stored in CSR format"),
+            Self::TooManyLabels(n) => write!(
+                f,
+                "Expected at most two labels for a binary model, got {}",
+                n
+            ),
+            Self::UnknownLabel(label) => write!(f, "Positive label {} does not occur", label),
+        }
+    }
+}
+
+impl std::error::Error for SvmError {}
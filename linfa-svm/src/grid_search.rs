@@ -0,0 +1,263 @@
+//! Grid search over the hyperparameters of a binary SVM
+//!
+//! Every combination of a penalty `C` and a kernel method is scored by the mean accuracy of a
+//! k-fold cross-validation. The kernel matrix of each method is computed once on the whole
+//! dataset and the kernels of the folds are assembled from its columns. Within a fold the values
+//! of `C` are visited in ascending order and each solver is warm started from the solution of the
+//! previous `C`.
+use linfa::{
+    dataset::{Dataset, Pr, Targets},
+    traits::{Fit, Transformer},
+};
+use linfa_kernel::{Kernel, KernelMethod};
+use ndarray::{ArrayBase, Axis, Data, Ix2};
+use std::cmp::Ordering;
+
+use super::platt_scaling::{folds, held_out_decision, sub_kernel};
+use super::{Float, Svm, SvmParams};
+
+/// Cross-validated search over `C` values and kernel methods
+pub struct GridSearch;
+
+impl GridSearch {
+    pub fn params<F: Float>() -> GridSearchParams<F> {
+        GridSearchParams {
+            c_values: vec![F::one()],
+            methods: vec![KernelMethod::Linear],
+            nfolds: 5,
+            params: Svm::params(),
+        }
+    }
+}
+
+/// Candidates and settings of the grid search
+pub struct GridSearchParams<F: Float> {
+    c_values: Vec<F>,
+    methods: Vec<KernelMethod<F>>,
+    nfolds: usize,
+    params: SvmParams<F, Pr>,
+}
+
+impl<F: Float> GridSearchParams<F> {
+    /// Set the candidate values of `C`, which is used for both classes
+    pub fn c_values(mut self, c_values: Vec<F>) -> Self {
+        self.c_values = c_values;
+        self
+    }
+
+    /// Set the candidate kernel methods
+    pub fn methods(mut self, methods: Vec<KernelMethod<F>>) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    /// Set the number of cross-validation folds
+    pub fn nfolds(mut self, nfolds: usize) -> Self {
+        self.nfolds = nfolds;
+        self
+    }
+
+    /// Set the parameters of the solver, the penalty is replaced by the candidates
+    ///
+    /// The [seed](struct.SvmParams.html#method.seed) of these parameters also shuffles the
+    /// cross-validation folds.
+    pub fn svm_params(mut self, params: SvmParams<F, Pr>) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Mean cross-validated accuracy of all `C` values for a single kernel method
+    fn score_method<D: Data<Elem = F>>(
+        &self,
+        records: &ArrayBase<D, Ix2>,
+        targets: &[bool],
+        method: &KernelMethod<F>,
+    ) -> Vec<F> {
+        let kernel = Kernel::params()
+            .method(method.clone())
+            .transform(records.view());
+        let columns = (0..targets.len())
+            .map(|i| kernel.column(i))
+            .collect::<Vec<_>>();
+
+        // visit the values of `C` in ascending order for warm starts
+        let mut order = (0..self.c_values.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            self.c_values[*a]
+                .partial_cmp(&self.c_values[*b])
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let mut correct = vec![0; self.c_values.len()];
+        for (valid, train) in folds(targets.len(), self.nfolds, self.params.seed) {
+            let train_targets = train.iter().map(|i| targets[*i]).collect::<Vec<_>>();
+
+            // a fold containing a single class cannot be trained and predicts this class
+            if train_targets.iter().all(|x| *x == train_targets[0]) {
+                let hits = valid
+                    .iter()
+                    .filter(|i| targets[**i] == train_targets[0])
+                    .count();
+                correct.iter_mut().for_each(|x| *x += hits);
+                continue;
+            }
+
+            let records = kernel.dataset.select(Axis(0), &train);
+            let fold_kernel = sub_kernel(&kernel, &columns, &train, records.view());
+
+            let sample_weight = self
+                .params
+                .sample_weight
+                .as_ref()
+                .map(|weights| train.iter().map(|i| weights[*i]).collect::<Vec<_>>());
+
+            let mut previous: Option<Svm<F, Pr>> = None;
+            for idx in &order {
+                let c = self.c_values[*idx];
+                let params = self.params.clone().pos_neg_weights(c, c);
+                let warm_start = previous.as_ref().map(|model| model.alpha.as_slice());
+                let model = params.fit_kernel(
+                    &fold_kernel,
+                    &train_targets,
+                    warm_start,
+                    sample_weight.as_deref(),
+                );
+
+                correct[*idx] += valid
+                    .iter()
+                    .filter(|i| {
+                        let val = held_out_decision(&model, &columns[**i], &train);
+                        (val > F::zero()) == targets[**i]
+                    })
+                    .count();
+
+                previous = Some(model);
+            }
+        }
+
+        let nsamples = F::from(targets.len()).unwrap();
+        correct
+            .into_iter()
+            .map(|x| F::from(x).unwrap() / nsamples)
+            .collect()
+    }
+}
+
+/// Mean cross-validated accuracy of a single parameter combination
+#[derive(Debug, Clone)]
+pub struct GridSearchScore<F> {
+    pub c: F,
+    pub method: KernelMethod<F>,
+    pub score: F,
+}
+
+/// Scores of all parameter combinations of a grid search
+#[derive(Debug, Clone)]
+pub struct GridSearchResult<F> {
+    scores: Vec<GridSearchScore<F>>,
+    best: usize,
+}
+
+impl<F: Float> GridSearchResult<F> {
+    /// Returns the combination with the highest accuracy
+    ///
+    /// Ties are resolved in favour of the earlier combination in [scores](#method.scores).
+    pub fn best(&self) -> &GridSearchScore<F> {
+        &self.scores[self.best]
+    }
+
+    /// Returns the scores of all combinations, ordered by kernel method and then by `C`, in
+    /// the order of the candidates
+    pub fn scores(&self) -> &[GridSearchScore<F>] {
+        &self.scores
+    }
+}
+
+impl<'a, F: Float, D: Data<Elem = F>, T: Targets<Elem = bool>> Fit<'a, ArrayBase<D, Ix2>, T>
+    for GridSearchParams<F>
+{
+    type Object = GridSearchResult<F>;
+
+    /// Score all combinations of `C` values and kernel methods
+    ///
+    /// __Panics__ if there are no candidates, or if the number of folds is smaller than two
+    /// or larger than the number of observations
+    fn fit(&self, dataset: &'a Dataset<ArrayBase<D, Ix2>, T>) -> Self::Object {
+        let targets = dataset.targets.as_slice();
+        assert!(
+            !self.c_values.is_empty() && !self.methods.is_empty(),
+            "Grid search requires at least one C value and kernel method"
+        );
+        assert!(
+            self.nfolds >= 2 && self.nfolds <= targets.len(),
+            "Number of folds has to be between 2 and {}",
+            targets.len()
+        );
+
+        let mut scores = Vec::with_capacity(self.c_values.len() * self.methods.len());
+        for method in &self.methods {
+            let method_scores = self.score_method(&dataset.records, targets, method);
+
+            for (c, score) in self.c_values.iter().zip(method_scores) {
+                scores.push(GridSearchScore {
+                    c: *c,
+                    method: method.clone(),
+                    score,
+                });
+            }
+        }
+
+        let best = scores.iter().enumerate().fold(0, |best, (idx, x)| {
+            if x.score > scores[best].score {
+                idx
+            } else {
+                best
+            }
+        });
+
+        GridSearchResult { scores, best }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GridSearch;
+    use linfa::dataset::Dataset;
+    use linfa::traits::Fit;
+    use linfa_kernel::KernelMethod;
+    use ndarray::Array;
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Uniform;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn test_grid_search() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // the positive class is a disc inside a ring, which is not linearly separable
+        let records = Array::random_using((60, 2), Uniform::new(-1., 1.), &mut rng);
+        let targets = records
+            .outer_iter()
+            .map(|x| x.dot(&x) < 0.4)
+            .collect::<Vec<_>>();
+        let dataset = Dataset::new(records, targets);
+
+        let result = GridSearch::params()
+            .c_values(vec![0.01, 1.0, 100.0])
+            .methods(vec![KernelMethod::Linear, KernelMethod::Gaussian(0.1)])
+            .nfolds(4)
+            .fit(&dataset);
+
+        assert_eq!(result.scores().len(), 6);
+        let best = result.best();
+        assert!(result.scores().iter().all(|x| x.score <= best.score));
+
+        // a linear kernel cannot separate the disc from the ring
+        match best.method {
+            KernelMethod::Gaussian(_) => {}
+            _ => panic!("Expected a Gaussian kernel, got {:?}", best.method),
+        }
+        assert!(best.c > 0.01);
+        assert!(best.score > 0.85);
+    }
+}
@@ -0,0 +1,161 @@
+//! Isotonic calibration
+//!
+//! Maps the decision values of a binary SVM to probabilities with a non-decreasing step
+//! function. The function is fitted to decision values of held-out samples with the
+//! pool-adjacent-violators algorithm: the samples are sorted by their decision value and
+//! neighbouring groups, whose fraction of positive samples decreases, are merged until the
+//! fractions are monotonic. Unlike Platt scaling this makes no assumption about the shape of
+//! the mapping.
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use super::Float;
+
+/// Breakpoints of the fitted step function
+///
+/// Each step is stored with its first and last decision value, between the breakpoints the
+/// probability is interpolated linearly and outside of them it is constant.
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(
+        crate = "serde_crate",
+        bound(serialize = "F: Serialize", deserialize = "F: Deserialize<'de>")
+    )
+)]
+#[derive(Debug, Clone)]
+pub struct IsotonicCalibration<F> {
+    decision_values: Vec<F>,
+    probabilities: Vec<F>,
+}
+
+/// Group of consecutive samples sharing a probability
+struct Block<F> {
+    positives: F,
+    count: F,
+    first: F,
+    last: F,
+}
+
+impl<F: Float> IsotonicCalibration<F> {
+    /// Fit the step function to decision values and the targets of the same samples
+    ///
+    /// __Panics__ if there are no samples
+    pub fn fit(decision_values: &[F], targets: &[bool]) -> IsotonicCalibration<F> {
+        assert!(
+            !decision_values.is_empty(),
+            "Isotonic calibration requires at least one sample"
+        );
+
+        let mut order = (0..decision_values.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            decision_values[*a]
+                .partial_cmp(&decision_values[*b])
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let mut blocks: Vec<Block<F>> = Vec::new();
+        for idx in order {
+            let val = decision_values[idx];
+            let positive = if targets[idx] { F::one() } else { F::zero() };
+
+            // samples with the same decision value have to share a probability
+            match blocks.last_mut() {
+                Some(block) if block.last == val => {
+                    block.positives += positive;
+                    block.count += F::one();
+                }
+                _ => blocks.push(Block {
+                    positives: positive,
+                    count: F::one(),
+                    first: val,
+                    last: val,
+                }),
+            }
+
+            // pool the last block with its predecessors, as long as the fraction decreases
+            while blocks.len() > 1 {
+                let block = &blocks[blocks.len() - 1];
+                let prev = &blocks[blocks.len() - 2];
+                if prev.positives * block.count <= block.positives * prev.count {
+                    break;
+                }
+
+                let block = blocks.pop().unwrap();
+                let prev = blocks.last_mut().unwrap();
+                prev.positives += block.positives;
+                prev.count += block.count;
+                prev.last = block.last;
+            }
+        }
+
+        let (mut decision_values, mut probabilities) = (Vec::new(), Vec::new());
+        for block in blocks {
+            let probability = block.positives / block.count;
+
+            decision_values.push(block.first);
+            probabilities.push(probability);
+            if block.last != block.first {
+                decision_values.push(block.last);
+                probabilities.push(probability);
+            }
+        }
+
+        IsotonicCalibration {
+            decision_values,
+            probabilities,
+        }
+    }
+
+    /// Evaluate the step function at a decision value
+    pub fn predict(&self, decision_value: F) -> F {
+        // index of the first breakpoint larger than the decision value
+        let idx = self
+            .decision_values
+            .iter()
+            .position(|x| *x > decision_value)
+            .unwrap_or(self.decision_values.len());
+
+        if idx == 0 {
+            self.probabilities[0]
+        } else if idx == self.decision_values.len() {
+            self.probabilities[idx - 1]
+        } else {
+            let (x0, x1) = (self.decision_values[idx - 1], self.decision_values[idx]);
+            let (p0, p1) = (self.probabilities[idx - 1], self.probabilities[idx]);
+
+            p0 + (p1 - p0) * (decision_value - x0) / (x1 - x0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IsotonicCalibration;
+
+    #[test]
+    fn test_isotonic_calibration() {
+        let decision_values = [-3.0, -2.0, -1.5, -0.5, 0.2, 0.5, 1.0, 1.0, 2.5, 3.0];
+        let targets = [
+            false, true, false, true, false, true, true, false, true, true,
+        ];
+
+        let isotonic = IsotonicCalibration::fit(&decision_values, &targets);
+
+        // the violators are pooled into non-decreasing steps
+        assert_eq!(isotonic.predict(-3.0), 0.0);
+        assert_eq!(isotonic.predict(-2.0), 0.5);
+        assert_eq!(isotonic.predict(-1.5), 0.5);
+        assert_eq!(isotonic.predict(3.0), 1.0);
+
+        // constant outside of the breakpoints and monotonic in between
+        assert_eq!(isotonic.predict(-10.0), 0.0);
+        assert_eq!(isotonic.predict(10.0), 1.0);
+        let probs = (0..100)
+            .map(|x| isotonic.predict(-4.0 + x as f64 * 0.08))
+            .collect::<Vec<_>>();
+        assert!(probs.iter().all(|p| *p >= 0.0 && *p <= 1.0));
+        assert!(probs.windows(2).all(|x| x[0] <= x[1]));
+    }
+}
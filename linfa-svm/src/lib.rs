@@ -16,10 +16,33 @@
 //! [fit_c](SVClassify/fn.fit_c) the
 //! C value controls the penalty given to missclassification and should be in the interval (0, inf). In
 //! [fit_nu](SVClassify/fn.fit_nu.html) the Nu value controls the number of support vectors and should be in the interval (0, 1].
+//! The estimator [NuSvc](struct.NuSvc.html) exposes the Nu formulation with its own parameters.
 //!
 //! For supervised classification with just one class of data a special classifier is available in
 //! [fit_one_class](SVClassify/fn.fit_one_class.html). It also accepts a Nu value.
 //!
+//! For regression the estimator [EpsilonSvr](struct.EpsilonSvr.html) exposes the epsilon-insensitive
+//! formulation with the penalty `C` and the tube width `epsilon` as its own parameters.
+//!
+//! For linear problems with many samples or features [LinearSvm](struct.LinearSvm.html) solves
+//! the problem with coordinate descent on the primal weight vector instead of SMO. Its parameters
+//! also implement `IncrementalFit`, which approximates the solution on streamed mini-batches.
+//! Non-linear problems of this size can be solved with it after mapping the records with
+//! [RandomFourierFeatures](struct.RandomFourierFeatures.html), which approximates the Gaussian
+//! kernel with an explicit randomized feature map.
+//!
+//! The penalty `C` and the kernel method can be chosen with [GridSearch](struct.GridSearch.html),
+//! which scores every combination of candidates with k-fold cross-validation. A whole path of
+//! models over increasing `C` is fitted by
+//! [regularization_path](struct.SvmParams.html#method.regularization_path), which warm starts
+//! each solve from the previous solution.
+//!
+//! Problems with more than two classes are solved with [MulticlassSvm](struct.MulticlassSvm.html),
+//! which trains a binary model for every pair of classes and predicts by majority voting, or with
+//! [OvrSvm](struct.OvrSvm.html), which trains a binary model for every class against all others
+//! and predicts the class with the largest decision value. With the `rayon` feature the binary
+//! models of `OvrSvm` are trained in parallel.
+//!
 //! For support vector regression two flavors are available. With
 //! [fit_epsilon](SVRegress/fn.fit_epsilon.html) a regression task is learned while minimizing deviation
 //! larger than epsilon. In [fit_nu](SVRegress/fn.fit_nu.html) the parameter epsilon is replaced with Nu
@@ -48,6 +71,9 @@
 //!
 //! After a couple of iterations the solution may be optimal.
 //!
+//! With the `rayon` feature the entries of the kernel columns, requested in each step, are
+//! evaluated in parallel. This does not change the numerical results.
+//!
 //! # Example
 //! The wine quality data consists of 11 features, like "acid", "sugar", "sulfur dioxide", and
 //! groups the quality into worst 3 to best 8. These are unified to good 8-7 and bad 3-6 to get a
@@ -67,34 +93,257 @@
 //! accuracy 0.98818624, MCC 0.9523008
 //! ```
 use linfa::{dataset::Pr, Float};
-use ndarray::Array1;
+use linfa_kernel::KernelMethod;
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2, Axis};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
 use std::marker::PhantomData;
+use std::ops::Mul;
+use std::rc::Rc;
+use std::time::Duration;
 
 #[cfg(feature = "serde")]
 use serde_crate::{Deserialize, Serialize};
 
 mod classification;
+mod epsilon_svr;
+mod error;
+mod grid_search;
+mod isotonic;
+mod linear;
+mod multiclass;
+mod nu_svc;
+mod ovr;
 mod permutable_kernel;
+mod platt_scaling;
+mod random_features;
 mod regression;
 pub mod solver_smo;
 
+pub use epsilon_svr::{EpsilonSvr, EpsilonSvrParams};
+pub use error::{Result, SvmError};
+pub use grid_search::{GridSearch, GridSearchParams, GridSearchResult, GridSearchScore};
+use isotonic::IsotonicCalibration;
+pub use linear::{LinearSvm, LinearSvmParams};
+pub use multiclass::{MulticlassSvm, MulticlassSvmParams};
+pub use nu_svc::{NuSvc, NuSvcParams};
+pub use ovr::{OvrSvm, OvrSvmParams};
 use permutable_kernel::Kernel;
-pub use solver_smo::SolverParams;
+pub use random_features::{RandomFourierFeatures, RandomFourierFeaturesParams};
+pub use solver_smo::{SolverCallback, SolverParams};
+
+/// Weights of the positive and negative class in C-SVC problems
+///
+/// The penalizing parameter `C` of each class is multiplied with its weight, which is the
+/// standard remedy for imbalanced training sets.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClassWeight<F: Float> {
+    /// Weight each class with `n / (2 * n_class)`, inversely proportional to its frequency
+    Auto,
+    /// Explicit weights, a missing class is weighted with one
+    Custom(HashMap<bool, F>),
+}
+
+impl<F: Float> ClassWeight<F> {
+    /// Returns the weights of the positive and negative class
+    fn weights(&self, targets: &[bool]) -> (F, F) {
+        match self {
+            ClassWeight::Auto => {
+                let npos = targets.iter().filter(|x| **x).count();
+                let nneg = targets.len() - npos;
+                let weight = |nclass: usize| {
+                    if nclass == 0 {
+                        F::one()
+                    } else {
+                        F::from(targets.len()).unwrap() / F::from(2 * nclass).unwrap()
+                    }
+                };
+
+                (weight(npos), weight(nneg))
+            }
+            ClassWeight::Custom(weights) => (
+                weights.get(&true).cloned().unwrap_or_else(F::one),
+                weights.get(&false).cloned().unwrap_or_else(F::one),
+            ),
+        }
+    }
+}
 
+/// Calibration of probability estimates
+///
+/// Both mappings are fitted to decision values of a cross-validation on the training set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Calibration {
+    /// Sigmoid `1 / (1 + exp(A*f + B))` of the decision value `f`, as proposed by Platt
+    Platt,
+    /// Monotonic step function fitted with the pool-adjacent-violators algorithm and linearly
+    /// interpolated between its steps. It makes no assumption about the shape of the mapping,
+    /// but needs more training samples than the sigmoid.
+    Isotonic,
+}
+
+/// Fitted mapping from decision values to probabilities
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(
+        crate = "serde_crate",
+        bound(serialize = "A: Serialize", deserialize = "A: Deserialize<'de>")
+    )
+)]
+#[derive(Debug, Clone)]
+enum Calibrator<A> {
+    Platt(A, A),
+    Isotonic(IsotonicCalibration<A>),
+}
+
+#[derive(Clone)]
 pub struct SvmParams<F: Float, T> {
     c: Option<(F, F)>,
     nu: Option<(F, F)>,
+    probability: bool,
+    class_weight: Option<ClassWeight<F>>,
+    calibration: Calibration,
+    warm_start: Option<Vec<F>>,
+    sample_weight: Option<Vec<F>>,
+    kernel: KernelMethod<F>,
+    validate_kernel: bool,
+    center_kernel: bool,
+    positive_label: Option<usize>,
+    seed: Option<u64>,
     solver_params: SolverParams<F>,
     phantom: PhantomData<T>,
 }
 
 impl<F: Float, T> SvmParams<F, T> {
+    /// Create parameters with the defaults of [Svm::params](struct.Svm.html#method.params)
+    ///
+    /// Besides a kernel dataset the parameters can fit plain records, the kernel matrix is then
+    /// built with the method set by [kernel](#method.kernel) and errors are returned instead of
+    /// panics.
+    ///
+    /// ```
+    /// use linfa::{traits::Fit, Dataset};
+    /// use linfa_kernel::KernelMethod;
+    /// use linfa_svm::SvmParams;
+    /// use ndarray::array;
+    ///
+    /// # fn main() -> linfa_svm::Result<()> {
+    /// let records = array![[-2., -1.], [-1., -2.], [1., 2.], [2., 1.]];
+    /// let data = Dataset::new(records.clone(), vec![false, false, true, true]);
+    ///
+    /// let model = SvmParams::params()
+    ///     .c(1.0)
+    ///     .kernel(KernelMethod::Gaussian(0.5))
+    ///     .fit(&data)?;
+    /// assert!(model.decision_function(records.view())[3] > 0.0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn params() -> Self {
+        Svm::params()
+    }
+
+    /// Set the kernel method used when fitting plain records, which defaults to a linear kernel
+    ///
+    /// This has no effect when fitting a kernel dataset, which already contains the method.
+    pub fn kernel(mut self, method: KernelMethod<F>) -> Self {
+        self.kernel = method;
+        self
+    }
+
+    /// Set the stopping threshold of the solver
     pub fn eps(mut self, new_eps: F) -> Self {
         self.solver_params.eps = new_eps;
         self
     }
 
+    /// Set the maximal number of solver iterations
+    ///
+    /// If the solver does not reach the threshold `eps` within these iterations, it exits early
+    /// and [converged](struct.Svm.html#method.converged) returns false.
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.solver_params.max_iter = Some(max_iter);
+        self
+    }
+
+    /// Set a wall-clock budget for the solver
+    ///
+    /// The elapsed time is only checked every few hundred iterations, so the budget can be
+    /// exceeded slightly. If the solver stops early, [converged](struct.Svm.html#method.converged)
+    /// returns false.
+    pub fn time_limit(mut self, time_limit: Duration) -> Self {
+        self.solver_params.time_limit = Some(time_limit);
+        self
+    }
+
+    /// Set a progress callback for the solver
+    ///
+    /// The callback is invoked every few hundred iterations with the current iteration and an
+    /// estimate of the objective. Returning `false` stops the solver and the model reports
+    /// `ExitReason::Aborted`.
+    pub fn callback(mut self, callback: impl FnMut(usize, F) -> bool + 'static) -> Self {
+        self.solver_params.callback = Some(Rc::new(RefCell::new(callback)));
+        self
+    }
+
+    /// Stop the solver early once the objective reaches a plateau
+    ///
+    /// The objective is evaluated whenever the shrinking counter resets, i.e. every
+    /// `min(n_samples, 1000)` iterations. If its relative change stays below `tol` for `checks`
+    /// consecutive evaluations, while the KKT violation is within a thousand times `eps`, the
+    /// solver stops and reports `ExitReason::ReachedThreshold`. This trades a slightly less
+    /// accurate solution for fewer iterations on problems, which converge slowly in the last
+    /// orders of magnitude of the KKT violation.
+    pub fn plateau_tol(mut self, tol: F, checks: usize) -> Self {
+        self.solver_params.plateau_tol = Some(tol);
+        self.solver_params.plateau_checks = checks;
+        self
+    }
+
+    /// Record the convergence of the solver, which is disabled by default
+    ///
+    /// Whenever the shrinking counter resets, i.e. every `min(n_samples, 1000)` iterations, the
+    /// gap of the maximal violating pair is appended to a history, which is available from
+    /// [convergence_history](struct.Svm.html#method.convergence_history) of the fitted model.
+    pub fn record_history(mut self, record_history: bool) -> Self {
+        self.solver_params.record_history = record_history;
+        self
+    }
+
+    /// Precompute the weight vector of linear models, which is enabled by default
+    ///
+    /// The weight vector `w = sum_i alpha_i y_i x_i` has one entry per feature and turns a
+    /// prediction into a single dot product. Without it, predictions of a linear kernel evaluate
+    /// the kernel function with every support vector and
+    /// [export_linear](struct.Svm.html#method.export_linear) returns `None`. Models fitted on
+    /// sparse records store no support vectors and always precompute the weight vector.
+    pub fn precompute_linear(mut self, precompute_linear: bool) -> Self {
+        self.solver_params.precompute_linear = precompute_linear;
+        self
+    }
+
+    /// Set the size of the kernel column cache in megabytes
+    ///
+    /// Recently used columns of the kernel matrix are kept in memory and the least recently
+    /// used column is evicted, once the budget is exhausted. A size of zero disables the cache.
+    pub fn cache_size(mut self, cache_size: usize) -> Self {
+        self.solver_params.cache_size = cache_size;
+        self
+    }
+
+    /// Enable or disable the shrinking heuristic of the solver
+    ///
+    /// Shrinking temporarily removes variables, which are likely to stay at their bounds, from
+    /// the optimization and reorders the remaining ones. This speeds up training on large
+    /// datasets, but the sequence of working pairs then depends on the shrinking schedule. The
+    /// solution agrees with the one of the unshrunk problem within the tolerance of the solver.
+    ///
+    /// With shrinking disabled, which is the default, the variables are never reordered and the
+    /// solver is deterministic: fitting the same kernel with the same parameters yields
+    /// bitwise identical coefficients, in the order of the training samples. Use this mode for
+    /// regression tests against reference implementations like LIBSVM.
     pub fn shrinking(mut self, shrinking: bool) -> Self {
         self.solver_params.shrinking = shrinking;
 
@@ -103,6 +352,11 @@ impl<F: Float, T> SvmParams<F, T> {
 }
 
 impl<F: Float> SvmParams<F, Pr> {
+    /// Set the same C value for positive and negative samples
+    pub fn c(self, c: F) -> Self {
+        self.pos_neg_weights(c, c)
+    }
+
     pub fn pos_neg_weights(mut self, c_pos: F, c_neg: F) -> Self {
         self.c = Some((c_pos, c_neg));
         self.nu = None;
@@ -116,9 +370,135 @@ impl<F: Float> SvmParams<F, Pr> {
 
         self
     }
+
+    /// Enable probability estimates with Platt scaling
+    ///
+    /// The sigmoid is fitted to decision values of a five-fold cross-validation on the training
+    /// set, which increases the training time accordingly. The sigmoid can be replaced with an
+    /// isotonic mapping by [calibration](#method.calibration). This has no effect on one-class
+    /// problems.
+    pub fn probability(mut self, probability: bool) -> Self {
+        self.probability = probability;
+
+        self
+    }
+
+    /// Set the mapping from decision values to probabilities, which defaults to Platt scaling
+    ///
+    /// This only has an effect together with [probability](#method.probability).
+    pub fn calibration(mut self, calibration: Calibration) -> Self {
+        self.calibration = calibration;
+
+        self
+    }
+
+    /// Enable or disable the validation of precomputed kernel matrices, which is enabled by default
+    ///
+    /// A precomputed matrix is rejected if it is not symmetric and a warning is logged for
+    /// negative diagonal entries, because the dual problem is ill-posed for kernels that are not
+    /// positive semi-definite. For a few thousand samples and more only a subset of the rows is
+    /// checked. Disable the validation if the matrix is known to be valid.
+    pub fn validate_kernel(mut self, validate_kernel: bool) -> Self {
+        self.validate_kernel = validate_kernel;
+
+        self
+    }
+
+    /// Center precomputed kernel matrices in feature space, which is disabled by default
+    ///
+    /// The Gram matrix `K` is replaced by `K - 1K - K1 + 1K1`, where `1` is the matrix with all
+    /// entries `1/n`. This corresponds to subtracting the mean of the training samples in
+    /// feature space and can improve the conditioning of the dual problem. The column means of
+    /// the training kernel are stored in the model, such that
+    /// [predict_precomputed](struct.Svm.html#method.predict_precomputed) centers new kernel
+    /// values in the same way.
+    pub fn center_kernel(mut self, center_kernel: bool) -> Self {
+        self.center_kernel = center_kernel;
+
+        self
+    }
+
+    /// Set the integer label of the positive class in
+    /// [fit_labels](struct.SvmParams.html#method.fit_labels)
+    ///
+    /// Samples of this class have positive decision values. Defaults to the larger of the two
+    /// labels in the training data.
+    pub fn positive_label(mut self, label: usize) -> Self {
+        self.positive_label = Some(label);
+
+        self
+    }
+
+    /// Set the seed of the randomized steps of fitting
+    ///
+    /// The seed shuffles the samples before they are split into the cross-validation folds of
+    /// [probability](#method.probability) estimates and of a
+    /// [GridSearch](struct.GridSearch.html), and draws the projection of
+    /// [random_fourier_features](#method.random_fourier_features). Without a seed the random
+    /// number generator is seeded from the entropy of the operating system. The solver itself is
+    /// deterministic, identical seeds therefore yield identical models.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+
+        self
+    }
+
+    /// Weight the penalizing parameter `C` of the positive and negative class
+    ///
+    /// The bounds of the dual coefficients become `C_pos * w_pos` and `C_neg * w_neg`. This
+    /// has no effect on Nu-SVC problems.
+    pub fn class_weight(mut self, class_weight: ClassWeight<F>) -> Self {
+        self.class_weight = Some(class_weight);
+
+        self
+    }
+
+    /// Returns the C of the positive and negative class, scaled by the class weights
+    pub(crate) fn weighted_c(&self, targets: &[bool]) -> Option<(F, F)> {
+        self.c.map(|(c_pos, c_neg)| match &self.class_weight {
+            Some(class_weight) => {
+                let (w_pos, w_neg) = class_weight.weights(targets);
+                (c_pos * w_pos, c_neg * w_neg)
+            }
+            None => (c_pos, c_neg),
+        })
+    }
+
+    /// Start the C-SVC solver from the dual coefficients of a previous model
+    ///
+    /// This speeds up a search over `C`, because the solution of a neighbouring `C` is usually
+    /// close to the new one. The coefficients `alpha` of a fitted [Svm](struct.Svm.html) can be
+    /// passed directly, they are clamped to the new bounds. The number of coefficients has to
+    /// match the number of training samples. This has no effect on Nu-SVC problems.
+    pub fn warm_start(mut self, alpha: Vec<F>) -> Self {
+        self.warm_start = Some(alpha);
+
+        self
+    }
+
+    /// Weight the penalizing parameter `C` of each training sample
+    ///
+    /// The bound of the dual coefficient of sample `i` becomes `C * w_i`, in addition to the
+    /// class weights. A sample with zero weight cannot become a support vector and has no
+    /// influence on the model. The number of weights has to match the number of training
+    /// samples. This has no effect on Nu-SVC problems and the cross-validation of Platt scaling.
+    ///
+    /// # Panics
+    ///
+    /// If a weight is negative or not finite.
+    pub fn sample_weight(mut self, weights: Vec<F>) -> Self {
+        assert!(
+            weights.iter().all(|w| w.is_finite() && *w >= F::zero()),
+            "Sample weights have to be non-negative"
+        );
+        self.sample_weight = Some(weights);
+
+        self
+    }
 }
 
 impl<F: Float> SvmParams<F, F> {
+    /// Select the Epsilon-SVR with penalty `c` and tube width `eps`, see also [EpsilonSvr](struct.EpsilonSvr.html)
     pub fn c_eps(mut self, c: F, eps: F) -> Self {
         self.c = Some((c, eps));
         self.nu = None;
@@ -146,51 +526,215 @@ pub mod SVRegress {
     pub use crate::regression::{fit_epsilon, fit_nu};
 }
 
-/// SMO can either exit because a threshold is reached or the iterations are maxed out
+/// SMO can either exit because a threshold is reached, the iterations are maxed out, the time
+/// budget is exhausted or the progress callback requested a stop
 #[cfg_attr(
     feature = "serde",
     derive(Serialize, Deserialize),
     serde(crate = "serde_crate")
 )]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ExitReason {
+    /// The KKT violation fell below `eps`, or the objective reached a plateau, and the model is
+    /// optimal within the requested tolerance
     ReachedThreshold,
+    /// The maximal number of iterations was performed, the model may be far from optimal and
+    /// should be treated with care
     ReachedIterations,
+    /// The time budget was exhausted, the model is the current, possibly suboptimal, solution
+    ReachedTimeLimit,
+    /// The progress callback requested a stop, the model is the solution at this point
+    Aborted,
+}
+
+impl fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExitReason::ReachedThreshold => write!(f, "reached threshold"),
+            ExitReason::ReachedIterations => write!(f, "reached maximal iterations"),
+            ExitReason::ReachedTimeLimit => write!(f, "reached time limit"),
+            ExitReason::Aborted => write!(f, "aborted"),
+        }
+    }
+}
+
+/// Diagnostic summary of a fitted model
+///
+/// Collects the solver statistics in a single place, the `Display` implementation renders them
+/// as a one-line report suitable for logging.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvmSummary<A> {
+    /// Number of iterations performed by the solver
+    pub iterations: usize,
+    /// Value of the dual objective at exit
+    pub objective: A,
+    /// Offset of the decision function
+    pub rho: A,
+    /// Number of support vectors
+    pub n_support: usize,
+    /// Whether the solver reached the stopping threshold
+    pub converged: bool,
+}
+
+impl<A: fmt::Display> fmt::Display for SvmSummary<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} after {} iterations, obj = {}, rho = {}, {} support vectors",
+            if self.converged {
+                "Converged"
+            } else {
+                "Not converged"
+            },
+            self.iterations,
+            self.objective,
+            self.rho,
+            self.n_support
+        )
+    }
+}
+
+/// Decision values of a fixed set of observations
+///
+/// Evaluating the decision function sums kernel values over all support vectors. The cache
+/// evaluates it once, such that the observations can be classified at many different cutoffs
+/// in linear time, for example to trace a ROC curve or to tune the threshold on a validation
+/// set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionCache<A> {
+    values: Array1<A>,
+}
+
+impl<A: Float> DecisionCache<A> {
+    /// Decision values of the cached observations
+    pub fn values(&self) -> ArrayView1<'_, A> {
+        self.values.view()
+    }
+
+    /// Classify the cached observations, values above `cutoff` belong to the positive class
+    ///
+    /// A cutoff of zero reproduces the predictions of the model.
+    pub fn threshold(&self, cutoff: A) -> Vec<bool> {
+        self.values.iter().map(|x| *x > cutoff).collect()
+    }
+}
+
+/// Statistics of a training kernel matrix, which center kernel values in feature space
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(
+        crate = "serde_crate",
+        bound(serialize = "A: Serialize", deserialize = "A: Deserialize<'de>")
+    )
+)]
+#[derive(Debug, Clone)]
+struct KernelCentering<A: Float> {
+    column_means: Array1<A>,
+    mean: A,
+}
+
+impl<A: Float> KernelCentering<A> {
+    /// Compute the column means and the overall mean of a Gram matrix
+    fn new(gram: &Array2<A>) -> Self {
+        let column_means = gram.mean_axis(Axis(0)).unwrap();
+        let mean = column_means.mean().unwrap();
+
+        KernelCentering { column_means, mean }
+    }
+
+    /// Center kernel values `K(x, x_i)`, with one row per observation `x` and one column per
+    /// training sample `x_i`
+    fn center(&self, kernel_values: ArrayView2<A>) -> Array2<A> {
+        let row_means = kernel_values.mean_axis(Axis(1)).unwrap();
+
+        let centered = &kernel_values - &self.column_means;
+        centered - &row_means.insert_axis(Axis(1)) + self.mean
+    }
+}
+
+/// Training records and kernel function needed to evaluate the decision function
+///
+/// Only records with a non-zero dual coefficient are stored, together with their index in the
+/// training set.
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(
+        crate = "serde_crate",
+        bound(serialize = "A: Serialize", deserialize = "A: Deserialize<'de>")
+    )
+)]
+#[derive(Debug, Clone)]
+struct SupportVectors<A: Float> {
+    method: KernelMethod<A>,
+    records: Array2<A>,
+    indices: Vec<usize>,
+}
+
+impl<A: Float> SupportVectors<A> {
+    /// Evaluate `sum_i alpha_i K(x_i, sample)` over all support vectors
+    fn weighted_sum(&self, alpha: &[A], sample: ArrayView1<A>) -> A {
+        self.records
+            .outer_iter()
+            .zip(self.indices.iter())
+            .map(|(x, i)| self.method.distance(x, sample.view()) * alpha[*i])
+            .sum()
+    }
 }
 
 /// The result of the SMO solver
 #[cfg_attr(
     feature = "serde",
     derive(Serialize, Deserialize),
-    serde(crate = "serde_crate")
+    serde(
+        crate = "serde_crate",
+        bound(serialize = "A: Serialize", deserialize = "A: Deserialize<'de>")
+    )
 )]
-pub struct Svm<'a, A: Float, T> {
+pub struct Svm<A: Float, T> {
     pub alpha: Vec<A>,
     pub rho: A,
     r: Option<A>,
     exit_reason: ExitReason,
     iterations: usize,
+    kkt_violation: A,
     obj: A,
-    #[cfg_attr(
-        feature = "serde",
-        serde(bound(
-            serialize = "&'a Kernel<'a, A>: Serialize",
-            deserialize = "&'a Kernel<'a, A>: Deserialize<'de>"
-        ))
-    )]
-    kernel: &'a Kernel<'a, A>,
+    support_vectors: Option<SupportVectors<A>>,
     linear_decision: Option<Array1<A>>,
+    calibrator: Option<Calibrator<A>>,
+    kernel_centering: Option<KernelCentering<A>>,
+    convergence_history: Option<Vec<A>>,
+    kernel_evaluations: usize,
     phantom: PhantomData<T>,
 }
 
-impl<'a, A: Float, T> Svm<'a, A, T> {
+impl<A: Float, T> Svm<A, T> {
     pub fn params() -> SvmParams<A, T> {
         SvmParams {
             c: Some((A::one(), A::one())),
             nu: None,
+            probability: false,
+            class_weight: None,
+            calibration: Calibration::Platt,
+            warm_start: None,
+            sample_weight: None,
+            kernel: KernelMethod::Linear,
+            validate_kernel: true,
+            center_kernel: false,
+            positive_label: None,
+            seed: None,
             solver_params: SolverParams {
-                eps: A::from(1e-7).unwrap(),
+                eps: SolverParams::default_eps(),
                 shrinking: false,
+                max_iter: None,
+                cache_size: 100,
+                time_limit: None,
+                callback: None,
+                plateau_tol: None,
+                plateau_checks: 3,
+                record_history: false,
+                precompute_linear: true,
             },
             phantom: PhantomData,
         }
@@ -203,7 +747,244 @@ impl<'a, A: Float, T> Svm<'a, A, T> {
             .filter(|x| x.abs() > A::from(1e-5).unwrap())
             .count()
     }
-    pub fn with_phantom<S>(self) -> Svm<'a, A, S> {
+
+    /// Returns true if the solver reached the stopping threshold
+    ///
+    /// A model, which exited after the maximal number of iterations, may not be optimal.
+    pub fn converged(&self) -> bool {
+        self.exit_reason == ExitReason::ReachedThreshold
+    }
+
+    /// Returns the reason why the solver stopped
+    ///
+    /// Only `ExitReason::ReachedThreshold` guarantees an optimal model, the other variants
+    /// report a solution, which was cut short.
+    pub fn exit_reason(&self) -> ExitReason {
+        self.exit_reason
+    }
+
+    /// Returns the number of iterations performed by the solver
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Returns an estimate of the violation of the optimality conditions at exit
+    ///
+    /// A converged model has a violation below the stopping threshold `eps`. For a model, which
+    /// exited early, this shows how far the solver was from the optimum.
+    pub fn kkt_violation(&self) -> A {
+        self.kkt_violation
+    }
+
+    /// Returns a summary of the solver statistics
+    pub fn fit_summary(&self) -> SvmSummary<A> {
+        SvmSummary {
+            iterations: self.iterations,
+            objective: self.obj,
+            rho: self.rho,
+            n_support: self.nsupport(),
+            converged: self.converged(),
+        }
+    }
+
+    /// Returns the KKT violation recorded at each check of the solver
+    ///
+    /// Returns `None` unless the model was fitted with
+    /// [record_history](struct.SvmParams.html#method.record_history) by the SMO solver. While
+    /// variables are shrunk, only the active variables contribute to the recorded violation.
+    pub fn convergence_history(&self) -> Option<&[A]> {
+        self.convergence_history.as_deref()
+    }
+
+    /// Returns the number of kernel columns computed during training
+    ///
+    /// Columns served from the kernel cache are not counted.
+    pub fn kernel_evaluations(&self) -> usize {
+        self.kernel_evaluations
+    }
+
+    /// Returns the kernel function and parameters the model was trained with
+    ///
+    /// Returns `None` if the model was fitted on a precomputed kernel matrix, on sparse records
+    /// or without a kernel, because the kernel function is then not stored.
+    pub fn kernel_method(&self) -> Option<&KernelMethod<A>> {
+        self.support_vectors.as_ref().map(|x| &x.method)
+    }
+
+    /// Returns the indices of the training samples, which are support vectors
+    ///
+    /// This uses the same threshold as `nsupport`, the length of the returned vector is therefore
+    /// identical to the number of support vectors.
+    pub fn support_vector_indices(&self) -> Vec<usize> {
+        self.alpha
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.abs() > A::from(1e-5).unwrap())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Returns the dual coefficients of the training samples
+    ///
+    /// For classification these are the signed coefficients `y_i * alpha_i`, for regression the
+    /// difference `alpha_i - alpha_i^*` of the two dual variables. Both are already stored in
+    /// this form after fitting.
+    pub fn dual_coefficients(&self) -> Array1<A> {
+        Array1::from(self.alpha.clone())
+    }
+
+    /// Returns the offset `rho` of the decision function
+    ///
+    /// The decision value of an observation `x` is `sum_i coef_i K(x_i, x) - rho` with the
+    /// [dual coefficients](#method.dual_coefficients) `coef_i`.
+    pub fn rho(&self) -> A {
+        self.rho
+    }
+
+    /// Returns the margin `r` of the Nu formulation
+    ///
+    /// The dual coefficients and `rho` of a Nu-SVC are already divided by `r`. Returns `None` for
+    /// models, which were not fitted with the Nu constraint.
+    pub fn r(&self) -> Option<A> {
+        self.r
+    }
+
+    /// Export the hyperplane of a linear model as weight vector `w` and offset `rho`
+    ///
+    /// The decision value of an observation `x` is `w·x - rho`, positive values correspond to
+    /// the positive class. This is sufficient to evaluate the model outside of Rust. Returns
+    /// `None` if the model was not fitted with a linear kernel.
+    pub fn export_linear(&self) -> Option<(Array1<A>, A)> {
+        self.linear_decision
+            .as_ref()
+            .map(|weights| (weights.clone(), self.rho))
+    }
+
+    /// Export the support vectors, their dual coefficients and the offset `rho`
+    ///
+    /// The decision value of an observation `x` is `sum_i coef_i K(sv_i, x) - rho`, where the
+    /// support vectors `sv_i` are the rows of the returned matrix and `K` is the kernel function
+    /// used during training.
+    ///
+    /// # Panics
+    ///
+    /// If the model was fitted on a precomputed kernel matrix or without a kernel, because the
+    /// training records are then not available.
+    pub fn export_support_vectors(&self) -> (Array2<A>, Array1<A>, A) {
+        let support_vectors = self.support_vectors();
+        let coefficients = support_vectors
+            .indices
+            .iter()
+            .map(|i| self.alpha[*i])
+            .collect();
+
+        (support_vectors.records.clone(), coefficients, self.rho)
+    }
+
+    /// Drop the support vectors whose dual coefficient is smaller than `threshold` in magnitude
+    ///
+    /// The coefficients of the dropped samples are set to zero and their records are removed,
+    /// which shrinks serialized models with a non-linear kernel. Nothing else is recomputed, so
+    /// the decision function changes by at most `threshold` times the sum of the dropped kernel
+    /// values. The precomputed weight vector of a linear model is kept as well, pruning then only
+    /// affects [nsupport](#method.nsupport) and [sparsity](#method.sparsity).
+    pub fn prune(&mut self, threshold: A) {
+        for alpha in self.alpha.iter_mut() {
+            if alpha.abs() < threshold {
+                *alpha = A::zero();
+            }
+        }
+
+        let alpha = &self.alpha;
+        if let Some(support_vectors) = self.support_vectors.as_mut() {
+            let (rows, indices): (Vec<_>, Vec<_>) = support_vectors
+                .indices
+                .iter()
+                .enumerate()
+                .filter(|(_, i)| alpha[**i] != A::zero())
+                .map(|(row, i)| (row, *i))
+                .unzip();
+
+            support_vectors.records = support_vectors.records.select(Axis(0), &rows);
+            support_vectors.indices = indices;
+        }
+    }
+
+    /// Returns the fraction of training samples, whose dual coefficient is exactly zero
+    ///
+    /// Models without dual coefficients, for example those trained online by the linear solver,
+    /// have a sparsity of zero.
+    pub fn sparsity(&self) -> f64 {
+        if self.alpha.is_empty() {
+            return 0.0;
+        }
+
+        let zeros = self.alpha.iter().filter(|x| **x == A::zero()).count();
+
+        zeros as f64 / self.alpha.len() as f64
+    }
+
+    /// Evaluate the decision function for a set of observations
+    ///
+    /// This is the signed distance `sum_i alpha_i y_i K(x_i, x) - rho` to the separating
+    /// hyperplane, which `predict` thresholds at zero for classification. For linear kernels the
+    /// precomputed normal vector of the hyperplane is used instead.
+    pub fn decision_function(&self, x: ArrayView2<A>) -> Array1<A> {
+        x.outer_iter()
+            .map(|sample| self.decision_value(sample))
+            .collect()
+    }
+
+    /// Evaluate the decision function once and cache it for repeated thresholding
+    pub fn decision_cache(&self, x: ArrayView2<A>) -> DecisionCache<A> {
+        DecisionCache {
+            values: self.decision_function(x),
+        }
+    }
+
+    /// Evaluate the decision function for a single observation
+    pub(crate) fn decision_value(&self, sample: ArrayView1<A>) -> A {
+        match self.linear_decision {
+            Some(ref x) => x.mul(&sample).sum() - self.rho,
+            None => self.support_vectors().weighted_sum(&self.alpha, sample) - self.rho,
+        }
+    }
+
+    /// Returns the support vectors of the training samples
+    ///
+    /// # Panics
+    ///
+    /// If the model was fitted on a precomputed kernel matrix, which has no records to
+    /// evaluate the kernel function on.
+    fn support_vectors(&self) -> &SupportVectors<A> {
+        self.support_vectors
+            .as_ref()
+            .expect("Model was fitted on a precomputed kernel, use `predict_precomputed` instead")
+    }
+
+    /// Store the support vectors and kernel function of the training set
+    ///
+    /// This has to be called once the dual coefficients are final, because only records with a
+    /// non-zero coefficient are retained.
+    fn with_support_vectors(mut self, kernel: &Kernel<A>) -> Self {
+        let indices = self
+            .alpha
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| **x != A::zero())
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+
+        self.support_vectors = Some(SupportVectors {
+            method: kernel.method.clone(),
+            records: kernel.dataset.select(Axis(0), &indices),
+            indices,
+        });
+
+        self
+    }
+
+    pub fn with_phantom<S>(self) -> Svm<A, S> {
         Svm {
             alpha: self.alpha,
             rho: self.rho,
@@ -211,14 +992,19 @@ impl<'a, A: Float, T> Svm<'a, A, T> {
             exit_reason: self.exit_reason,
             obj: self.obj,
             iterations: self.iterations,
-            kernel: self.kernel,
+            kkt_violation: self.kkt_violation,
+            support_vectors: self.support_vectors,
             linear_decision: self.linear_decision,
+            calibrator: self.calibrator,
+            kernel_centering: self.kernel_centering,
+            convergence_history: self.convergence_history,
+            kernel_evaluations: self.kernel_evaluations,
             phantom: PhantomData,
         }
     }
 }
 
-impl<'a, A: Float, T> fmt::Display for Svm<'a, A, T> {
+impl<A: Float, T> fmt::Display for Svm<A, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.exit_reason {
             ExitReason::ReachedThreshold => write!(
@@ -235,6 +1021,26 @@ impl<'a, A: Float, T> fmt::Display for Svm<'a, A, T> {
                 self.obj,
                 self.nsupport()
             ),
+            ExitReason::ReachedTimeLimit => write!(
+                f,
+                "Reached time limit after {} iterations with obj = {} and {} support vectors",
+                self.iterations,
+                self.obj,
+                self.nsupport()
+            ),
+            ExitReason::Aborted => write!(
+                f,
+                "Aborted after {} iterations with obj = {} and {} support vectors",
+                self.iterations,
+                self.obj,
+                self.nsupport()
+            ),
+        }?;
+
+        write!(f, ", rho = {}", self.rho)?;
+        match self.kernel_method() {
+            Some(method) => write!(f, ", kernel {}", method),
+            None => Ok(()),
         }
     }
 }
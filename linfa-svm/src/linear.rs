@@ -0,0 +1,438 @@
+//! Linear SVM for wide or tall datasets
+//!
+//! The SMO solver works on kernel columns, which is wasteful for a linear kernel: a column costs
+//! `n_samples * n_features` operations and the solver needs many of them. This module follows
+//! LIBLINEAR and runs coordinate descent on the dual of the L2-regularized hinge loss, while
+//! keeping the primal weight vector `w = sum_i alpha_i y_i x_i` up to date. A single coordinate
+//! step then only costs `O(n_features)` and no kernel matrix is ever formed.
+//!
+//! The bias is learned by appending a constant feature to every record, as done in LIBLINEAR. It
+//! is therefore regularized as well and the solution can slightly differ from the SMO solver. A
+//! larger value of the constant feature, set with `bias`, weakens this regularization and
+//! recovers the hyperplane of the SMO solver.
+//!
+//! For streaming data the parameters also implement `IncrementalFit`, which updates the weight
+//! vector with a Pegasos-style sub-gradient step per mini-batch instead of solving the dual.
+use linfa::{
+    dataset::{Dataset, Pr, Targets},
+    traits::{Fit, IncrementalFit},
+};
+use ndarray::{s, Array1, ArrayBase, ArrayView1, Axis, Data, Ix2};
+use std::marker::PhantomData;
+
+use super::{ExitReason, Float, Svm};
+
+/// Linear Support Vector Classification solved in the primal weight space
+///
+/// The fitted model is a plain [Svm](struct.Svm.html) with a linear decision function, it
+/// predicts with the weight vector and does not store any support vectors.
+pub struct LinearSvm;
+
+impl LinearSvm {
+    pub fn params<F: Float>() -> LinearSvmParams<F> {
+        LinearSvmParams {
+            c: (F::one(), F::one()),
+            eps: F::from(1e-3).unwrap(),
+            max_iter: 1000,
+            bias: F::one(),
+        }
+    }
+}
+
+/// Hyperparameters of the linear SVM
+pub struct LinearSvmParams<F: Float> {
+    c: (F, F),
+    eps: F,
+    max_iter: usize,
+    bias: F,
+}
+
+impl<F: Float> LinearSvmParams<F> {
+    /// Set the C value for positive and negative samples
+    pub fn pos_neg_weights(mut self, c_pos: F, c_neg: F) -> Self {
+        self.c = (c_pos, c_neg);
+        self
+    }
+
+    /// Set the stopping condition
+    ///
+    /// The solver stops once the spread of the projected gradient within one pass over the
+    /// data falls below `eps`.
+    pub fn eps(mut self, eps: F) -> Self {
+        self.eps = eps;
+        self
+    }
+
+    /// Set the maximal number of passes over the data
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Set the value of the constant feature used to learn the bias
+    ///
+    /// Larger values reduce the influence of the regularization on the bias, a value of zero
+    /// fits a hyperplane through the origin.
+    pub fn bias(mut self, bias: F) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    /// Run dual coordinate descent and return the model with the primal weight vector
+    fn fit_records<D: Data<Elem = F>>(
+        &self,
+        records: &ArrayBase<D, Ix2>,
+        targets: &[bool],
+    ) -> Svm<F, Pr> {
+        assert_eq!(
+            records.nrows(),
+            targets.len(),
+            "Expected one target for every record"
+        );
+
+        let nfeatures = records.ncols();
+        let bias_sq = self.bias * self.bias;
+
+        // the weight vector stores the bias in its last entry
+        let mut w = Array1::zeros(nfeatures + 1);
+        let mut alpha = vec![F::zero(); targets.len()];
+        let diag = records
+            .outer_iter()
+            .map(|x| x.dot(&x) + bias_sq)
+            .collect::<Vec<_>>();
+
+        let mut iter = 0;
+        let mut converged = false;
+        let mut violation = F::infinity();
+        while iter < self.max_iter {
+            iter += 1;
+            let (mut pg_max, mut pg_min) = (F::neg_infinity(), F::infinity());
+
+            for (i, (x, target)) in records.outer_iter().zip(targets.iter()).enumerate() {
+                if diag[i] <= F::zero() {
+                    continue;
+                }
+
+                let (y, upper) = if *target {
+                    (F::one(), self.c.0)
+                } else {
+                    (-F::one(), self.c.1)
+                };
+                let grad = y * decision(&w, x, self.bias) - F::one();
+
+                // project the gradient onto the box constraints `0 <= alpha_i <= C_i`
+                let pg = if alpha[i] == F::zero() {
+                    grad.min(F::zero())
+                } else if alpha[i] == upper {
+                    grad.max(F::zero())
+                } else {
+                    grad
+                };
+                pg_max = pg_max.max(pg);
+                pg_min = pg_min.min(pg);
+
+                if pg != F::zero() {
+                    let old = alpha[i];
+                    alpha[i] = (alpha[i] - grad / diag[i]).max(F::zero()).min(upper);
+
+                    let delta = (alpha[i] - old) * y;
+                    let (mut weights, mut bias) = w.view_mut().split_at(Axis(0), nfeatures);
+                    weights.scaled_add(delta, &x);
+                    bias[0] += delta * self.bias;
+                }
+            }
+
+            // the spread is negative infinity, if every sample was skipped
+            violation = (pg_max - pg_min).max(F::zero());
+            if violation <= self.eps {
+                converged = true;
+                break;
+            }
+        }
+
+        let sum_alpha = alpha.iter().fold(F::zero(), |acc, a| acc + *a);
+        let obj = w.dot(&w) / F::from(2.0).unwrap() - sum_alpha;
+
+        if !converged {
+            log::warn!(
+                "Coordinate descent did not converge within {} passes, the KKT violation is {}",
+                iter,
+                violation
+            );
+        }
+
+        let rho = -w[nfeatures] * self.bias;
+        let linear_decision = w.slice(s![..nfeatures]).to_owned();
+
+        Svm {
+            alpha: alpha
+                .into_iter()
+                .zip(targets.iter())
+                .map(|(a, t)| if *t { a } else { -a })
+                .collect(),
+            rho,
+            r: None,
+            exit_reason: if converged {
+                ExitReason::ReachedThreshold
+            } else {
+                ExitReason::ReachedIterations
+            },
+            obj,
+            iterations: iter,
+            kkt_violation: violation,
+            support_vectors: None,
+            linear_decision: Some(linear_decision),
+            calibrator: None,
+            kernel_centering: None,
+            convergence_history: None,
+            kernel_evaluations: 0,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<F: Float> LinearSvmParams<F> {
+    /// Update the weight vector with a single sub-gradient step on a mini-batch
+    ///
+    /// The objective is the one of the C-SVM on all observations streamed so far, whose number
+    /// is kept in the iteration counter of the model. Each observation violating the margin
+    /// contributes at most `C` to the step, like a dual coefficient bounded by `C`.
+    fn partial_fit<D: Data<Elem = F>>(
+        &self,
+        model: Option<Svm<F, Pr>>,
+        records: &ArrayBase<D, Ix2>,
+        targets: &[bool],
+    ) -> Svm<F, Pr> {
+        assert_eq!(
+            records.nrows(),
+            targets.len(),
+            "Expected one target for every record"
+        );
+
+        let nfeatures = records.ncols();
+
+        // the weight vector stores the bias in its last entry
+        let mut w = Array1::zeros(nfeatures + 1);
+        let mut nseen = targets.len();
+        if let Some(model) = model {
+            let weights = model
+                .linear_decision
+                .expect("Online updates require a model with a linear decision function");
+            assert_eq!(
+                weights.len(),
+                nfeatures,
+                "Expected {} features",
+                weights.len()
+            );
+
+            w.slice_mut(s![..nfeatures]).assign(&weights);
+            if self.bias != F::zero() {
+                w[nfeatures] = -model.rho / self.bias;
+            }
+            nseen += model.iterations;
+        }
+
+        let mut step = Array1::zeros(nfeatures + 1);
+        let mut loss = F::zero();
+        for (x, target) in records.outer_iter().zip(targets.iter()) {
+            let (y, c) = if *target {
+                (F::one(), self.c.0)
+            } else {
+                (-F::one(), self.c.1)
+            };
+
+            let margin = y * decision(&w, x, self.bias);
+            if margin < F::one() {
+                loss += c * (F::one() - margin);
+
+                let (mut weights, mut bias) = step.view_mut().split_at(Axis(0), nfeatures);
+                weights.scaled_add(c * y, &x);
+                bias[0] += c * y * self.bias;
+            }
+        }
+        let obj = w.dot(&w) / F::from(2.0).unwrap() + loss;
+
+        // the regularization is shared by all observations seen so far, the batch therefore
+        // shrinks the weights by its fraction of them
+        if nseen > 0 {
+            let shrink = F::from(targets.len()).unwrap() / F::from(nseen).unwrap();
+            w *= F::one() - shrink;
+        }
+        w += &step;
+
+        // the optimum lies in a ball of radius `sqrt(2 C n)`, because the objective at `w = 0`
+        // is at most `C n`
+        let radius = (F::from(2 * nseen).unwrap() * self.c.0.max(self.c.1)).sqrt();
+        let norm = w.dot(&w).sqrt();
+        if norm > radius {
+            w *= radius / norm;
+        }
+
+        Svm {
+            alpha: Vec::new(),
+            rho: -w[nfeatures] * self.bias,
+            r: None,
+            exit_reason: ExitReason::ReachedIterations,
+            obj,
+            iterations: nseen,
+            kkt_violation: F::infinity(),
+            support_vectors: None,
+            linear_decision: Some(w.slice(s![..nfeatures]).to_owned()),
+            calibrator: None,
+            kernel_centering: None,
+            convergence_history: None,
+            kernel_evaluations: 0,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Evaluate `w^T x + w_b * bias` with the bias stored in the last entry of `w`
+fn decision<F: Float>(w: &Array1<F>, x: ArrayView1<F>, bias: F) -> F {
+    let nfeatures = x.len();
+
+    w.slice(s![..nfeatures]).dot(&x) + w[nfeatures] * bias
+}
+
+impl<'a, F: Float, D: Data<Elem = F>, T: Targets<Elem = bool>> Fit<'a, ArrayBase<D, Ix2>, T>
+    for LinearSvmParams<F>
+{
+    type Object = Svm<F, Pr>;
+
+    fn fit(&self, dataset: &'a Dataset<ArrayBase<D, Ix2>, T>) -> Self::Object {
+        self.fit_records(&dataset.records, dataset.targets.as_slice())
+    }
+}
+
+impl<'a, F: Float, D: Data<Elem = F>, T: Targets<Elem = bool>>
+    IncrementalFit<'a, ArrayBase<D, Ix2>, T> for LinearSvmParams<F>
+{
+    type ObjectIn = Option<Svm<F, Pr>>;
+    type ObjectOut = Svm<F, Pr>;
+
+    /// Update a linear model with a mini-batch of observations
+    ///
+    /// Without a former model the weights start at zero. The returned model is an approximation
+    /// of the batch solution: it stores no dual coefficients, its iteration counter is the
+    /// number of streamed observations and it never reports convergence. The objective is the
+    /// primal objective on the mini-batch before the update.
+    ///
+    /// __Panics__ if the former model has no linear decision function or a different number of
+    /// features
+    fn fit_with(
+        &self,
+        model: Self::ObjectIn,
+        dataset: &'a Dataset<ArrayBase<D, Ix2>, T>,
+    ) -> Self::ObjectOut {
+        self.partial_fit(model, &dataset.records, dataset.targets.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinearSvm;
+    use crate::Svm;
+    use linfa::dataset::{Dataset, Pr};
+    use linfa::traits::{Fit, IncrementalFit, Predict, Transformer};
+    use linfa_kernel::{Kernel, KernelMethod};
+    use ndarray::{Array, Axis};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Uniform;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn test_linear_svm() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((20, 2), Uniform::new(-2., -0.5), &mut rng).view(),
+                Array::random_using((20, 2), Uniform::new(0.5, 2.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..40).map(|x| x < 20).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries.clone(), targets.clone());
+
+        // a large constant feature makes the regularization of the bias negligible, without it
+        // the hyperplane is pulled towards the origin and differs from the dual solution
+        let linear: Svm<f64, Pr> = LinearSvm::params()
+            .pos_neg_weights(1.0, 1.0)
+            .bias(10.0)
+            .eps(1e-6)
+            .fit(&dataset);
+        assert!(linear.converged());
+
+        let kernel = Kernel::params()
+            .method(KernelMethod::Linear)
+            .transform(&dataset);
+        let dual = Svm::params().pos_neg_weights(1.0, 1.0).fit(&kernel);
+
+        // both solvers find the same hyperplane
+        let (w_linear, w_dual) = (
+            linear.linear_decision.as_ref().unwrap(),
+            dual.linear_decision.as_ref().unwrap(),
+        );
+        for (a, b) in w_linear.iter().zip(w_dual.iter()) {
+            assert!((a - b).abs() < 1e-3);
+        }
+        assert!((linear.rho - dual.rho).abs() < 1e-3);
+
+        // and separate the training data in the same way
+        let pred_linear: Vec<Pr> = linear.predict(entries.view());
+        let pred_dual: Vec<Pr> = dual.predict(entries.view());
+        for ((a, b), t) in pred_linear.iter().zip(pred_dual.iter()).zip(targets.iter()) {
+            assert_eq!(**a > 0.0, *t);
+            assert_eq!(**b > 0.0, *t);
+        }
+    }
+
+    #[test]
+    fn test_online_linear_svm() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((100, 2), Uniform::new(-2., -0.5), &mut rng).view(),
+                Array::random_using((100, 2), Uniform::new(0.5, 2.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..200).map(|x| x < 100).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries.clone(), targets.clone());
+        let accuracy = |model: &Svm<f64, Pr>| {
+            let pred: Vec<Pr> = model.predict(entries.view());
+            let correct = pred
+                .iter()
+                .zip(targets.iter())
+                .filter(|(p, t)| (***p > 0.0) == **t)
+                .count();
+
+            correct as f64 / 200.0
+        };
+
+        let params = LinearSvm::params().pos_neg_weights(1.0, 1.0);
+        let batch = params.fit(&dataset);
+
+        // stream the data in mini-batches of ten observations for a few epochs
+        let mut model = None;
+        for epoch in 0..5 {
+            let shuffled = dataset.shuffled(epoch);
+            for (records, targets) in shuffled
+                .records()
+                .axis_chunks_iter(Axis(0), 10)
+                .zip(shuffled.targets().chunks(10))
+            {
+                model = Some(params.fit_with(model, &Dataset::new(records, targets)));
+            }
+        }
+        let online = model.unwrap();
+
+        assert_eq!(online.iterations(), 1000);
+        assert!(!online.converged());
+        assert_eq!(online.sparsity(), 0.0);
+        assert_eq!(accuracy(&batch), 1.0);
+        assert!(accuracy(&online) >= 0.98);
+    }
+}
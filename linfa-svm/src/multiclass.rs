@@ -0,0 +1,207 @@
+//! One-vs-one multiclass classification
+//!
+//! The SMO solver is strictly binary. For `k` classes this module trains `k*(k-1)/2` binary
+//! models, one for each pair of classes, and predicts the class with the most pairwise votes.
+use linfa::{
+    dataset::{Dataset, Labels, Pr, Targets},
+    traits::Fit,
+    traits::Predict,
+};
+use linfa_kernel::KernelInner;
+use ndarray::{Array2, ArrayBase, ArrayView1, Axis, Data, Ix2};
+use std::collections::HashMap;
+
+use super::permutable_kernel::Kernel;
+use super::{Float, Svm, SvmParams};
+
+/// Hyperparameters of the one-vs-one multiclass SVM
+///
+/// The binary parameters are reused for every pairwise sub-problem.
+pub struct MulticlassSvmParams<F: Float> {
+    params: SvmParams<F, Pr>,
+}
+
+impl<F: Float> SvmParams<F, Pr> {
+    /// Train a one-vs-one multiclass model with these binary parameters
+    pub fn multiclass(self) -> MulticlassSvmParams<F> {
+        MulticlassSvmParams { params: self }
+    }
+}
+
+/// Fitted one-vs-one multiclass SVM
+///
+/// The binary model for the classes `(i, j)` with `i < j` treats `i` as the positive class.
+pub struct MulticlassSvm<F: Float> {
+    classes: Vec<usize>,
+    models: HashMap<(usize, usize), Svm<F, Pr>>,
+}
+
+impl<F: Float> MulticlassSvm<F> {
+    /// Returns the sorted classes seen during training
+    pub fn classes(&self) -> &[usize] {
+        &self.classes
+    }
+
+    /// Returns the number of pairwise binary models
+    pub fn nmodels(&self) -> usize {
+        self.models.len()
+    }
+
+    /// Predict a single sample by majority voting over all pairs
+    ///
+    /// Ties in the number of votes are broken by the summed magnitude of the decision values,
+    /// and finally by the smaller class.
+    fn predict_sample(&self, sample: ArrayView1<F>) -> usize {
+        let mut votes: HashMap<usize, (usize, F)> = HashMap::new();
+
+        for ((i, j), model) in &self.models {
+            let val = model.decision_value(sample);
+            let winner = if val > F::zero() { *i } else { *j };
+
+            let entry = votes.entry(winner).or_insert((0, F::zero()));
+            entry.0 += 1;
+            entry.1 += val.abs();
+        }
+
+        let mut best = self.classes[0];
+        let mut best_votes = (0, F::zero());
+        for class in &self.classes {
+            let class_votes = votes.get(class).cloned().unwrap_or((0, F::zero()));
+
+            if class_votes.0 > best_votes.0
+                || (class_votes.0 == best_votes.0 && class_votes.1 > best_votes.1)
+            {
+                best = *class;
+                best_votes = class_votes;
+            }
+        }
+
+        best
+    }
+}
+
+impl<'a, F: Float, T: Labels<Elem = usize>> Fit<'a, Kernel<'a, F>, T> for MulticlassSvmParams<F> {
+    type Object = MulticlassSvm<F>;
+
+    fn fit(&self, dataset: &'a Dataset<Kernel<'a, F>, T>) -> Self::Object {
+        let kernel = &dataset.records;
+        let targets = dataset.targets().as_slice();
+
+        let mut classes = dataset.labels();
+        classes.sort_unstable();
+
+        let mut models = HashMap::new();
+        for (idx, class_i) in classes.iter().enumerate() {
+            for class_j in &classes[idx + 1..] {
+                let indices = targets
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, x)| *x == class_i || *x == class_j)
+                    .map(|(i, _)| i)
+                    .collect::<Vec<_>>();
+                let pair_targets = indices
+                    .iter()
+                    .map(|i| targets[*i] == *class_i)
+                    .collect::<Vec<_>>();
+
+                // build the kernel matrix of the pair from the full kernel matrix
+                let columns = indices
+                    .iter()
+                    .map(|i| kernel.column(*i))
+                    .collect::<Vec<_>>();
+                let records = kernel.dataset.select(Axis(0), &indices);
+                let pair_kernel = Kernel {
+                    inner: KernelInner::Dense(Array2::from_shape_fn(
+                        (indices.len(), indices.len()),
+                        |(i, j)| columns[j][indices[i]],
+                    )),
+                    method: kernel.method.clone(),
+                    dataset: records.view(),
+                    linear: false,
+                };
+
+                let sample_weight = self
+                    .params
+                    .sample_weight
+                    .as_ref()
+                    .map(|weights| indices.iter().map(|i| weights[*i]).collect::<Vec<_>>());
+                let model = self.params.fit_kernel(
+                    &pair_kernel,
+                    &pair_targets,
+                    None,
+                    sample_weight.as_deref(),
+                );
+
+                models.insert((*class_i, *class_j), model);
+            }
+        }
+
+        MulticlassSvm { classes, models }
+    }
+}
+
+/// Predict the classes of a set of observations
+impl<F: Float, D: Data<Elem = F>> Predict<ArrayBase<D, Ix2>, Vec<usize>> for MulticlassSvm<F> {
+    fn predict(&self, data: ArrayBase<D, Ix2>) -> Vec<usize> {
+        data.outer_iter()
+            .map(|sample| self.predict_sample(sample))
+            .collect()
+    }
+}
+
+impl<F: Float, T: Targets> Predict<Dataset<Array2<F>, T>, Dataset<Array2<F>, Vec<usize>>>
+    for MulticlassSvm<F>
+{
+    fn predict(&self, data: Dataset<Array2<F>, T>) -> Dataset<Array2<F>, Vec<usize>> {
+        let Dataset { records, .. } = data;
+        let predicted = self.predict(records.view());
+
+        Dataset::new(records, predicted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Svm;
+    use linfa::dataset::Dataset;
+    use linfa::traits::{Fit, Predict, Transformer};
+    use linfa_kernel::{Kernel, KernelMethod};
+
+    use ndarray::{Array, Axis};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Uniform;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn test_three_class_linear() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // three well separated clusters
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((10, 2), Uniform::new(-3., -2.), &mut rng).view(),
+                Array::random_using((10, 2), Uniform::new(-0.5, 0.5), &mut rng).view(),
+                Array::random_using((10, 2), Uniform::new(2., 3.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..30).map(|x| x / 10).collect::<Vec<usize>>();
+        let dataset = Dataset::new(entries.clone(), targets.clone());
+
+        let dataset = Kernel::params()
+            .method(KernelMethod::Linear)
+            .transform(&dataset);
+
+        let model = Svm::params()
+            .pos_neg_weights(1.0, 1.0)
+            .multiclass()
+            .fit(&dataset);
+
+        assert_eq!(model.classes(), &[0, 1, 2]);
+        assert_eq!(model.nmodels(), 3);
+
+        let predicted: Vec<usize> = model.predict(entries.view());
+        assert_eq!(predicted, targets);
+    }
+}
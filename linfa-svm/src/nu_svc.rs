@@ -0,0 +1,147 @@
+//! Nu-Support Vector Classification
+//!
+//! Instead of the penalty `C` the Nu-SVC is parametrized by `nu` in the interval (0, 1], which is
+//! an upper bound on the fraction of margin errors and a lower bound on the fraction of support
+//! vectors. This makes the parameter easier to interpret than `C`.
+use linfa::{dataset::Pr, dataset::Targets, traits::Fit, Dataset};
+
+use super::permutable_kernel::Kernel;
+use super::{Float, Svm, SvmParams};
+
+/// Nu-Support Vector Classification
+///
+/// The dual coefficients are initialized such that `nu * l / 2` of them, for each class, sit at
+/// the upper bound and the SMO solver runs with the additional Nu constraint. The fitted model
+/// is a plain [Svm](struct.Svm.html).
+pub struct NuSvc;
+
+impl NuSvc {
+    pub fn params<F: Float>() -> NuSvcParams<F> {
+        NuSvcParams {
+            params: Svm::params().nu_weight(F::from(0.5).unwrap()),
+        }
+    }
+}
+
+/// Hyperparameters of the Nu-SVC
+pub struct NuSvcParams<F: Float> {
+    params: SvmParams<F, Pr>,
+}
+
+impl<F: Float> NuSvcParams<F> {
+    /// Set the Nu value, a lower bound on the fraction of support vectors
+    ///
+    /// # Panics
+    ///
+    /// If `nu` is not in the interval (0, 1]
+    pub fn nu(mut self, nu: f64) -> Self {
+        assert!(
+            nu > 0.0 && nu <= 1.0,
+            "Nu has to be in the interval (0, 1], got {}",
+            nu
+        );
+        self.params = self.params.nu_weight(F::from(nu).unwrap());
+
+        self
+    }
+
+    /// Set stopping condition
+    pub fn eps(mut self, eps: F) -> Self {
+        self.params = self.params.eps(eps);
+
+        self
+    }
+
+    /// Set the maximal number of iterations of the solver
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.params = self.params.max_iter(max_iter);
+
+        self
+    }
+
+    /// Enable or disable the shrinking heuristic of the solver
+    pub fn shrinking(mut self, shrinking: bool) -> Self {
+        self.params = self.params.shrinking(shrinking);
+
+        self
+    }
+
+    /// Enable probability estimates with Platt scaling
+    pub fn probability(mut self, probability: bool) -> Self {
+        self.params = self.params.probability(probability);
+
+        self
+    }
+}
+
+impl<'a, F: Float, T: Targets<Elem = bool>> Fit<'a, Kernel<'a, F>, T> for NuSvcParams<F>
+where
+    SvmParams<F, Pr>: Fit<'a, Kernel<'a, F>, T, Object = Svm<F, Pr>>,
+{
+    type Object = Svm<F, Pr>;
+
+    fn fit(&self, dataset: &'a Dataset<Kernel<'a, F>, T>) -> Self::Object {
+        self.params.fit(dataset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NuSvc;
+    use linfa::dataset::{Dataset, Pr};
+    use linfa::traits::{Fit, Predict, Transformer};
+    use linfa_kernel::{Kernel, KernelMethod};
+    use ndarray::{Array, Axis};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Uniform;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn test_nu_svc() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // two overlapping classes
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((50, 2), Uniform::new(-1., 0.3), &mut rng).view(),
+                Array::random_using((50, 2), Uniform::new(-0.3, 1.), &mut rng).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..100).map(|x| x < 50).collect::<Vec<_>>();
+        let dataset = Dataset::new(entries.clone(), targets.clone());
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(1.0))
+            .transform(&dataset);
+
+        for nu in &[0.2, 0.5, 0.8] {
+            let model = NuSvc::params().nu(*nu).fit(&dataset);
+            assert!(model.converged());
+
+            // nu is a lower bound on the fraction of support vectors
+            let fraction = model.nsupport() as f64 / 100.0;
+            assert!(
+                fraction >= *nu,
+                "{} support vectors for nu {}",
+                fraction,
+                nu
+            );
+        }
+
+        let model = NuSvc::params().nu(0.2).fit(&dataset);
+        let predicted: Vec<Pr> = model.predict(entries.view());
+        let correct = predicted
+            .iter()
+            .zip(targets.iter())
+            .filter(|(p, t)| (***p > 0.0) == **t)
+            .count();
+        assert!(correct > 80);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_nu_svc_range() {
+        NuSvc::params::<f64>().nu(1.5);
+    }
+}
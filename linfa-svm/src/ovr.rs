@@ -0,0 +1,256 @@
+//! One-vs-rest multiclass classification
+//!
+//! For `k` classes this module trains `k` binary models, each separating one class from all
+//! others, and predicts the class with the largest decision value. The binary problems share the
+//! kernel matrix of the whole dataset and are independent of each other. With the `rayon`
+//! feature they are solved in parallel.
+use linfa::{
+    dataset::{Dataset, Labels, Pr, Targets},
+    traits::Fit,
+    traits::Predict,
+};
+use ndarray::{Array2, ArrayBase, ArrayView1, Data, Ix2};
+#[cfg(feature = "rayon")]
+use rayon_crate::prelude::*;
+
+use super::permutable_kernel::Kernel;
+#[cfg(feature = "rayon")]
+use super::SolverParams;
+use super::{Float, Svm, SvmParams};
+
+/// Hyperparameters of the one-vs-rest multiclass SVM
+///
+/// The binary parameters are reused for every class.
+pub struct OvrSvmParams<F: Float> {
+    params: SvmParams<F, Pr>,
+}
+
+impl<F: Float> SvmParams<F, Pr> {
+    /// Train a one-vs-rest multiclass model with these binary parameters
+    pub fn one_vs_rest(self) -> OvrSvmParams<F> {
+        OvrSvmParams { params: self }
+    }
+}
+
+/// Fitted one-vs-rest multiclass SVM
+///
+/// The binary model of each class treats this class as the positive class.
+pub struct OvrSvm<F: Float> {
+    classes: Vec<usize>,
+    models: Vec<Svm<F, Pr>>,
+}
+
+impl<F: Float> OvrSvm<F> {
+    /// Returns the sorted classes seen during training
+    pub fn classes(&self) -> &[usize] {
+        &self.classes
+    }
+
+    /// Returns the number of binary models, one for each class
+    pub fn nmodels(&self) -> usize {
+        self.models.len()
+    }
+
+    /// Predict a single sample as the class with the largest decision value
+    ///
+    /// Ties are broken in favour of the smaller class.
+    fn predict_sample(&self, sample: ArrayView1<F>) -> usize {
+        let mut best = (self.classes[0], F::neg_infinity());
+        for (class, model) in self.classes.iter().zip(self.models.iter()) {
+            let val = model.decision_value(sample);
+            if val > best.1 {
+                best = (*class, val);
+            }
+        }
+
+        best.0
+    }
+}
+
+impl<F: Float> OvrSvmParams<F> {
+    /// Solve the binary problem of every class one after another
+    #[cfg(not(feature = "rayon"))]
+    fn fit_models<'a>(
+        &self,
+        kernel: &'a Kernel<'a, F>,
+        targets: &[usize],
+        classes: &[usize],
+    ) -> Vec<Svm<F, Pr>> {
+        classes
+            .iter()
+            .map(|class| {
+                let class_targets = targets.iter().map(|x| x == class).collect::<Vec<_>>();
+
+                self.params.fit_kernel(
+                    kernel,
+                    &class_targets,
+                    None,
+                    self.params.sample_weight.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    /// Solve the binary problems of all classes in parallel
+    ///
+    /// The progress callback is not thread safe and therefore not invoked, all other solver
+    /// parameters are identical to the sequential fit.
+    #[cfg(feature = "rayon")]
+    fn fit_models<'a>(
+        &self,
+        kernel: &'a Kernel<'a, F>,
+        targets: &[usize],
+        classes: &[usize],
+    ) -> Vec<Svm<F, Pr>> {
+        let problems = classes
+            .iter()
+            .map(|class| {
+                let class_targets = targets.iter().map(|x| x == class).collect::<Vec<_>>();
+                let problem = self.params.binary_problem(
+                    &class_targets,
+                    None,
+                    self.params.sample_weight.as_deref(),
+                );
+
+                (class_targets, problem)
+            })
+            .collect::<Vec<_>>();
+
+        let SolverParams {
+            eps,
+            shrinking,
+            max_iter,
+            cache_size,
+            time_limit,
+            plateau_tol,
+            plateau_checks,
+            record_history,
+            precompute_linear,
+            ..
+        } = self.params.solver_params;
+
+        problems
+            .into_par_iter()
+            .map(|(class_targets, problem)| {
+                let params = SolverParams {
+                    eps,
+                    shrinking,
+                    max_iter,
+                    cache_size,
+                    time_limit,
+                    callback: None,
+                    plateau_tol,
+                    plateau_checks,
+                    record_history,
+                    precompute_linear,
+                };
+
+                problem.solve(params, kernel, &class_targets)
+            })
+            .collect()
+    }
+}
+
+impl<'a, F: Float, T: Labels<Elem = usize>> Fit<'a, Kernel<'a, F>, T> for OvrSvmParams<F> {
+    type Object = OvrSvm<F>;
+
+    fn fit(&self, dataset: &'a Dataset<Kernel<'a, F>, T>) -> Self::Object {
+        let mut classes = dataset.labels();
+        classes.sort_unstable();
+
+        let models = self.fit_models(&dataset.records, dataset.targets().as_slice(), &classes);
+
+        OvrSvm { classes, models }
+    }
+}
+
+/// Predict the classes of a set of observations
+impl<F: Float, D: Data<Elem = F>> Predict<ArrayBase<D, Ix2>, Vec<usize>> for OvrSvm<F> {
+    fn predict(&self, data: ArrayBase<D, Ix2>) -> Vec<usize> {
+        data.outer_iter()
+            .map(|sample| self.predict_sample(sample))
+            .collect()
+    }
+}
+
+impl<F: Float, T: Targets> Predict<Dataset<Array2<F>, T>, Dataset<Array2<F>, Vec<usize>>>
+    for OvrSvm<F>
+{
+    fn predict(&self, data: Dataset<Array2<F>, T>) -> Dataset<Array2<F>, Vec<usize>> {
+        let Dataset { records, .. } = data;
+        let predicted = self.predict(records.view());
+
+        Dataset::new(records, predicted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Svm;
+    use linfa::dataset::{Dataset, Pr};
+    use linfa::traits::{Fit, Predict, Transformer};
+    use linfa_kernel::{Kernel, KernelMethod};
+
+    use ndarray::{Array, Axis};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Uniform;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn test_one_vs_rest() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // four clusters in the corners of a square
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random_using((15, 2), Uniform::new(-3., -1.5), &mut rng).view(),
+                Array::random_using((15, 2), Uniform::new(1.5, 3.), &mut rng).view(),
+                (Array::random_using((15, 2), Uniform::new(1.5, 3.), &mut rng)
+                    * ndarray::arr1(&[1., -1.]))
+                .view(),
+                (Array::random_using((15, 2), Uniform::new(1.5, 3.), &mut rng)
+                    * ndarray::arr1(&[-1., 1.]))
+                .view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..60).map(|x| x / 15).collect::<Vec<usize>>();
+        let dataset = Dataset::new(entries.clone(), targets.clone());
+        let dataset = Kernel::params()
+            .method(KernelMethod::Gaussian(2.0))
+            .transform(&dataset);
+
+        let params = Svm::params().pos_neg_weights(1.0, 1.0);
+        let model = params.clone().one_vs_rest().fit(&dataset);
+
+        assert_eq!(model.classes(), &[0, 1, 2, 3]);
+        assert_eq!(model.nmodels(), 4);
+
+        let predicted: Vec<usize> = model.predict(entries.view());
+        assert_eq!(predicted, targets);
+
+        // the models match a sequential one-vs-rest baseline of binary fits
+        let mut decision = Array::zeros((60, 4));
+        for class in 0..4 {
+            let class_targets = targets.iter().map(|x| *x == class).collect::<Vec<_>>();
+            let binary = Dataset::new(entries.clone(), class_targets);
+            let binary = Kernel::params()
+                .method(KernelMethod::Gaussian(2.0))
+                .transform(&binary);
+            let baseline: Svm<f64, Pr> = params.fit(&binary);
+
+            assert_eq!(model.models[class].alpha, baseline.alpha);
+            assert_eq!(model.models[class].rho, baseline.rho);
+            decision
+                .column_mut(class)
+                .assign(&baseline.decision_function(entries.view()));
+        }
+
+        let baseline = decision
+            .outer_iter()
+            .map(|row| (0..4).fold(0, |best, i| if row[i] > row[best] { i } else { best }))
+            .collect::<Vec<_>>();
+        assert_eq!(predicted, baseline);
+    }
+}
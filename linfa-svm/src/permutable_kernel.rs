@@ -1,6 +1,11 @@
 use crate::Float;
 use linfa_kernel::Kernel as LinfaKernel;
-use ndarray::{Array1, ArrayView2};
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2};
+#[cfg(feature = "rayon")]
+use rayon_crate::prelude::*;
+use sprs::{CsMat, CsVecView};
+use std::cell::RefCell;
+use std::rc::Rc;
 
 pub type Kernel<'a, A> = LinfaKernel<ArrayView2<'a, A>>;
 
@@ -8,7 +13,112 @@ pub trait Permutable<'a, A: Float> {
     fn swap_indices(&mut self, i: usize, j: usize);
     fn distances(&self, idx: usize, length: usize) -> Vec<A>;
     fn self_distance(&self, idx: usize) -> A;
-    fn inner(&self) -> &'a Kernel<'a, A>;
+    fn inner(&self) -> Option<&'a Kernel<'a, A>>;
+    fn kernel_evaluations(&self) -> usize;
+}
+
+/// Evaluate the entries `0..length` of a kernel column
+///
+/// With the `rayon` feature the entries are evaluated in parallel. Every entry only depends on its
+/// own position, the result is therefore identical with and without the feature.
+#[cfg(feature = "rayon")]
+fn evaluate<A: Float>(length: usize, entry: impl Fn(usize) -> A + Sync + Send) -> Vec<A> {
+    (0..length).into_par_iter().map(entry).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn evaluate<A: Float>(length: usize, entry: impl Fn(usize) -> A) -> Vec<A> {
+    (0..length).map(entry).collect()
+}
+
+/// Memory-bounded cache of kernel columns
+///
+/// Columns are stored in the original order of the samples and looked up by the original index,
+/// swapping indices during shrinking therefore leaves the cache valid. Like in LIBSVM the cached
+/// samples are linked in a circular list, ordered by their last use. If the memory budget is
+/// exhausted, the least recently used column is evicted in constant time.
+pub struct KernelCache<A: Float> {
+    columns: Vec<Option<Rc<Vec<A>>>>,
+    // links of the recency list, the entry `nsamples` is the head with the least recently used
+    // sample as successor and the most recently used one as predecessor
+    prev: Vec<usize>,
+    next: Vec<usize>,
+    len: usize,
+    capacity: usize,
+    misses: usize,
+}
+
+impl<A: Float> KernelCache<A> {
+    /// Create a cache for columns of length `nsamples` within `size_mb` megabytes
+    pub fn new(size_mb: usize, nsamples: usize) -> KernelCache<A> {
+        let column_size = usize::max(nsamples, 1) * std::mem::size_of::<A>();
+        let capacity = usize::min(size_mb * 1024 * 1024 / column_size, nsamples);
+
+        KernelCache::with_capacity(capacity, nsamples)
+    }
+
+    /// Create a cache holding at most `capacity` columns of `nsamples` samples
+    fn with_capacity(capacity: usize, nsamples: usize) -> KernelCache<A> {
+        KernelCache {
+            columns: vec![None; nsamples],
+            prev: vec![nsamples; nsamples + 1],
+            next: vec![nsamples; nsamples + 1],
+            len: 0,
+            capacity,
+            misses: 0,
+        }
+    }
+
+    /// Remove sample `idx` from the recency list
+    fn unlink(&mut self, idx: usize) {
+        let (prev, next) = (self.prev[idx], self.next[idx]);
+        self.next[prev] = next;
+        self.prev[next] = prev;
+    }
+
+    /// Insert sample `idx` as the most recently used one
+    fn push_back(&mut self, idx: usize) {
+        let head = self.columns.len();
+        let last = self.prev[head];
+
+        self.next[last] = idx;
+        self.prev[idx] = last;
+        self.next[idx] = head;
+        self.prev[head] = idx;
+    }
+
+    /// Return the column `idx` and compute it with `compute` if it is not cached
+    pub fn get(&mut self, idx: usize, compute: impl FnOnce() -> Vec<A>) -> Rc<Vec<A>> {
+        if let Some(column) = self.columns[idx].clone() {
+            self.unlink(idx);
+            self.push_back(idx);
+
+            return column;
+        }
+
+        self.misses += 1;
+        let column = Rc::new(compute());
+
+        if self.capacity > 0 {
+            if self.len >= self.capacity {
+                let lru = self.next[self.columns.len()];
+                self.unlink(lru);
+                self.columns[lru] = None;
+                self.len -= 1;
+            }
+
+            self.columns[idx] = Some(column.clone());
+            self.push_back(idx);
+            self.len += 1;
+        }
+
+        column
+    }
+
+    /// Number of computed columns
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
 }
 
 /// Kernel matrix with permutable columns
@@ -17,18 +127,25 @@ pub trait Permutable<'a, A: Float> {
 /// optimization and it is therefore necessary to reorder entries.
 pub struct PermutableKernel<'a, A: Float> {
     kernel: &'a Kernel<'a, A>,
+    cache: RefCell<KernelCache<A>>,
     kernel_diag: Array1<A>,
     kernel_indices: Vec<usize>,
     targets: Vec<bool>,
 }
 
 impl<'a, A: Float> PermutableKernel<'a, A> {
-    pub fn new(kernel: &'a Kernel<'a, A>, targets: Vec<bool>) -> PermutableKernel<'a, A> {
+    pub fn new(
+        kernel: &'a Kernel<'a, A>,
+        targets: Vec<bool>,
+        cache_size: usize,
+    ) -> PermutableKernel<'a, A> {
         let kernel_diag = kernel.diagonal();
         let kernel_indices = (0..kernel.size()).collect::<Vec<_>>();
+        let cache = RefCell::new(KernelCache::new(cache_size, kernel.size()));
 
         PermutableKernel {
             kernel,
+            cache,
             kernel_diag,
             kernel_indices,
             targets,
@@ -46,7 +163,7 @@ impl<'a, A: Float> Permutable<'a, A> for PermutableKernel<'a, A> {
     fn distances(&self, idx: usize, length: usize) -> Vec<A> {
         let idx = self.kernel_indices[idx];
 
-        let kernel = self.kernel.column(idx);
+        let kernel = self.cache.borrow_mut().get(idx, || self.kernel.column(idx));
         let target_i = self.targets[idx];
 
         // reorder entries
@@ -65,8 +182,13 @@ impl<'a, A: Float> Permutable<'a, A> for PermutableKernel<'a, A> {
     }
 
     /// Return internal kernel
-    fn inner(&self) -> &'a Kernel<'a, A> {
-        self.kernel
+    fn inner(&self) -> Option<&'a Kernel<'a, A>> {
+        Some(self.kernel)
+    }
+
+    /// Return the number of computed kernel columns
+    fn kernel_evaluations(&self) -> usize {
+        self.cache.borrow().misses()
     }
 
     /// Return distance to itself
@@ -79,17 +201,20 @@ impl<'a, A: Float> Permutable<'a, A> for PermutableKernel<'a, A> {
 
 pub struct PermutableKernelOneClass<'a, A: Float> {
     kernel: &'a Kernel<'a, A>,
+    cache: RefCell<KernelCache<A>>,
     kernel_diag: Array1<A>,
     kernel_indices: Vec<usize>,
 }
 
 impl<'a, A: Float> PermutableKernelOneClass<'a, A> {
-    pub fn new(kernel: &'a Kernel<'a, A>) -> PermutableKernelOneClass<'a, A> {
+    pub fn new(kernel: &'a Kernel<'a, A>, cache_size: usize) -> PermutableKernelOneClass<'a, A> {
         let kernel_diag = kernel.diagonal();
         let kernel_indices = (0..kernel.size()).collect::<Vec<_>>();
+        let cache = RefCell::new(KernelCache::new(cache_size, kernel.size()));
 
         PermutableKernelOneClass {
             kernel,
+            cache,
             kernel_diag,
             kernel_indices,
         }
@@ -106,7 +231,7 @@ impl<'a, A: Float> Permutable<'a, A> for PermutableKernelOneClass<'a, A> {
     fn distances(&self, idx: usize, length: usize) -> Vec<A> {
         let idx = self.kernel_indices[idx];
 
-        let kernel = self.kernel.column(idx);
+        let kernel = self.cache.borrow_mut().get(idx, || self.kernel.column(idx));
 
         // reorder entries
         (0..length)
@@ -115,8 +240,13 @@ impl<'a, A: Float> Permutable<'a, A> for PermutableKernelOneClass<'a, A> {
     }
 
     /// Return internal kernel
-    fn inner(&self) -> &'a Kernel<'a, A> {
-        self.kernel
+    fn inner(&self) -> Option<&'a Kernel<'a, A>> {
+        Some(self.kernel)
+    }
+
+    /// Return the number of computed kernel columns
+    fn kernel_evaluations(&self) -> usize {
+        self.cache.borrow().misses()
     }
 
     /// Return distance to itself
@@ -129,14 +259,16 @@ impl<'a, A: Float> Permutable<'a, A> for PermutableKernelOneClass<'a, A> {
 
 pub struct PermutableKernelRegression<'a, A: Float> {
     kernel: &'a Kernel<'a, A>,
+    cache: RefCell<KernelCache<A>>,
     kernel_diag: Array1<A>,
     kernel_indices: Vec<usize>,
     signs: Vec<bool>,
 }
 
 impl<'a, A: Float> PermutableKernelRegression<'a, A> {
-    pub fn new(kernel: &'a Kernel<'a, A>) -> PermutableKernelRegression<'a, A> {
+    pub fn new(kernel: &'a Kernel<'a, A>, cache_size: usize) -> PermutableKernelRegression<'a, A> {
         let kernel_diag = kernel.diagonal();
+        let cache = RefCell::new(KernelCache::new(cache_size, kernel.size()));
         let kernel_indices = (0..2 * kernel.size())
             .map(|x| {
                 if x < kernel.size() {
@@ -152,6 +284,7 @@ impl<'a, A: Float> PermutableKernelRegression<'a, A> {
 
         PermutableKernelRegression {
             kernel,
+            cache,
             kernel_diag,
             kernel_indices,
             signs,
@@ -168,7 +301,11 @@ impl<'a, A: Float> Permutable<'a, A> for PermutableKernelRegression<'a, A> {
 
     /// Return distances from node `idx` to all other nodes
     fn distances(&self, idx: usize, length: usize) -> Vec<A> {
-        let kernel = self.kernel.column(self.kernel_indices[idx]);
+        let kernel_idx = self.kernel_indices[idx];
+        let kernel = self
+            .cache
+            .borrow_mut()
+            .get(kernel_idx, || self.kernel.column(kernel_idx));
 
         // reorder entries
         let sign_i = self.signs[idx];
@@ -187,8 +324,113 @@ impl<'a, A: Float> Permutable<'a, A> for PermutableKernelRegression<'a, A> {
     }
 
     /// Return internal kernel
-    fn inner(&self) -> &'a Kernel<'a, A> {
-        self.kernel
+    fn inner(&self) -> Option<&'a Kernel<'a, A>> {
+        Some(self.kernel)
+    }
+
+    /// Return the number of computed kernel columns
+    fn kernel_evaluations(&self) -> usize {
+        self.cache.borrow().misses()
+    }
+
+    /// Return distance to itself
+    fn self_distance(&self, idx: usize) -> A {
+        let idx = self.kernel_indices[idx];
+
+        self.kernel_diag[idx]
+    }
+}
+
+/// Dot product of a sparse row with a dense vector, only visiting the non-zero entries
+pub fn sparse_dot<A: Float>(row: CsVecView<A>, dense: ArrayView1<A>) -> A {
+    row.iter().map(|(j, val)| *val * dense[j]).sum()
+}
+
+/// Linear kernel over sparse records with permutable columns
+///
+/// The records are stored in CSR format, one row per sample. A kernel column is computed by
+/// scattering the record of the column into a dense buffer and multiplying it with the non-zero
+/// entries of every row, which keeps the cost proportional to the number of non-zeros.
+pub struct SparseLinearKernel<'a, A: Float> {
+    records: &'a CsMat<A>,
+    cache: RefCell<KernelCache<A>>,
+    kernel_diag: Array1<A>,
+    kernel_indices: Vec<usize>,
+    targets: Vec<bool>,
+}
+
+impl<'a, A: Float> SparseLinearKernel<'a, A> {
+    pub fn new(
+        records: &'a CsMat<A>,
+        targets: Vec<bool>,
+        cache_size: usize,
+    ) -> SparseLinearKernel<'a, A> {
+        let kernel_diag = records
+            .outer_iterator()
+            .map(|row| row.iter().map(|(_, val)| *val * *val).sum())
+            .collect();
+        let kernel_indices = (0..records.rows()).collect::<Vec<_>>();
+        let cache = RefCell::new(KernelCache::new(cache_size, records.rows()));
+
+        SparseLinearKernel {
+            records,
+            cache,
+            kernel_diag,
+            kernel_indices,
+            targets,
+        }
+    }
+
+    /// Compute the kernel column of sample `idx` in the original order
+    fn column(&self, idx: usize) -> Vec<A> {
+        let mut dense = Array1::zeros(self.records.cols());
+        for (j, val) in self.records.outer_view(idx).unwrap().iter() {
+            dense[j] = *val;
+        }
+
+        let records = self.records;
+        evaluate(records.rows(), |j| {
+            sparse_dot(records.outer_view(j).unwrap(), dense.view())
+        })
+    }
+}
+
+impl<'a, A: Float> Permutable<'a, A> for SparseLinearKernel<'a, A> {
+    /// Swap two indices
+    fn swap_indices(&mut self, i: usize, j: usize) {
+        self.kernel_indices.swap(i, j);
+    }
+
+    /// Return distances from node `idx` to all other nodes
+    fn distances(&self, idx: usize, length: usize) -> Vec<A> {
+        let idx = self.kernel_indices[idx];
+
+        let kernel = self.cache.borrow_mut().get(idx, || self.column(idx));
+        let target_i = self.targets[idx];
+
+        // reorder entries
+        (0..length)
+            .map(|j| {
+                let val = kernel[self.kernel_indices[j]];
+                let target_j = self.targets[self.kernel_indices[j]];
+
+                if target_j != target_i {
+                    -val
+                } else {
+                    val
+                }
+            })
+            .collect()
+    }
+
+    /// The sparse records are not wrapped in a dense kernel
+    fn inner(&self) -> Option<&'a Kernel<'a, A>> {
+        None
+    }
+
+    /// Return the number of computed kernel columns
+    fn kernel_evaluations(&self) -> usize {
+        self.cache.borrow().misses()
     }
 
     /// Return distance to itself
@@ -199,9 +441,77 @@ impl<'a, A: Float> Permutable<'a, A> for PermutableKernelRegression<'a, A> {
     }
 }
 
+/// Precomputed kernel matrix with permutable columns
+///
+/// This wraps a Gram matrix supplied by the user, for example from a string or graph kernel. As
+/// no records are available, the fitted model can only predict from kernel values.
+pub struct PrecomputedKernel<'a, A: Float> {
+    gram: &'a Array2<A>,
+    kernel_indices: Vec<usize>,
+    targets: Vec<bool>,
+}
+
+impl<'a, A: Float> PrecomputedKernel<'a, A> {
+    pub fn new(gram: &'a Array2<A>, targets: Vec<bool>) -> PrecomputedKernel<'a, A> {
+        let kernel_indices = (0..gram.nrows()).collect::<Vec<_>>();
+
+        PrecomputedKernel {
+            gram,
+            kernel_indices,
+            targets,
+        }
+    }
+}
+
+impl<'a, A: Float> Permutable<'a, A> for PrecomputedKernel<'a, A> {
+    /// Swap two indices
+    fn swap_indices(&mut self, i: usize, j: usize) {
+        self.kernel_indices.swap(i, j);
+    }
+
+    /// Return distances from node `idx` to all other nodes
+    fn distances(&self, idx: usize, length: usize) -> Vec<A> {
+        let idx = self.kernel_indices[idx];
+
+        let kernel = self.gram.column(idx);
+        let target_i = self.targets[idx];
+
+        // reorder entries
+        (0..length)
+            .map(|j| {
+                let val = kernel[self.kernel_indices[j]];
+                let target_j = self.targets[self.kernel_indices[j]];
+
+                if target_j != target_i {
+                    -val
+                } else {
+                    val
+                }
+            })
+            .collect()
+    }
+
+    /// A precomputed kernel has no internal kernel
+    fn inner(&self) -> Option<&'a Kernel<'a, A>> {
+        None
+    }
+
+    /// The kernel matrix is supplied by the user, no kernel values are computed
+    fn kernel_evaluations(&self) -> usize {
+        0
+    }
+
+    /// Return distance to itself
+    fn self_distance(&self, idx: usize) -> A {
+        let idx = self.kernel_indices[idx];
+
+        self.gram[(idx, idx)]
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Permutable, PermutableKernel};
+    use super::{KernelCache, Permutable, PermutableKernel};
     use linfa_kernel::{Kernel, KernelInner, KernelMethod};
     use ndarray::array;
 
@@ -216,7 +526,7 @@ mod tests {
             linear: false,
         };
 
-        let mut kernel = PermutableKernel::new(&dist, targets);
+        let mut kernel = PermutableKernel::new(&dist, targets, 0);
 
         assert_eq!(kernel.distances(0, 3), &[1.0, 0.3, 0.1]);
         assert_eq!(kernel.distances(1, 3), &[0.3, 1.0, 0.5]);
@@ -236,4 +546,30 @@ mod tests {
         assert_eq!(kernel.distances(1, 3), &[0.5, 1.0, 0.1]);
         assert_eq!(kernel.distances(2, 3), &[0.3, 0.1, 1.0]);
     }
+
+    #[test]
+    fn test_kernel_cache_lru() {
+        let mut cache = KernelCache::<f64>::with_capacity(2, 4);
+
+        cache.get(0, || vec![0.0; 4]);
+        cache.get(1, || vec![1.0; 4]);
+        assert_eq!(cache.misses(), 2);
+
+        // a hit makes sample 0 the most recently used one
+        assert_eq!(*cache.get(0, || unreachable!()), vec![0.0; 4]);
+        assert_eq!(cache.misses(), 2);
+
+        // sample 1 is evicted, sample 0 stays cached
+        cache.get(2, || vec![2.0; 4]);
+        assert_eq!(cache.misses(), 3);
+        cache.get(0, || unreachable!());
+        assert_eq!(cache.misses(), 3);
+        cache.get(1, || vec![1.0; 4]);
+        assert_eq!(cache.misses(), 4);
+
+        // sample 2 was the least recently used one
+        cache.get(0, || unreachable!());
+        cache.get(2, || vec![2.0; 4]);
+        assert_eq!(cache.misses(), 5);
+    }
 }
@@ -0,0 +1,252 @@
+//! Platt scaling
+//!
+//! Maps the decision values of a binary SVM to probabilities with a sigmoid function
+//! `1 / (1 + exp(A*f + B))`. The parameters `A` and `B` are fitted to decision values of
+//! samples, which were not part of the training set. This implementation follows the
+//! pseudo-code of Lin, Lin and Weng, "A note on Platt's probabilistic outputs for support
+//! vector machines", which avoids numerical overflows of the original algorithm.
+use linfa_kernel::KernelInner;
+use ndarray::{Array2, ArrayView2, Axis};
+use ndarray_rand::rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use super::permutable_kernel::Kernel;
+use super::{Float, Svm};
+use linfa::dataset::Pr;
+
+/// Number of folds used to estimate unbiased decision values
+const NFOLDS: usize = 5;
+
+/// Estimate unbiased decision values of the training samples with cross-validation
+///
+/// The training set is split into `NFOLDS` folds, for each fold a model is trained on the
+/// remaining samples with `fit` and the decision values of the held-out samples are recorded.
+/// The samples are assigned to folds as described in [folds](fn.folds.html). The sigmoid of Platt scaling or an
+/// isotonic mapping is then fitted to these values.
+pub fn cross_validate<F: Float>(
+    kernel: &Kernel<F>,
+    targets: &[bool],
+    seed: Option<u64>,
+    fit: impl for<'a> Fn(&'a Kernel<'a, F>, &[bool]) -> Svm<F, Pr>,
+) -> Vec<F> {
+    let nsamples = targets.len();
+    let nfolds = usize::min(NFOLDS, nsamples);
+    let mut decision_values = vec![F::zero(); nsamples];
+
+    // we collect the kernel columns of all samples, because the sub-kernels and decision
+    // values of the held-out samples only need entries of the full kernel matrix
+    let columns = (0..nsamples).map(|i| kernel.column(i)).collect::<Vec<_>>();
+
+    for (valid, train) in folds(nsamples, nfolds, seed) {
+        let train_targets = train.iter().map(|i| targets[*i]).collect::<Vec<_>>();
+
+        // a fold containing a single class cannot be trained, LIBSVM assigns the decision
+        // value of the class in this case
+        if train_targets.iter().all(|x| *x) || train_targets.iter().all(|x| !*x) {
+            let val = if train_targets[0] {
+                F::one()
+            } else {
+                -F::one()
+            };
+            for i in valid {
+                decision_values[i] = val;
+            }
+            continue;
+        }
+
+        let records = kernel.dataset.select(Axis(0), &train);
+        let sub_kernel = sub_kernel(kernel, &columns, &train, records.view());
+
+        let model = fit(&sub_kernel, &train_targets);
+
+        for i in valid {
+            decision_values[i] = held_out_decision(&model, &columns[i], &train);
+        }
+    }
+
+    decision_values
+}
+
+/// Split the samples into `nfolds` pairs of validation and training indices
+///
+/// The samples are shuffled with a random number generator, seeded from `seed` or from the entropy
+/// of the operating system if no seed is given. Every `nfolds`-th shuffled sample is then assigned
+/// to the same validation fold, the indices of each fold remain sorted.
+pub(crate) fn folds(
+    nsamples: usize,
+    nfolds: usize,
+    seed: Option<u64>,
+) -> impl Iterator<Item = (Vec<usize>, Vec<usize>)> {
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut order = (0..nsamples).collect::<Vec<_>>();
+    order.shuffle(&mut rng);
+
+    let mut assignment = vec![0; nsamples];
+    for (pos, idx) in order.into_iter().enumerate() {
+        assignment[idx] = pos % nfolds;
+    }
+
+    (0..nfolds).map(move |fold| (0..nsamples).partition(|i| assignment[*i] == fold))
+}
+
+/// Assemble the kernel matrix of the samples `indices` from the columns of the full kernel
+pub(crate) fn sub_kernel<'a, F: Float>(
+    kernel: &Kernel<F>,
+    columns: &[Vec<F>],
+    indices: &[usize],
+    records: ArrayView2<'a, F>,
+) -> Kernel<'a, F> {
+    Kernel {
+        inner: KernelInner::Dense(Array2::from_shape_fn(
+            (indices.len(), indices.len()),
+            |(i, j)| columns[indices[j]][indices[i]],
+        )),
+        method: kernel.method.clone(),
+        dataset: records,
+        linear: false,
+    }
+}
+
+/// Decision value of a held-out sample with the kernel `column` of the full kernel
+///
+/// The model was trained on the samples `train` of the full kernel.
+pub(crate) fn held_out_decision<F: Float>(model: &Svm<F, Pr>, column: &[F], train: &[usize]) -> F {
+    train
+        .iter()
+        .zip(model.alpha.iter())
+        .map(|(j, a)| column[*j] * *a)
+        .sum::<F>()
+        - model.rho
+}
+
+/// Fit the parameters `(A, B)` of the sigmoid to decision values with Newton's method
+pub fn platt_newton_method<F: Float>(decision_values: &[F], targets: &[bool]) -> (F, F) {
+    let max_iter = 100;
+    let min_step = F::from(1e-10).unwrap();
+    let sigma = F::from(1e-12).unwrap();
+    let eps = F::from(1e-5).unwrap();
+
+    let num_pos = targets.iter().filter(|x| **x).count();
+    let num_neg = targets.len() - num_pos;
+    let (num_pos, num_neg) = (F::from(num_pos).unwrap(), F::from(num_neg).unwrap());
+
+    // regularized target probabilities to avoid overfitting
+    let hi_target = (num_pos + F::one()) / (num_pos + F::from(2.0).unwrap());
+    let lo_target = F::one() / (num_neg + F::from(2.0).unwrap());
+    let t = targets
+        .iter()
+        .map(|x| if *x { hi_target } else { lo_target })
+        .collect::<Vec<_>>();
+
+    let objective = |a: F, b: F| {
+        decision_values
+            .iter()
+            .zip(t.iter())
+            .map(|(dec, t)| {
+                let f_apb = *dec * a + b;
+                if f_apb >= F::zero() {
+                    *t * f_apb + (F::one() + (-f_apb).exp()).ln()
+                } else {
+                    (*t - F::one()) * f_apb + (F::one() + f_apb.exp()).ln()
+                }
+            })
+            .sum::<F>()
+    };
+
+    let mut a = F::zero();
+    let mut b = ((num_neg + F::one()) / (num_pos + F::one())).ln();
+    let mut fval = objective(a, b);
+
+    for _ in 0..max_iter {
+        // update gradient and hessian, use H' = H + sigma I
+        let (mut h11, mut h22, mut h21) = (sigma, sigma, F::zero());
+        let (mut g1, mut g2) = (F::zero(), F::zero());
+
+        for (dec, t) in decision_values.iter().zip(t.iter()) {
+            let f_apb = *dec * a + b;
+            let (p, q) = if f_apb >= F::zero() {
+                let e = (-f_apb).exp();
+                (e / (F::one() + e), F::one() / (F::one() + e))
+            } else {
+                let e = f_apb.exp();
+                (F::one() / (F::one() + e), e / (F::one() + e))
+            };
+
+            let d2 = p * q;
+            h11 += *dec * *dec * d2;
+            h22 += d2;
+            h21 += *dec * d2;
+
+            let d1 = *t - p;
+            g1 += *dec * d1;
+            g2 += d1;
+        }
+
+        // stopping criterion
+        if g1.abs() < eps && g2.abs() < eps {
+            break;
+        }
+
+        // compute the Newton direction
+        let det = h11 * h22 - h21 * h21;
+        let da = -(h22 * g1 - h21 * g2) / det;
+        let db = -(-h21 * g1 + h11 * g2) / det;
+        let gd = g1 * da + g2 * db;
+
+        // line search
+        let mut stepsize = F::one();
+        while stepsize >= min_step {
+            let new_a = a + stepsize * da;
+            let new_b = b + stepsize * db;
+            let new_f = objective(new_a, new_b);
+
+            if new_f < fval + F::from(1e-4).unwrap() * stepsize * gd {
+                a = new_a;
+                b = new_b;
+                fval = new_f;
+                break;
+            }
+
+            stepsize /= F::from(2.0).unwrap();
+        }
+
+        if stepsize < min_step {
+            break;
+        }
+    }
+
+    (a, b)
+}
+
+/// Evaluate the sigmoid at a decision value
+pub fn platt_predict<F: Float>(decision_value: F, a: F, b: F) -> F {
+    let f_apb = decision_value * a + b;
+
+    if f_apb >= F::zero() {
+        (-f_apb).exp() / (F::one() + (-f_apb).exp())
+    } else {
+        F::one() / (F::one() + f_apb.exp())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{platt_newton_method, platt_predict};
+
+    #[test]
+    fn test_platt_newton_method() {
+        let decision_values = [-3.0, -2.0, -1.5, -0.5, 0.2, 0.5, 1.0, 2.0, 2.5, 3.0];
+        let targets = [
+            false, false, false, true, false, true, true, true, true, true,
+        ];
+
+        let (a, b) = platt_newton_method(&decision_values, &targets);
+
+        // the probability should increase with the decision value
+        assert!(a < 0.0);
+        assert!(platt_predict(-3.0, a, b) < 0.2);
+        assert!(platt_predict(3.0, a, b) > 0.8);
+    }
+}
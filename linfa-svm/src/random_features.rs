@@ -0,0 +1,246 @@
+//! Random Fourier features approximating the Gaussian kernel
+//!
+//! The kernel matrix grows quadratically with the number of samples and the SMO solver does not
+//! scale to large datasets. Following Rahimi and Recht, the Gaussian kernel
+//! `exp(-gamma * ||x - y||^2)` is the expectation of `z(x)^T z(y)` for the randomized feature map
+//! ```ignore
+//! z(x) = sqrt(2 / D) * cos(W^T x + b)
+//! ```
+//! with the entries of `W` drawn from `N(0, 2 * gamma)` and `b` uniformly from `[0, 2 pi)`. The
+//! mapped records can be fed to [LinearSvm](struct.LinearSvm.html), whose cost is linear in the
+//! number of samples, and the approximation improves with the number of components `D`.
+use linfa::{
+    dataset::{Dataset, Targets},
+    traits::Transformer,
+};
+use ndarray::{Array1, Array2, ArrayBase, Data, Ix2};
+use ndarray_rand::rand::{rngs::StdRng, Rng, SeedableRng};
+use ndarray_rand::rand_distr::{Normal, Uniform};
+use ndarray_rand::RandomExt;
+
+use super::{Float, SvmParams};
+
+/// Random Fourier features of the Gaussian kernel
+pub struct RandomFourierFeatures;
+
+impl RandomFourierFeatures {
+    /// Default parameters with a seed drawn from the entropy of the operating system
+    pub fn params<F: Float>() -> RandomFourierFeaturesParams<F> {
+        RandomFourierFeaturesParams {
+            gamma: F::one(),
+            n_components: 100,
+            seed: StdRng::from_entropy().gen(),
+        }
+    }
+}
+
+impl<F: Float, T> SvmParams<F, T> {
+    /// Random Fourier features drawn from the [seed](struct.SvmParams.html#method.seed) of these
+    /// parameters
+    ///
+    /// Without a seed the projection is drawn from the entropy of the operating system.
+    pub fn random_fourier_features(&self) -> RandomFourierFeaturesParams<F> {
+        let features = RandomFourierFeatures::params();
+
+        match self.seed {
+            Some(seed) => features.seed(seed),
+            None => features,
+        }
+    }
+}
+
+/// Hyperparameters of the random feature map
+///
+/// The projection is drawn from the seed whenever records are transformed. Training and test
+/// records with the same number of features are therefore mapped into the same feature space, as
+/// long as the same parameters are used. Parameters created independently without a seed draw
+/// different projections.
+#[derive(Debug, Clone)]
+pub struct RandomFourierFeaturesParams<F: Float> {
+    gamma: F,
+    n_components: usize,
+    seed: u64,
+}
+
+impl<F: Float> RandomFourierFeaturesParams<F> {
+    /// Set the width `gamma` of the approximated kernel `exp(-gamma * ||x - y||^2)`
+    ///
+    /// This is the kernel `KernelMethod::Gaussian(1 / gamma)`.
+    pub fn gamma(mut self, gamma: F) -> Self {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Set the number of components `D` of the mapped records
+    pub fn n_components(mut self, n_components: usize) -> Self {
+        self.n_components = n_components;
+        self
+    }
+
+    /// Set the seed of the random projection
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Draw the projection `W` and the offsets `b` for records with `nfeatures` features
+    fn projection(&self, nfeatures: usize) -> (Array2<F>, Array1<F>) {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let std_dev = (2.0 * self.gamma.to_f64().unwrap()).sqrt();
+
+        let weights = Array2::random_using(
+            (nfeatures, self.n_components),
+            Normal::new(0.0, std_dev).unwrap(),
+            &mut rng,
+        );
+        let offsets = Array1::random_using(
+            self.n_components,
+            Uniform::new(0.0, 2.0 * std::f64::consts::PI),
+            &mut rng,
+        );
+
+        (
+            weights.mapv(|x| F::from(x).unwrap()),
+            offsets.mapv(|x| F::from(x).unwrap()),
+        )
+    }
+}
+
+/// Map the records into the randomized feature space
+///
+/// __Panics__ if `gamma` is negative or not finite
+impl<F: Float, D: Data<Elem = F>> Transformer<ArrayBase<D, Ix2>, Array2<F>>
+    for RandomFourierFeaturesParams<F>
+{
+    fn transform(&self, x: ArrayBase<D, Ix2>) -> Array2<F> {
+        let (weights, offsets) = self.projection(x.ncols());
+        let scale = (F::from(2.0).unwrap() / F::from(self.n_components).unwrap()).sqrt();
+
+        (x.dot(&weights) + &offsets).mapv(|x| x.cos() * scale)
+    }
+}
+
+impl<'a, F: Float, T: Targets> Transformer<&'a Dataset<Array2<F>, T>, Dataset<Array2<F>, &'a T>>
+    for RandomFourierFeaturesParams<F>
+{
+    fn transform(&self, x: &'a Dataset<Array2<F>, T>) -> Dataset<Array2<F>, &'a T> {
+        let records = self.transform(x.records.view());
+
+        Dataset::new(records, &x.targets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RandomFourierFeatures;
+    use crate::{LinearSvm, Svm, SvmParams};
+    use linfa::dataset::{Dataset, Pr};
+    use linfa::traits::{Fit, Predict, Transformer};
+    use linfa_kernel::{Kernel, KernelMethod};
+    use ndarray::{s, Array, Array2};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Uniform;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    fn accuracy(predicted: &[Pr], targets: &[bool]) -> f64 {
+        let correct = predicted
+            .iter()
+            .zip(targets.iter())
+            .filter(|(p, t)| (***p > 0.0) == **t)
+            .count();
+
+        correct as f64 / targets.len() as f64
+    }
+
+    #[test]
+    fn test_kernel_approximation() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = Array::random_using((50, 3), Uniform::new(-1., 1.), &mut rng);
+
+        let exact = Kernel::params()
+            .method(KernelMethod::Gaussian(2.0))
+            .transform(records.view());
+        let features: Array2<f64> = RandomFourierFeatures::params()
+            .seed(42)
+            .gamma(0.5)
+            .n_components(5000)
+            .transform(records.view());
+
+        assert_eq!(features.dim(), (50, 5000));
+        let approx = features.dot(&features.t());
+        for j in 0..50 {
+            for (i, expected) in exact.column(j).into_iter().enumerate() {
+                assert!((approx[(i, j)] - expected).abs() < 0.05);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_fourier_features() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // the positive class is a disc inside a ring, which is not linearly separable
+        let records = Array::random_using((400, 2), Uniform::new(-1., 1.), &mut rng);
+        let targets = records
+            .outer_iter()
+            .map(|x| x.dot(&x) < 0.4)
+            .collect::<Vec<_>>();
+        let (train, test) = (records.slice(s![..200, ..]), records.slice(s![200.., ..]));
+        let (train_targets, test_targets) = (&targets[..200], &targets[200..]);
+
+        let dataset = Dataset::new(train.to_owned(), train_targets.to_vec());
+        let kernel = Kernel::params()
+            .method(KernelMethod::Gaussian(0.5))
+            .transform(&dataset);
+        let model: Svm<f64, Pr> = Svm::params().pos_neg_weights(10.0, 10.0).fit(&kernel);
+        let exact = accuracy(&model.predict(test), test_targets);
+
+        let mut gaps = Vec::new();
+        for n_components in &[2, 20, 500] {
+            let features = Svm::<f64, Pr>::params()
+                .seed(42)
+                .random_fourier_features()
+                .gamma(2.0)
+                .n_components(*n_components);
+
+            let dataset = features.transform(&dataset);
+            let model = LinearSvm::params()
+                .pos_neg_weights(10.0, 10.0)
+                .fit(&dataset);
+            let predicted = model.predict(features.transform(test));
+
+            gaps.push(exact - accuracy(&predicted, test_targets));
+        }
+
+        assert!(exact > 0.9);
+        assert!(gaps[0] > gaps[1] && gaps[1] >= gaps[2], "{:?}", gaps);
+        assert!(gaps[2].abs() < 0.03, "{:?}", gaps);
+    }
+
+    #[test]
+    fn test_seed() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = Array::random_using((10, 3), Uniform::new(-1., 1.), &mut rng);
+
+        // the seed of the SVM parameters draws the projection
+        let transform = |params: SvmParams<f64, Pr>| -> Array2<f64> {
+            params
+                .random_fourier_features()
+                .n_components(20)
+                .transform(records.view())
+        };
+        let seeded = |seed| transform(Svm::params().seed(seed));
+        assert_eq!(seeded(7), seeded(7));
+        assert_ne!(seeded(7), seeded(8));
+        assert_eq!(
+            seeded(7),
+            RandomFourierFeatures::params()
+                .seed(7)
+                .n_components(20)
+                .transform(records.view())
+        );
+
+        // without a seed the projection is drawn from entropy
+        assert_ne!(transform(Svm::params()), transform(Svm::params()));
+    }
+}
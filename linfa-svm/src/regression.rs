@@ -1,7 +1,6 @@
 //! Support Vector Regression
 use linfa::{dataset::Dataset, traits::Fit, traits::Predict};
-use ndarray::{ArrayBase, Data, Ix2};
-use std::ops::Mul;
+use ndarray::{ArrayBase, ArrayView1, Axis, Data, Ix2};
 
 use super::permutable_kernel::{Kernel, PermutableKernelRegression};
 use super::solver_smo::SolverState;
@@ -22,10 +21,10 @@ use super::{Float, Svm, SvmParams};
 pub fn fit_epsilon<'a, A: Float>(
     params: SolverParams<A>,
     kernel: &'a Kernel<'a, A>,
-    target: &'a [A],
+    target: &[A],
     c: A,
     p: A,
-) -> Svm<'a, A, A> {
+) -> Svm<A, A> {
     let mut linear_term = vec![A::zero(); 2 * target.len()];
     let mut targets = vec![true; 2 * target.len()];
 
@@ -37,12 +36,12 @@ pub fn fit_epsilon<'a, A: Float>(
         targets[i + target.len()] = false;
     }
 
-    let kernel = PermutableKernelRegression::new(kernel);
+    let permutable = PermutableKernelRegression::new(kernel, params.cache_size);
     let solver = SolverState::new(
         vec![A::zero(); 2 * target.len()],
         linear_term,
         targets.to_vec(),
-        kernel,
+        permutable,
         vec![c; 2 * target.len()],
         params,
         false,
@@ -56,7 +55,7 @@ pub fn fit_epsilon<'a, A: Float>(
     }
     res.alpha.truncate(target.len());
 
-    res.with_phantom()
+    res.with_support_vectors(kernel).with_phantom()
 }
 
 /// Support Vector Regression with nu parameter
@@ -73,10 +72,10 @@ pub fn fit_epsilon<'a, A: Float>(
 pub fn fit_nu<'a, A: Float>(
     params: SolverParams<A>,
     kernel: &'a Kernel<'a, A>,
-    target: &'a [A],
+    target: &[A],
     c: A,
     nu: A,
-) -> Svm<'a, A, A> {
+) -> Svm<A, A> {
     let mut alpha = vec![A::zero(); 2 * target.len()];
     let mut linear_term = vec![A::zero(); 2 * target.len()];
     let mut targets = vec![true; 2 * target.len()];
@@ -94,12 +93,12 @@ pub fn fit_nu<'a, A: Float>(
         targets[i + target.len()] = false;
     }
 
-    let kernel = PermutableKernelRegression::new(kernel);
+    let permutable = PermutableKernelRegression::new(kernel, params.cache_size);
     let solver = SolverState::new(
         alpha,
         linear_term,
         targets.to_vec(),
-        kernel,
+        permutable,
         vec![c; 2 * target.len()],
         params,
         false,
@@ -113,11 +112,11 @@ pub fn fit_nu<'a, A: Float>(
     }
     res.alpha.truncate(target.len());
 
-    res.with_phantom()
+    res.with_support_vectors(kernel).with_phantom()
 }
 
 impl<'a, F: Float> Fit<'a, Kernel<'a, F>, Vec<F>> for SvmParams<F, F> {
-    type Object = Svm<'a, F, F>;
+    type Object = Svm<F, F>;
 
     fn fit(&self, dataset: &'a Dataset<Kernel<'a, F>, Vec<F>>) -> Self::Object {
         match (self.c, self.nu) {
@@ -141,7 +140,7 @@ impl<'a, F: Float> Fit<'a, Kernel<'a, F>, Vec<F>> for SvmParams<F, F> {
 }
 
 impl<'a, F: Float> Fit<'a, Kernel<'a, F>, &Vec<F>> for SvmParams<F, F> {
-    type Object = Svm<'a, F, F>;
+    type Object = Svm<F, F>;
 
     fn fit(&self, dataset: &'a Dataset<Kernel<'a, F>, &Vec<F>>) -> Self::Object {
         match (self.c, self.nu) {
@@ -165,21 +164,21 @@ impl<'a, F: Float> Fit<'a, Kernel<'a, F>, &Vec<F>> for SvmParams<F, F> {
 }
 
 /// Predict a probability with a set of observations
-impl<'a, D: Data<Elem = f64>> Predict<ArrayBase<D, Ix2>, Vec<f64>> for Svm<'a, f64, f64> {
+impl<D: Data<Elem = f64>> Predict<ArrayBase<D, Ix2>, Vec<f64>> for Svm<f64, f64> {
     fn predict(&self, data: ArrayBase<D, Ix2>) -> Vec<f64> {
         data.outer_iter()
-            .map(|data| {
-                let val = match self.linear_decision {
-                    Some(ref x) => x.mul(&data).sum() - self.rho,
-                    None => self.kernel.weighted_sum(&self.alpha, data.view()) - self.rho,
-                };
-
-                // this is safe because `F` is only implemented for `f32` and `f64`
-                val
-            })
+            .map(|data| self.decision_value(data))
             .collect()
     }
 }
+
+impl Svm<f64, f64> {
+    /// Predict the target of a single observation
+    pub fn predict_one(&self, sample: ArrayView1<f64>) -> f64 {
+        self.predict(sample.insert_axis(Axis(0)))[0]
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::Svm;
@@ -188,7 +187,11 @@ pub mod tests {
     use linfa::metrics::Regression;
     use linfa::traits::{Fit, Predict, Transformer};
     use linfa_kernel::{Kernel, KernelMethod};
-    use ndarray::{Array, Array1};
+    use ndarray::{array, Array, Array1};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Uniform;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
 
     #[test]
     fn test_linear_epsilon_regression() {
@@ -204,12 +207,42 @@ pub mod tests {
 
         let dataset = Dataset::new(kernel, &target);
 
-        let model = Svm::params().nu_eps(2., 0.01).fit(&dataset);
+        let model = Svm::params().c_eps(2., 0.01).fit(&dataset);
 
         println!("{}", model);
 
         let predicted = Array1::from(model.predict(sin_curve.clone()));
         assert!(predicted.mean_squared_error(&target) < 1e-2);
+        assert_eq!(model.predict_one(sin_curve.row(0)), predicted[0]);
+    }
+
+    #[test]
+    fn test_noisy_linear_epsilon_regression() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let eps = 0.5;
+
+        // noisy linear function `y = 2x + 1` with noise smaller than the tolerance
+        let records = Array::linspace(0f64, 10., 50).into_shape((50, 1)).unwrap();
+        let noise = Array::random_using(50, Uniform::new(-0.2, 0.2), &mut rng);
+        let target = (records.column(0).mapv(|x| 2. * x + 1.) + noise).to_vec();
+
+        let kernel = Kernel::params()
+            .method(KernelMethod::Linear)
+            .transform(&records);
+
+        let dataset = Dataset::new(kernel, &target);
+
+        let model = Svm::params().c_eps(10., eps).fit(&dataset);
+
+        // all predictions should lie within the epsilon tube around the targets
+        let predicted = model.predict(records.view());
+        for (p, t) in predicted.iter().zip(target.iter()) {
+            assert!((p - t).abs() <= eps + 1e-3);
+        }
+
+        // and the learned slope should approximate the original function
+        let slope = model.predict(array![[1.]]).remove(0) - model.predict(array![[0.]]).remove(0);
+        assert!((slope - 2.).abs() < 0.1);
     }
 
     #[test]
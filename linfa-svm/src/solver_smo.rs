@@ -2,7 +2,16 @@ use super::permutable_kernel::Permutable;
 use super::{ExitReason, Float, Svm};
 
 use ndarray::{Array1, Axis};
+use std::cell::RefCell;
 use std::marker::PhantomData;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Progress callback of the solver
+///
+/// Called with the current iteration and an estimate of the objective. Returning `false` stops
+/// the solver.
+pub type SolverCallback<A> = Rc<RefCell<dyn FnMut(usize, A) -> bool>>;
 
 /// Parameters of the solver routine
 #[derive(Clone)]
@@ -10,7 +19,67 @@ pub struct SolverParams<A: Float> {
     /// Stopping condition
     pub eps: A,
     /// Should we shrink, e.g. ignore bounded alphas
+    ///
+    /// Without shrinking the active set is never permuted and the solution is reproducible.
     pub shrinking: bool,
+    /// Maximal number of iterations, defaults to `max(10_000_000, 100 * n)` if not set
+    pub max_iter: Option<usize>,
+    /// Size of the kernel column cache in megabytes
+    pub cache_size: usize,
+    /// Wall-clock budget of the solver, checked whenever the shrinking counter resets
+    pub time_limit: Option<Duration>,
+    /// Progress callback, invoked whenever the shrinking counter resets
+    pub callback: Option<SolverCallback<A>>,
+    /// Relative change of the objective below which the solver is considered to plateau,
+    /// checked whenever the shrinking counter resets
+    pub plateau_tol: Option<A>,
+    /// Number of consecutive checks on a plateau after which the solver stops
+    pub plateau_checks: usize,
+    /// Record the KKT violation whenever the shrinking counter resets
+    pub record_history: bool,
+    /// Accumulate the weight vector of a linear kernel after solving
+    pub precompute_linear: bool,
+}
+
+impl<A: Float> SolverParams<A> {
+    /// Default stopping condition
+    ///
+    /// This is `1e-7`, but at least a thousand machine epsilons. Below that the KKT violation is
+    /// dominated by rounding errors of the gradient and a `f32` solver would never converge.
+    pub fn default_eps() -> A {
+        A::max(A::from(1e-7).unwrap(), A::epsilon() * A::from(1e3).unwrap())
+    }
+}
+
+/// Replacement of a degenerate curvature `K_ii + K_jj - 2 K_ij` of the working set
+///
+/// The curvature is non-positive for indefinite kernels and can be close to zero for
+/// (almost) duplicated samples. LIBSVM uses `1e-12` for double precision, scaling with the
+/// machine epsilon keeps the step bounded for `f32` as well.
+fn tau<A: Float>() -> A {
+    A::epsilon() * A::from(1e4).unwrap()
+}
+
+/// Multiple of `eps`, which the KKT violation may not exceed for a plateau of the objective
+///
+/// The objective is quadratic in the dual coefficients and flattens out long before the
+/// coefficients settle. Requiring the violation to be within three orders of magnitude of the
+/// stopping threshold keeps the early exit close to the solution of the full solve.
+fn plateau_slack<A: Float>() -> A {
+    A::from(1e3).unwrap()
+}
+
+/// Center of the feasible interval `[lb, ub]` of the offset without free variables
+///
+/// Either bound is infinite if all alphas of a class sit at the same bound. The finite bound is
+/// used in this case instead of returning an infinite or `NaN` offset.
+fn midpoint<A: Float>(ub: A, lb: A) -> A {
+    match (ub.is_finite(), lb.is_finite()) {
+        (true, true) => (ub + lb) / A::from(2.0).unwrap(),
+        (true, false) => ub,
+        (false, true) => lb,
+        (false, false) => A::zero(),
+    }
 }
 
 /// Status of alpha variables of the solver
@@ -176,6 +245,7 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
         self.active_set.swap(i, j);
         self.kernel.swap_indices(i, j);
         self.targets.swap(i, j);
+        self.bounds.swap(i, j);
     }
 
     /// Reconstruct gradients from inactivate variables
@@ -200,7 +270,7 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
             for i in self.nactive()..self.ntotal() {
                 let dist_i = self.kernel.distances(i, self.nactive());
                 for j in 0..self.nactive() {
-                    if self.alpha[i].free_floating() {
+                    if self.alpha[j].free_floating() {
                         self.gradient[i] += self.alpha[j].val() * dist_i[j];
                     }
                 }
@@ -231,12 +301,17 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
         let old_alpha_i = self.alpha[i].val();
         let old_alpha_j = self.alpha[j].val();
 
+        // the status of the variables before the update decides whether the gradient of the
+        // variables at the upper bound changes
+        let ui = self.alpha[i].reached_upper();
+        let uj = self.alpha[j].reached_upper();
+
         if self.targets[i] != self.targets[j] {
             let mut quad_coef = self.kernel.self_distance(i)
                 + self.kernel.self_distance(j)
                 + (A::one() + A::one()) * dist_i[j];
-            if quad_coef <= A::zero() {
-                quad_coef = A::from(1e-10).unwrap();
+            if quad_coef <= tau() {
+                quad_coef = tau();
             }
 
             let delta = -(self.gradient[i] + self.gradient[j]) / quad_coef;
@@ -270,8 +345,8 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
             //dbg!(self.kernel.self_distance(i), self.kernel.self_distance(j), A::from(2.0).unwrap() * dist_i[j]);
             let mut quad_coef = self.kernel.self_distance(i) + self.kernel.self_distance(j)
                 - A::from(2.0).unwrap() * dist_i[j];
-            if quad_coef <= A::zero() {
-                quad_coef = A::from(1e-10).unwrap();
+            if quad_coef <= tau() {
+                quad_coef = tau();
             }
 
             let delta = (self.gradient[i] - self.gradient[j]) / quad_coef;
@@ -322,9 +397,6 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
         }
 
         // update alpha status and gradient bar
-        let ui = self.alpha[i].reached_upper();
-        let uj = self.alpha[j].reached_upper();
-
         self.alpha[i] = Alpha::from(self.alpha[i].val(), self.bound(i));
         self.alpha[j] = Alpha::from(self.alpha[j].val(), self.bound(j));
 
@@ -348,11 +420,11 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
             let dist_j = self.kernel.distances(j, self.ntotal());
             let bound_j = self.bound(j);
             if uj {
-                for k in 0..self.nactive() {
+                for k in 0..self.ntotal() {
                     self.gradient_fixed[k] -= bound_j * dist_j[k];
                 }
             } else {
-                for k in 0..self.nactive() {
+                for k in 0..self.ntotal() {
                     self.gradient_fixed[k] += bound_j * dist_j[k];
                 }
             }
@@ -445,11 +517,7 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
                                 + self.kernel.self_distance(j)
                                 - A::from(2.0).unwrap() * self.target(i) * dist_ij;
 
-                            let obj_diff = if quad_coef > A::zero() {
-                                -(grad_diff * grad_diff) / quad_coef
-                            } else {
-                                -(grad_diff * grad_diff) / A::from(1e-10).unwrap()
-                            };
+                            let obj_diff = -(grad_diff * grad_diff) / A::max(quad_coef, tau());
 
                             if obj_diff <= obj_diff_min.0 {
                                 obj_diff_min = (obj_diff, j as isize);
@@ -466,11 +534,7 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
                             + self.kernel.self_distance(j)
                             + A::from(2.0).unwrap() * self.target(i) * dist_ij;
 
-                        let obj_diff = if quad_coef > A::zero() {
-                            -(grad_diff * grad_diff) / quad_coef
-                        } else {
-                            -(grad_diff * grad_diff) / A::from(1e-10).unwrap()
-                        };
+                        let obj_diff = -(grad_diff * grad_diff) / A::max(quad_coef, tau());
                         if obj_diff <= obj_diff_min.0 {
                             obj_diff_min = (obj_diff, j as isize);
                         }
@@ -525,11 +589,7 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
                         let quad_coef = self.kernel.self_distance(i) + self.kernel.self_distance(j)
                             - A::from(2.0).unwrap() * dist_i_p[j];
 
-                        let obj_diff = if quad_coef > A::zero() {
-                            -(grad_diff * grad_diff) / quad_coef
-                        } else {
-                            -(grad_diff * grad_diff) / A::from(1e-10).unwrap()
-                        };
+                        let obj_diff = -(grad_diff * grad_diff) / A::max(quad_coef, tau());
 
                         if obj_diff <= obj_diff_min.0 {
                             obj_diff_min = (obj_diff, j as isize);
@@ -550,11 +610,7 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
                     let quad_coef = self.kernel.self_distance(i) + self.kernel.self_distance(j)
                         - A::from(2.0).unwrap() * dist_i_n[j];
 
-                    let obj_diff = if quad_coef > A::zero() {
-                        -(grad_diff * grad_diff) / quad_coef
-                    } else {
-                        -(grad_diff * grad_diff) / A::from(1e-10).unwrap()
-                    };
+                    let obj_diff = -(grad_diff * grad_diff) / A::max(quad_coef, tau());
                     if obj_diff <= obj_diff_min.0 {
                         obj_diff_min = (obj_diff, j as isize);
                     }
@@ -589,7 +645,7 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
             if self.targets[i] {
                 self.gradient[i] > gmax2
             } else {
-                -self.gradient[i] > gmax1
+                self.gradient[i] > gmax1
             }
         } else {
             false
@@ -630,8 +686,9 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
             self.nactive = self.ntotal();
         }
 
-        // swap items until working set is homogeneous
-        for i in 0..self.nactive() {
+        // swap items until working set is homogeneous, the active set shrinks while it is visited
+        let mut i = 0;
+        while i < self.nactive() {
             if self.should_shrunk(i, gmax1, gmax2) {
                 self.nactive -= 1;
                 // only consider items behing this one
@@ -643,11 +700,14 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
                     self.nactive -= 1;
                 }
             }
+            i += 1;
         }
     }
 
     pub fn do_shrinking_nu(&mut self) {
-        let (gmax1, gmax2, gmax3, gmax4) = self.max_violating_pair_nu();
+        // the maximal violations are named like in LIBSVM, where the second and fourth one are
+        // swapped
+        let (gmax1, gmax4, gmax2, gmax3) = self.max_violating_pair_nu();
         let (gmax1, gmax2, gmax3, gmax4) = (gmax1.0, gmax2.0, gmax3.0, gmax4.0);
 
         // work on all variables when 10*eps is reached
@@ -659,8 +719,9 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
             self.nactive = self.ntotal();
         }
 
-        // swap items until working set is homogeneous
-        for i in 0..self.nactive() {
+        // swap items until working set is homogeneous, the active set shrinks while it is visited
+        let mut i = 0;
+        while i < self.nactive() {
             if self.should_shrunk_nu(i, gmax1, gmax2, gmax3, gmax4) {
                 self.nactive -= 1;
                 // only consider items behing this one
@@ -672,6 +733,7 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
                     self.nactive -= 1;
                 }
             }
+            i += 1;
         }
     }
 
@@ -710,7 +772,7 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
         if nfree > 0 {
             sum_free / A::from(nfree).unwrap()
         } else {
-            (ub + lb) / A::from(2.0).unwrap()
+            midpoint(ub, lb)
         }
     }
 
@@ -725,7 +787,7 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
                 if self.alpha[i].reached_upper() {
                     lb1 = A::max(lb1, self.gradient[i]);
                 } else if self.alpha[i].reached_lower() {
-                    ub1 = A::max(ub1, self.gradient[i]);
+                    ub1 = A::min(ub1, self.gradient[i]);
                 } else {
                     nfree1 += 1;
                     sum_free1 += self.gradient[i];
@@ -736,7 +798,7 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
                 if self.alpha[i].reached_upper() {
                     lb2 = A::max(lb2, self.gradient[i]);
                 } else if self.alpha[i].reached_lower() {
-                    ub2 = A::max(ub2, self.gradient[i]);
+                    ub2 = A::min(ub2, self.gradient[i]);
                 } else {
                     nfree2 += 1;
                     sum_free2 += self.gradient[i];
@@ -747,12 +809,12 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
         let r1 = if nfree1 > 0 {
             sum_free1 / A::from(nfree1).unwrap()
         } else {
-            (ub1 + lb1) / A::from(2.0).unwrap()
+            midpoint(ub1, lb1)
         };
         let r2 = if nfree2 > 0 {
             sum_free2 / A::from(nfree2).unwrap()
         } else {
-            (ub2 + lb2) / A::from(2.0).unwrap()
+            midpoint(ub2, lb2)
         };
 
         self.r = (r1 + r2) / A::from(2.0).unwrap();
@@ -760,20 +822,145 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
         (r1 - r2) / A::from(2.0).unwrap()
     }
 
-    pub fn solve(mut self) -> Svm<'a, A, A> {
+    /// Evaluate the objective function `a^T Q a / 2 + p^T a` at arbitrary coefficients
+    ///
+    /// The coefficients are unsigned and ordered like the variables of this state, which is the
+    /// order of construction before the solver permuted them. This is independent of the
+    /// current solution and useful to verify warm starts or the solution of a fitted model.
+    ///
+    /// # Panics
+    ///
+    /// If the number of coefficients differs from the number of variables
+    pub fn objective(&self, alpha: &[A]) -> A {
+        assert_eq!(
+            alpha.len(),
+            self.ntotal(),
+            "Expected {} coefficients, got {}",
+            self.ntotal(),
+            alpha.len()
+        );
+
+        // accumulate the gradient d(a) = p + Q a of the given coefficients
+        let mut gradient = self.p.clone();
+        for i in 0..alpha.len() {
+            if alpha[i] != A::zero() {
+                let dist_i = self.kernel.distances(i, alpha.len());
+                for j in 0..alpha.len() {
+                    gradient[j] += alpha[i] * dist_i[j];
+                }
+            }
+        }
+
+        let mut v = A::zero();
+        for i in 0..alpha.len() {
+            v += alpha[i] * (gradient[i] + self.p[i]);
+        }
+
+        v / A::from(2.0).unwrap()
+    }
+
+    /// Calculate the objective function of the current solution
+    ///
+    /// While variables are shrunk, their gradient may be outdated and this is only an estimate.
+    fn current_objective(&self) -> A {
+        let mut v = A::zero();
+        for i in 0..self.targets.len() {
+            v += self.alpha[i].val() * (self.gradient[i] + self.p[i]);
+        }
+
+        v / A::from(2.0).unwrap()
+    }
+
+    /// Estimate the violation of the KKT conditions
+    ///
+    /// This is the gap of the maximal violating pair, which the solver compares against `eps`.
+    /// While variables are shrunk, only the active variables are considered.
+    fn kkt_violation(&self) -> A {
+        let violation = if self.nu_constraint {
+            let (gmax1, gmax2, gmax3, gmax4) = self.max_violating_pair_nu();
+            A::max(gmax1.0 + gmax2.0, gmax3.0 + gmax4.0)
+        } else {
+            let (gmax1, gmax2) = self.max_violating_pair();
+            gmax1.0 + gmax2.0
+        };
+
+        // a sum of negative infinities means that no pair can be selected at all
+        violation.max(A::zero())
+    }
+
+    pub fn solve(mut self) -> Svm<A, A> {
         let mut iter = 0;
-        let max_iter = if self.targets.len() > std::usize::MAX / 100 {
-            std::usize::MAX
+        let max_iter = match self.params.max_iter {
+            Some(max_iter) => max_iter,
+            None => {
+                let max_iter = if self.targets.len() > std::usize::MAX / 100 {
+                    std::usize::MAX
+                } else {
+                    100 * self.targets.len()
+                };
+
+                usize::max(10_000_000, max_iter)
+            }
+        };
+
+        let start = Instant::now();
+        let mut timed_out = false;
+        let mut aborted = false;
+        let mut plateaued = false;
+        let (mut last_objective, mut plateau_count): (Option<A>, _) = (None, 0);
+        let mut history = if self.params.record_history {
+            Some(Vec::new())
         } else {
-            100 * self.targets.len()
+            None
         };
 
-        let max_iter = usize::max(10_000_000, max_iter);
         let mut counter = usize::min(self.targets.len(), 1000) + 1;
         while iter < max_iter {
             counter -= 1;
             if counter == 0 {
                 counter = usize::min(self.ntotal(), 1000);
+
+                if let Some(history) = history.as_mut() {
+                    history.push(self.kkt_violation());
+                }
+
+                if let Some(callback) = &self.params.callback {
+                    if !(callback.borrow_mut())(iter, self.current_objective()) {
+                        aborted = true;
+                        break;
+                    }
+                }
+
+                // checking the clock only here avoids the overhead in every iteration
+                if let Some(time_limit) = self.params.time_limit {
+                    if start.elapsed() >= time_limit {
+                        timed_out = true;
+                        break;
+                    }
+                }
+
+                // stop once the objective made no relative progress for a couple of checks, but
+                // only close to the optimum, a flat objective far from it may still hide large
+                // changes of the dual coefficients
+                if let Some(tol) = self.params.plateau_tol {
+                    let objective = self.current_objective();
+                    if let Some(last) = last_objective {
+                        if (objective - last).abs() <= tol * last.abs()
+                            && self.kkt_violation() <= self.params.eps * plateau_slack::<A>()
+                        {
+                            plateau_count += 1;
+                        } else {
+                            plateau_count = 0;
+                        }
+                    }
+                    last_objective = Some(objective);
+
+                    if plateau_count >= self.params.plateau_checks {
+                        plateaued = true;
+                        break;
+                    }
+                }
+
                 if self.params.shrinking {
                     self.do_shrinking();
                 }
@@ -781,7 +968,9 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
 
             let (mut i, mut j, is_optimal) = self.select_working_set();
             if is_optimal {
+                // the shrunk variables may still violate the optimality conditions
                 self.reconstruct_gradient();
+                self.nactive = self.ntotal();
                 let (i2, j2, is_optimal) = self.select_working_set();
                 if is_optimal {
                     break;
@@ -799,7 +988,9 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
             self.update((i, j));
         }
 
-        if iter >= max_iter && self.nactive() < self.targets.len() {
+        if (iter >= max_iter || timed_out || aborted || plateaued)
+            && self.nactive() < self.targets.len()
+        {
             self.reconstruct_gradient();
             self.nactive = self.ntotal();
         }
@@ -811,34 +1002,52 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
             None
         };
 
-        // calculate object function
-        let mut v = A::zero();
-        for i in 0..self.targets.len() {
-            v += self.alpha[i].val() * (self.gradient[i] + self.p[i]);
-        }
-        let obj = v / A::from(2.0).unwrap();
+        let obj = self.current_objective();
+        let kkt_violation = self.kkt_violation();
 
-        let exit_reason = if max_iter == iter {
+        let exit_reason = if aborted {
+            ExitReason::Aborted
+        } else if timed_out {
+            ExitReason::ReachedTimeLimit
+        } else if max_iter == iter {
             ExitReason::ReachedIterations
         } else {
             ExitReason::ReachedThreshold
         };
 
-        // put back the solution
-        let alpha: Vec<A> = (0..self.ntotal())
-            .map(|i| self.alpha[self.active_set[i]].val())
-            .collect();
+        if exit_reason == ExitReason::ReachedIterations {
+            log::warn!(
+                "SMO solver did not converge within {} iterations, the KKT violation is {}",
+                iter,
+                kkt_violation
+            );
+        }
+
+        // put back the solution, the active set stores the original index of each variable
+        let mut alpha = vec![A::zero(); self.ntotal()];
+        for i in 0..self.ntotal() {
+            alpha[self.active_set[i]] = self.alpha[i].val();
+        }
 
         // if the kernel is linear, then we can pre-calculate the dot product
-        let linear_decision = if self.kernel.inner().is_linear() {
-            let mut tmp = Array1::zeros(self.kernel.inner().dataset.len_of(Axis(1)));
-            for (i, elm) in self.kernel.inner().dataset.outer_iter().enumerate() {
-                tmp.scaled_add(self.target(i) * alpha[i], &elm);
-            }
+        //
+        // the weight vector is accumulated from the records, this works for dense and sparse
+        // kernel matrices alike, because prediction evaluates the full kernel function anyway
+        //
+        // in regression problems every sample occurs twice, for the positive and negative
+        // deviation, and the corresponding record has to be found by wrapping around
+        let linear_decision = match self.kernel.inner() {
+            Some(kernel) if kernel.is_linear() && self.params.precompute_linear => {
+                let dataset = &kernel.dataset;
+                let mut tmp = Array1::zeros(dataset.len_of(Axis(1)));
+                for i in 0..self.ntotal() {
+                    let idx = self.active_set[i] % dataset.len_of(Axis(0));
+                    tmp.scaled_add(self.target(i) * self.alpha[i].val(), &dataset.row(idx));
+                }
 
-            Some(tmp)
-        } else {
-            None
+                Some(tmp)
+            }
+            _ => None,
         };
 
         Svm {
@@ -848,8 +1057,13 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
             exit_reason,
             obj,
             iterations: iter,
-            kernel: self.kernel.inner(),
+            kkt_violation,
+            support_vectors: None,
             linear_decision,
+            calibrator: None,
+            kernel_centering: None,
+            convergence_history: history,
+            kernel_evaluations: self.kernel.kernel_evaluations(),
             phantom: PhantomData,
         }
     }
@@ -873,12 +1087,20 @@ mod tests {
             dataset: &kernel
         };
         let targets = vec![true, true];
-        let kernel = PermutableKernel::new(&kernel, targets.clone());
+        let kernel = PermutableKernel::new(&kernel, targets.clone(), 100);
 
         let p = vec![-34., -38.];
         let params = SolverParams {
             eps: 1e-6,
-            shrinking: false
+            shrinking: false,
+            max_iter: None,
+            cache_size: 100,
+            time_limit: None,
+            callback: None,
+            plateau_tol: None,
+            plateau_checks: 3,
+            record_history: false,
+            precompute_linear: true,
         };
 
         let solver = SolverState::new(vec![1.0, 1.0], p, targets, kernel, vec![1000.0; 2], &params, false);
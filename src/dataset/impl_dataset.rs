@@ -1,8 +1,10 @@
-use ndarray::{Array1, Array2, ArrayBase, ArrayView1, ArrayView2, Axis, Data, Dimension, Ix2};
-use rand::{seq::SliceRandom, Rng};
+use ndarray::{s, Array1, Array2, ArrayBase, ArrayView1, ArrayView2, Axis, Data, Dimension, Ix2};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 use std::collections::HashMap;
 
-use super::{iter::Iter, Dataset, Float, Label, Labels, Records, Targets};
+use super::{iter::Iter, Dataset, Float, Label, Labels, Records, Resample, Targets};
+use crate::error::Error;
+use crate::traits::{Fit, Predict};
 
 impl<F: Float, L: Label> Dataset<Array2<F>, Vec<L>> {
     pub fn iter(&self) -> Iter<'_, Array2<F>, Vec<L>> {
@@ -150,6 +152,19 @@ impl<F: Float, T: Clone> Dataset<Array2<F>, Vec<T>> {
 }
 
 impl<F: Float, T: Clone> Dataset<Array2<F>, Array1<T>> {
+    /// Create a dataset, which owns its records and targets
+    ///
+    /// __Panics__ if the number of records and targets differ
+    pub fn from_arrays(records: Array2<F>, targets: Array1<T>) -> Self {
+        assert_eq!(
+            records.nrows(),
+            targets.len(),
+            "number of records and targets have to be equal"
+        );
+
+        Dataset::new(records, targets)
+    }
+
     pub fn shuffle<R: Rng>(self, mut rng: &mut R) -> Self {
         let mut indices = (0..self.observations()).collect::<Vec<_>>();
         indices.shuffle(&mut rng);
@@ -185,6 +200,11 @@ impl<F: Float, T: Targets, D: Data<Elem = F>> Dataset<ArrayBase<D, Ix2>, T> {
         (dataset1, dataset2)
     }
 
+    /// Borrow the records and targets at once, for example to predict on the training data
+    pub fn records_targets(&self) -> (ArrayView2<'_, F>, &[T::Elem]) {
+        (self.records.view(), self.targets.as_slice())
+    }
+
     pub fn view(&self) -> Dataset<ArrayView2<'_, F>, ArrayView1<'_, T::Elem>> {
         let records = self.records().view();
         let targets = ArrayView1::from(self.targets.as_slice());
@@ -193,6 +213,33 @@ impl<F: Float, T: Targets, D: Data<Elem = F>> Dataset<ArrayBase<D, Ix2>, T> {
     }
 }
 
+impl<F: Float, E: Clone, T: Targets<Elem = E>, D: Data<Elem = F>> Dataset<ArrayBase<D, Ix2>, T> {
+    /// Return a copy of the dataset with its observations in random order
+    ///
+    /// Records, targets and weights are permuted in lockstep with a random number generator
+    /// seeded by `seed`, the same seed therefore always produces the same order. Datasets of
+    /// borrowed records are copied into an owned dataset.
+    pub fn shuffled(&self, seed: u64) -> Dataset<Array2<F>, Vec<E>> {
+        let mut indices = (0..self.observations()).collect::<Vec<_>>();
+        indices.shuffle(&mut StdRng::seed_from_u64(seed));
+
+        self.select_observations(&indices)
+    }
+
+    /// Copy the observations at `indices` into an owned dataset, together with their weights
+    fn select_observations(&self, indices: &[usize]) -> Dataset<Array2<F>, Vec<E>> {
+        let records = self.records().select(Axis(0), indices);
+        let targets = self.targets().as_slice();
+        let targets = indices.iter().map(|i| targets[*i].clone()).collect();
+        let weights = match self.weights() {
+            Some(weights) => indices.iter().map(|i| weights[*i]).collect(),
+            None => vec![],
+        };
+
+        Dataset::new(records, targets).with_weights(weights)
+    }
+}
+
 impl<F: Float, L: Label, T: Labels<Elem = L>, D: Data<Elem = F>> Dataset<ArrayBase<D, Ix2>, T> {
     pub fn one_vs_all(&self) -> Vec<Dataset<ArrayView2<'_, F>, Vec<bool>>> {
         self.labels()
@@ -211,6 +258,182 @@ impl<F: Float, L: Label, T: Labels<Elem = L>, D: Data<Elem = F>> Dataset<ArrayBa
     }
 }
 
+impl<F: Float, L: Label, T: Labels<Elem = L>, D: Data<Elem = F>> Dataset<ArrayBase<D, Ix2>, T> {
+    /// Evaluate the accuracy of a model with k-fold cross-validation
+    ///
+    /// The observations are shuffled with `rng` and split into `k` folds of nearly equal size.
+    /// For each fold a model is fitted with `params` on the remaining folds and the fraction of
+    /// correctly predicted targets in the held-out fold is recorded. Errors of the fitting
+    /// routine are passed on.
+    ///
+    /// Returns the accuracy of each fold.
+    ///
+    /// __Panics__ if `k` is smaller than two or larger than the number of observations
+    pub fn cross_validate<P, M, E, R>(
+        &self,
+        k: usize,
+        rng: &mut R,
+        params: &P,
+    ) -> Result<Array1<F>, E>
+    where
+        P: for<'a> Fit<'a, ArrayView2<'a, F>, ArrayView1<'a, L>, Object = Result<M, E>>,
+        M: for<'a> Predict<ArrayView2<'a, F>, Array1<L>>,
+        R: Rng,
+    {
+        let nobservations = self.observations();
+        assert!(
+            k >= 2 && k <= nobservations,
+            "Number of folds has to be between 2 and {}",
+            nobservations
+        );
+
+        // shuffle once, afterwards each validation fold is a contiguous block and only the
+        // training set has to be assembled
+        let mut indices = (0..nobservations).collect::<Vec<_>>();
+        indices.shuffle(rng);
+
+        let records = self.records().select(Axis(0), &indices);
+        let targets = indices
+            .iter()
+            .map(|i| self.targets().as_slice()[*i].clone())
+            .collect::<Array1<_>>();
+
+        let mut scores = Array1::zeros(k);
+        for (fold, score) in scores.iter_mut().enumerate() {
+            let (start, end) = (fold * nobservations / k, (fold + 1) * nobservations / k);
+
+            let train_records = ndarray::stack(
+                Axis(0),
+                &[records.slice(s![..start, ..]), records.slice(s![end.., ..])],
+            )
+            .unwrap();
+            let train_targets = targets
+                .slice(s![..start])
+                .iter()
+                .chain(targets.slice(s![end..]).iter())
+                .cloned()
+                .collect::<Array1<_>>();
+
+            let train = Dataset::new(train_records.view(), train_targets.view());
+            let model = params.fit(&train)?;
+
+            let predicted = model.predict(records.slice(s![start..end, ..]));
+            let correct = predicted
+                .iter()
+                .zip(targets.slice(s![start..end]).iter())
+                .filter(|(a, b)| a == b)
+                .count();
+
+            *score = F::from(correct).unwrap() / F::from(end - start).unwrap();
+        }
+
+        Ok(scores)
+    }
+
+    /// Split the dataset into a training and a test set with the same class proportions
+    ///
+    /// The observations of every class are shuffled with a random number generator seeded by
+    /// `seed` and the fraction `ratio` of them is put into the training set. Each class keeps at
+    /// least one observation on both sides, the number of observations per class in the
+    /// training set therefore differs by at most one from the exact proportion. Weights are
+    /// split along with the observations.
+    ///
+    /// Returns an error if `ratio` is not in the open interval (0, 1) or a class has fewer than
+    /// two observations.
+    #[allow(clippy::type_complexity)]
+    pub fn stratified_split(
+        &self,
+        ratio: f32,
+        seed: u64,
+    ) -> Result<(Dataset<Array2<F>, Vec<L>>, Dataset<Array2<F>, Vec<L>>), Error> {
+        if !(ratio > 0.0 && ratio < 1.0) {
+            return Err(Error::Parameters(format!(
+                "Split ratio has to be between 0 and 1, got {}",
+                ratio
+            )));
+        }
+
+        let classes = self.class_indices();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (mut first, mut second) = (Vec::new(), Vec::new());
+        for mut indices in classes {
+            let n = indices.len();
+            if n < 2 {
+                return Err(Error::Parameters(format!(
+                    "Observation {} is the only one of its class, each class needs at least two",
+                    indices[0]
+                )));
+            }
+
+            let ntrain = ((n as f32 * ratio).round() as usize).max(1).min(n - 1);
+            indices.shuffle(&mut rng);
+            first.extend_from_slice(&indices[..ntrain]);
+            second.extend_from_slice(&indices[ntrain..]);
+        }
+
+        // mix the classes in both sets
+        first.shuffle(&mut rng);
+        second.shuffle(&mut rng);
+
+        Ok((
+            self.select_observations(&first),
+            self.select_observations(&second),
+        ))
+    }
+
+    /// Resample the observations such that all classes are equally frequent
+    ///
+    /// With [Resample::OverSample](enum.Resample.html) the observations of every class are
+    /// drawn with replacement until the class is as frequent as the most frequent one, all
+    /// original observations are kept. With [Resample::UnderSample](enum.Resample.html) every
+    /// class is reduced to a random subset of the size of the least frequent one. The random
+    /// number generator is seeded by `seed`, weights are copied along with the observations and
+    /// the classes are mixed in the returned dataset.
+    pub fn resample_balanced(&self, strategy: Resample, seed: u64) -> Dataset<Array2<F>, Vec<L>> {
+        let classes = self.class_indices();
+        let sizes = classes.iter().map(|indices| indices.len());
+        let target = match strategy {
+            Resample::OverSample => sizes.max(),
+            Resample::UnderSample => sizes.min(),
+        }
+        .unwrap_or(0);
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut selected = Vec::with_capacity(target * classes.len());
+        for mut indices in classes {
+            let n = indices.len();
+            match strategy {
+                Resample::OverSample => {
+                    selected.extend_from_slice(&indices);
+                    selected.extend((n..target).map(|_| indices[rng.gen_range(0, n)]));
+                }
+                Resample::UnderSample => {
+                    indices.shuffle(&mut rng);
+                    selected.extend_from_slice(&indices[..target]);
+                }
+            }
+        }
+        selected.shuffle(&mut rng);
+
+        self.select_observations(&selected)
+    }
+
+    /// Indices of the observations of each class, with the classes in order of first occurrence
+    ///
+    /// The order keeps sampling reproducible regardless of the iteration order of hash maps.
+    fn class_indices(&self) -> Vec<Vec<usize>> {
+        let mut classes: Vec<(&L, Vec<usize>)> = Vec::new();
+        for (idx, target) in self.targets().as_slice().iter().enumerate() {
+            match classes.iter_mut().find(|(class, _)| *class == target) {
+                Some((_, indices)) => indices.push(idx),
+                None => classes.push((target, vec![idx])),
+            }
+        }
+
+        classes.into_iter().map(|(_, indices)| indices).collect()
+    }
+}
+
 impl<L: Label, R: Records, S: Labels<Elem = L>> Dataset<R, S> {
     pub fn labels(&self) -> Vec<L> {
         self.targets.labels()
@@ -257,3 +480,136 @@ impl<F: Float, T: Targets, D: Data<Elem = F>, I: Dimension> From<(ArrayBase<D, I
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_stratified_split() {
+        let targets = (0..50)
+            .map(|x| match x {
+                0..=29 => 0,
+                30..=44 => 1,
+                _ => 2,
+            })
+            .collect::<Vec<usize>>();
+        let records = Array2::from_shape_fn((50, 2), |(i, j)| (i * 2 + j) as f64);
+        let dataset = Dataset::new(records, targets);
+
+        let (train, test) = dataset.stratified_split(0.7, 42).unwrap();
+        assert_eq!(train.observations() + test.observations(), 50);
+
+        let count =
+            |targets: &[usize], class: usize| targets.iter().filter(|x| **x == class).count();
+        for (class, n) in &[(0, 30), (1, 15), (2, 5)] {
+            let ntrain = count(train.targets(), *class) as f32;
+            let ntest = count(test.targets(), *class) as f32;
+
+            assert_eq!(ntrain + ntest, *n as f32);
+            assert!((ntrain - 0.7 * *n as f32).abs() <= 1.0);
+            assert!((ntest - 0.3 * *n as f32).abs() <= 1.0);
+        }
+
+        // records stay attached to their targets
+        for (record, target) in train.records().outer_iter().zip(train.targets().iter()) {
+            assert_eq!(dataset.targets()[record[0] as usize / 2], *target);
+        }
+
+        // the split is reproducible
+        let (train2, _) = dataset.stratified_split(0.7, 42).unwrap();
+        assert_eq!(train.targets(), train2.targets());
+        assert_eq!(train.records(), train2.records());
+    }
+
+    #[test]
+    fn test_shuffled() {
+        let records = Array2::from_shape_fn((20, 2), |(i, j)| (i * 2 + j) as f64);
+        let targets = (0..20).collect::<Vec<usize>>();
+        let weights = (0..20).map(|x| x as f32).collect::<Vec<_>>();
+        let dataset = Dataset::new(records, targets).with_weights(weights);
+
+        let shuffled = dataset.shuffled(42);
+        assert_ne!(shuffled.targets(), dataset.targets());
+
+        // records and weights stay attached to their targets
+        for (i, record) in shuffled.records().outer_iter().enumerate() {
+            let target = shuffled.targets()[i];
+            assert_eq!(record[0] as usize, target * 2);
+            assert_eq!(shuffled.weights().unwrap()[i], target as f32);
+        }
+
+        // the same seed gives the same permutation, also for borrowed records
+        let view = Dataset::new(dataset.records().view(), dataset.targets().as_slice());
+        let shuffled2 = view.shuffled(42);
+        assert_eq!(shuffled.targets(), shuffled2.targets());
+        assert_eq!(shuffled.records(), shuffled2.records());
+        assert_ne!(dataset.shuffled(43).targets(), shuffled.targets());
+    }
+
+    #[test]
+    fn test_resample_balanced() {
+        let targets = (0..30).map(|x| x < 24).collect::<Vec<_>>();
+        let records = Array2::from_shape_fn((30, 2), |(i, j)| (i * 2 + j) as f64);
+        let weights = (0..30).map(|x| x as f32).collect::<Vec<_>>();
+        let dataset = Dataset::new(records, targets).with_weights(weights);
+
+        let count = |targets: &[bool], class: bool| targets.iter().filter(|x| **x == class).count();
+
+        let over = dataset.resample_balanced(Resample::OverSample, 42);
+        assert_eq!(over.observations(), 48);
+        assert_eq!(count(over.targets(), true), 24);
+        assert_eq!(count(over.targets(), false), 24);
+
+        let under = dataset.resample_balanced(Resample::UnderSample, 42);
+        assert_eq!(under.observations(), 12);
+        assert_eq!(count(under.targets(), true), 6);
+        assert_eq!(count(under.targets(), false), 6);
+
+        // records and weights stay attached to their targets
+        for resampled in &[&over, &under] {
+            for (i, record) in resampled.records().outer_iter().enumerate() {
+                let idx = record[0] as usize / 2;
+                assert_eq!(resampled.targets()[i], dataset.targets()[idx]);
+                assert_eq!(resampled.weights().unwrap()[i], idx as f32);
+            }
+        }
+
+        // every minority observation is kept and the result is reproducible
+        let minority = (24..30).all(|i| {
+            over.records()
+                .outer_iter()
+                .any(|record| record[0] as usize == i * 2)
+        });
+        assert!(minority);
+        let over2 = dataset.resample_balanced(Resample::OverSample, 42);
+        assert_eq!(over.records(), over2.records());
+    }
+
+    #[test]
+    fn test_from_arrays() {
+        let dataset = Dataset::from_arrays(array![[0., 1.], [2., 3.], [4., 5.]], array![0, 1, 1]);
+        assert_eq!(dataset.observations(), 3);
+
+        let (records, targets) = dataset.records_targets();
+        assert_eq!(records, array![[0., 1.], [2., 3.], [4., 5.]]);
+        assert_eq!(targets, &[0, 1, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_arrays_mismatch() {
+        Dataset::from_arrays(array![[0.], [1.]], array![0usize]);
+    }
+
+    #[test]
+    fn test_stratified_split_errors() {
+        let dataset = Dataset::new(array![[0.], [1.], [2.]], vec![0usize, 0, 1]);
+
+        assert!(dataset.stratified_split(0.5, 42).is_err());
+        assert!(Dataset::new(array![[0.], [1.]], vec![0usize, 0])
+            .stratified_split(1.0, 42)
+            .is_err());
+    }
+}
@@ -26,6 +26,7 @@ pub trait Label: PartialEq + Eq + Hash + Clone {}
 impl Label for bool {}
 impl Label for usize {}
 impl Label for String {}
+impl Label for &str {}
 
 /// Probability types
 ///
@@ -55,6 +56,15 @@ impl Deref for Pr {
     }
 }
 
+/// Strategy of [Dataset::resample_balanced](struct.Dataset.html#method.resample_balanced)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Resample {
+    /// Draw observations of the smaller classes with replacement
+    OverSample,
+    /// Drop random observations of the larger classes
+    UnderSample,
+}
+
 /// Dataset
 ///
 /// A dataset contains a number of records and targets. Each record corresponds to a single target
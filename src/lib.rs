@@ -33,6 +33,7 @@ pub mod error;
 mod metrics_classification;
 mod metrics_regression;
 pub mod prelude;
+pub mod preprocessing;
 pub mod traits;
 
 pub use dataset::{Dataset, Float, Label};
@@ -52,7 +53,10 @@ extern crate netblas_src;
 /// Common metrics functions for classification and regression
 pub mod metrics {
     pub use crate::metrics_classification::{
-        BinaryClassification, ConfusionMatrix, ReceiverOperatingCharacteristic, ToConfusionMatrix,
+        balanced_accuracy, confusion_matrix, f1_per_class, log_loss, macro_f1, precision_per_class,
+        recall_per_class, roc_auc, roc_curve, weighted_f1, Accuracy, BinaryClassification,
+        Classifier, ConfusionMatrix, LogLoss, Metric, ReceiverOperatingCharacteristic,
+        ToConfusionMatrix, F1,
     };
     pub use crate::metrics_regression::Regression;
 }
@@ -3,6 +3,7 @@
 //! Scoring is essential for classification and regression tasks. This module implements
 //! common scoring functions like precision, accuracy, recall, f1-score, ROC and ROC
 //! Aread-Under-Curve.
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
@@ -10,7 +11,7 @@ use std::fmt;
 use ndarray::prelude::*;
 use ndarray::Data;
 
-use crate::dataset::{Dataset, Label, Labels, Pr, Records, Targets};
+use crate::dataset::{Dataset, Float, Label, Labels, Pr, Records, Targets};
 
 /// Return tuple of class index for each element of prediction and ground_truth
 fn map_prediction_to_idx<L: Label>(
@@ -230,6 +231,148 @@ impl<A> ConfusionMatrix<A> {
     }
 }
 
+/// Count matrix for integer encoded classes
+///
+/// Returns an `n_classes x n_classes` matrix, where the rows correspond to the true classes and
+/// the columns to the predicted classes. Entry `(i, j)` counts the samples of class `i`, which
+/// were predicted as class `j`.
+///
+/// __Panics__ if `y_true` and `y_pred` have different lengths or contain a class larger than or
+/// equal to `n_classes`
+pub fn confusion_matrix(
+    y_true: &[usize],
+    y_pred: ArrayView1<usize>,
+    n_classes: usize,
+) -> Array2<usize> {
+    assert_eq!(
+        y_true.len(),
+        y_pred.len(),
+        "true and predicted classes have to be of equal length"
+    );
+
+    let mut matrix = Array2::zeros((n_classes, n_classes));
+    for (t, p) in y_true.iter().zip(y_pred.iter()) {
+        assert!(
+            *t < n_classes && *p < n_classes,
+            "classes have to be smaller than {}",
+            n_classes
+        );
+
+        matrix[(*t, *p)] += 1;
+    }
+
+    matrix
+}
+
+/// Precision of each class from a count matrix with true classes in rows
+///
+/// This is the fraction of correct predictions among all predictions of a class, classes which
+/// were never predicted have a precision of zero.
+pub fn precision_per_class(matrix: &Array2<usize>) -> Array1<f32> {
+    let predicted = matrix.sum_axis(Axis(0));
+
+    Array1::from_shape_fn(matrix.nrows(), |i| ratio(matrix[(i, i)], predicted[i]))
+}
+
+/// Recall of each class from a count matrix with true classes in rows
+///
+/// This is the fraction of samples of a class which were predicted correctly, classes without
+/// samples have a recall of zero.
+pub fn recall_per_class(matrix: &Array2<usize>) -> Array1<f32> {
+    let actual = matrix.sum_axis(Axis(1));
+
+    Array1::from_shape_fn(matrix.nrows(), |i| ratio(matrix[(i, i)], actual[i]))
+}
+
+/// F1-score of each class from a count matrix with true classes in rows
+///
+/// The harmonic mean of precision and recall, zero if both are zero.
+pub fn f1_per_class(matrix: &Array2<usize>) -> Array1<f32> {
+    let precision = precision_per_class(matrix);
+    let recall = recall_per_class(matrix);
+
+    precision
+        .iter()
+        .zip(recall.iter())
+        .map(|(p, r)| {
+            if p + r > 0.0 {
+                2.0 * p * r / (p + r)
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Unweighted mean of the F1-scores of all classes from a count matrix with true classes in rows
+///
+/// Every class contributes equally, regardless of its number of samples. Classes which neither
+/// occur nor were predicted are left out.
+pub fn macro_f1(matrix: &Array2<usize>) -> f32 {
+    let (actual, predicted) = (matrix.sum_axis(Axis(1)), matrix.sum_axis(Axis(0)));
+    let f1 = f1_per_class(matrix);
+
+    let (sum, count) = f1
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| actual[*i] + predicted[*i] > 0)
+        .fold((0.0, 0), |(sum, count), (_, f1)| (sum + f1, count + 1));
+
+    ratio_f32(sum, count)
+}
+
+/// Mean of the F1-scores of all classes from a count matrix, weighted by the number of samples
+///
+/// This accounts for class imbalance, but can hide a poor score of a rare class.
+pub fn weighted_f1(matrix: &Array2<usize>) -> f32 {
+    let actual = matrix.sum_axis(Axis(1));
+    let f1 = f1_per_class(matrix);
+
+    let sum = f1
+        .iter()
+        .zip(actual.iter())
+        .map(|(f1, n)| f1 * *n as f32)
+        .sum::<f32>();
+
+    ratio_f32(sum, actual.sum())
+}
+
+/// Unweighted mean of the recall of all classes from a count matrix with true classes in rows
+///
+/// For balanced datasets this equals the accuracy, for imbalanced datasets a classifier which
+/// always predicts the majority class only scores `1 / n_classes`. Classes without samples are
+/// left out.
+pub fn balanced_accuracy(matrix: &Array2<usize>) -> f32 {
+    let actual = matrix.sum_axis(Axis(1));
+    let recall = recall_per_class(matrix);
+
+    let (sum, count) = recall
+        .iter()
+        .zip(actual.iter())
+        .filter(|(_, n)| **n > 0)
+        .fold((0.0, 0), |(sum, count), (recall, _)| {
+            (sum + recall, count + 1)
+        });
+
+    ratio_f32(sum, count)
+}
+
+fn ratio_f32(sum: f32, total: usize) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        sum / total as f32
+    }
+}
+
+fn ratio(count: usize, total: usize) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f32 / total as f32
+    }
+}
+
 /// Print a confusion matrix
 impl<A: fmt::Display> fmt::Debug for ConfusionMatrix<A> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -446,6 +589,236 @@ impl ReceiverOperatingCharacteristic {
     }
 }
 
+/// Compute the ROC curve of arbitrary scores, where larger scores indicate the positive class
+///
+/// The scores can be the decision values of a classifier or the probabilities of the positive
+/// class. Returns the false-positive rate, the true-positive rate and the threshold of each point
+/// of the curve. A sample is predicted positive at a threshold if its score is at least the
+/// threshold. Tied scores are grouped into a single point, the thresholds are therefore the
+/// distinct scores in decreasing order, preceded by infinity for the origin of the curve.
+///
+/// __Panics__ if the number of scores and targets differ, or if `y_true` contains no positive or
+/// no negative samples, because the rates are undefined then
+pub fn roc_curve<A: Float>(
+    scores: ArrayView1<A>,
+    y_true: &[bool],
+) -> (Array1<A>, Array1<A>, Array1<A>) {
+    assert_eq!(
+        scores.len(),
+        y_true.len(),
+        "number of scores and targets have to be equal"
+    );
+    assert!(
+        y_true.iter().any(|x| *x) && y_true.iter().any(|x| !*x),
+        "targets have to contain positive and negative samples"
+    );
+
+    let mut order = (0..scores.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| {
+        scores[*b]
+            .partial_cmp(&scores[*a])
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let (mut tp, mut fp) = (0, 0);
+    let (mut tps, mut fps, mut thresholds) = (vec![0], vec![0], vec![A::infinity()]);
+    for (k, idx) in order.iter().enumerate() {
+        if y_true[*idx] {
+            tp += 1;
+        } else {
+            fp += 1;
+        }
+
+        // a group of tied scores is closed by its last member
+        let closed = match order.get(k + 1) {
+            Some(next) => scores[*next] != scores[*idx],
+            None => true,
+        };
+        if closed {
+            tps.push(tp);
+            fps.push(fp);
+            thresholds.push(scores[*idx]);
+        }
+    }
+
+    let rate = |counts: Vec<usize>, total: usize| {
+        counts
+            .into_iter()
+            .map(|x| A::from(x).unwrap() / A::from(total).unwrap())
+            .collect::<Array1<_>>()
+    };
+
+    (rate(fps, fp), rate(tps, tp), Array1::from(thresholds))
+}
+
+/// Compute the area under the ROC curve of arbitrary scores with the trapezoidal rule
+///
+/// A value of one means that the scores separate both classes perfectly, a value of one half
+/// corresponds to random scores. See [roc_curve](fn.roc_curve.html) for the handling of ties.
+///
+/// __Panics__ if the number of scores and targets differ, or if `y_true` contains no positive or
+/// no negative samples
+pub fn roc_auc<A: Float>(scores: ArrayView1<A>, y_true: &[bool]) -> A {
+    let (fpr, tpr, _) = roc_curve(scores, y_true);
+    let curve = fpr
+        .iter()
+        .cloned()
+        .zip(tpr.iter().cloned())
+        .collect::<Vec<_>>();
+
+    trapezoidal(&curve)
+}
+
+/// Compute the mean negative log-likelihood of the true labels under predicted probabilities
+///
+/// Row `i` of `proba` contains the probability of each class for sample `i`, with the columns
+/// ordered like `classes`, for example the output of `predict_proba`. The probabilities are
+/// clipped to `[eps, 1 - eps]` with the machine epsilon `eps` of `A`, such that a confident but
+/// wrong prediction gives a large, but finite loss. Lower values are better.
+///
+/// __Panics__ if the number of rows and targets differ, if the number of columns and classes
+/// differ, or if a target is not contained in `classes`
+pub fn log_loss<A: Float>(proba: ArrayView2<A>, y_true: &[usize], classes: &[usize]) -> A {
+    assert_eq!(
+        proba.nrows(),
+        y_true.len(),
+        "number of samples and targets have to be equal"
+    );
+    assert_eq!(
+        proba.ncols(),
+        classes.len(),
+        "number of columns and classes have to be equal"
+    );
+
+    let eps = A::epsilon();
+    let loss = proba
+        .outer_iter()
+        .zip(y_true.iter())
+        .map(|(row, target)| {
+            let idx = classes
+                .iter()
+                .position(|x| x == target)
+                .unwrap_or_else(|| panic!("Target {} is not a known class", target));
+
+            -row[idx].max(eps).min(A::one() - eps).ln()
+        })
+        .sum::<A>();
+
+    loss / A::from(y_true.len()).unwrap()
+}
+
+/// Fitted classifier, which can be evaluated by a [Metric](trait.Metric.html)
+pub trait Classifier<A: Float, L: Label> {
+    /// Predict the label of each sample
+    fn predict_labels(&self, x: ArrayView2<A>) -> Vec<L>;
+
+    /// Predict the probability of each class
+    ///
+    /// Returns a matrix of shape `n_samples x n_classes` and the classes of its columns.
+    fn predict_class_proba(&self, x: ArrayView2<A>) -> (Array2<A>, Vec<L>);
+
+    /// Score the predictions for `x` against the true labels `y`
+    fn score_with<M: Metric<A, L>>(&self, x: ArrayView2<A>, y: &[L], metric: M) -> A {
+        metric.score(self, x, y)
+    }
+}
+
+/// Scoring function of a fitted classifier
+///
+/// A metric only depends on the predictions of a model, such that the same implementation
+/// evaluates every [Classifier](trait.Classifier.html).
+pub trait Metric<A: Float, L: Label> {
+    /// Score the predictions of `model` for `x` against the true labels `y`
+    fn score<C: Classifier<A, L> + ?Sized>(&self, model: &C, x: ArrayView2<A>, y: &[L]) -> A;
+}
+
+/// Fraction of correctly predicted labels, higher values are better
+///
+/// __Panics__ if the number of samples and targets differ
+#[derive(Debug, Clone, Copy)]
+pub struct Accuracy;
+
+impl<A: Float, L: Label> Metric<A, L> for Accuracy {
+    fn score<C: Classifier<A, L> + ?Sized>(&self, model: &C, x: ArrayView2<A>, y: &[L]) -> A {
+        assert_eq!(
+            x.nrows(),
+            y.len(),
+            "number of samples and targets have to be equal"
+        );
+
+        let predicted = model.predict_labels(x);
+        let correct = predicted
+            .iter()
+            .zip(y.iter())
+            .filter(|(a, b)| a == b)
+            .count();
+
+        A::from(correct).unwrap() / A::from(y.len()).unwrap()
+    }
+}
+
+/// Unweighted mean of the F1-scores of all classes, higher values are better
+///
+/// This is [macro_f1](fn.macro_f1.html) of the classes occurring in the true or predicted labels.
+/// For binary problems both classes contribute.
+///
+/// __Panics__ if the number of samples and targets differ
+#[derive(Debug, Clone, Copy)]
+pub struct F1;
+
+impl<A: Float, L: Label> Metric<A, L> for F1 {
+    fn score<C: Classifier<A, L> + ?Sized>(&self, model: &C, x: ArrayView2<A>, y: &[L]) -> A {
+        assert_eq!(
+            x.nrows(),
+            y.len(),
+            "number of samples and targets have to be equal"
+        );
+
+        let predicted = model.predict_labels(x);
+
+        // encode the labels as indices in order of their first occurrence
+        let mut indices = HashMap::new();
+        let mut encode = |label: &L| {
+            let next = indices.len();
+            *indices.entry(label.clone()).or_insert(next)
+        };
+        let y_true = y.iter().map(&mut encode).collect::<Vec<_>>();
+        let y_pred = predicted.iter().map(&mut encode).collect::<Array1<_>>();
+
+        let matrix = confusion_matrix(&y_true, y_pred.view(), indices.len());
+
+        A::from(macro_f1(&matrix)).unwrap()
+    }
+}
+
+/// Mean negative log-likelihood of the true labels, lower values are better
+///
+/// This is [log_loss](fn.log_loss.html) of the predicted class probabilities.
+///
+/// __Panics__ if the number of samples and targets differ, or if a target is not one of the
+/// predicted classes
+#[derive(Debug, Clone, Copy)]
+pub struct LogLoss;
+
+impl<A: Float, L: Label> Metric<A, L> for LogLoss {
+    fn score<C: Classifier<A, L> + ?Sized>(&self, model: &C, x: ArrayView2<A>, y: &[L]) -> A {
+        let (proba, classes) = model.predict_class_proba(x);
+
+        let y_true = y
+            .iter()
+            .map(|target| {
+                classes
+                    .iter()
+                    .position(|x| x == target)
+                    .expect("Target is not a known class")
+            })
+            .collect::<Vec<_>>();
+        let indices = (0..classes.len()).collect::<Vec<_>>();
+
+        log_loss(proba.view(), &y_true, &indices)
+    }
+}
+
 /// Classification for binary-labels
 ///
 /// This contains Receiver-Operating-Characterstics curves as these only work for binary
@@ -516,10 +889,14 @@ impl<R: Records, R2: Records, T: Targets<Elem = bool>, T2: Targets<Elem = Pr>>
 
 #[cfg(test)]
 mod tests {
-    use super::{BinaryClassification, ToConfusionMatrix};
+    use super::{
+        balanced_accuracy, confusion_matrix, f1_per_class, log_loss, macro_f1, precision_per_class,
+        recall_per_class, roc_auc, roc_curve, weighted_f1, Accuracy, BinaryClassification,
+        Classifier, LogLoss, ToConfusionMatrix, F1,
+    };
     use super::{Dataset, Pr};
     use approx::{abs_diff_eq, AbsDiffEq};
-    use ndarray::{array, Array1, ArrayBase, ArrayView1, Data, Dimension};
+    use ndarray::{array, s, Array1, Array2, ArrayBase, ArrayView1, ArrayView2, Data, Dimension};
     use rand::{distributions::Uniform, Rng, SeedableRng};
     use rand_isaac::Isaac64Rng;
     use std::borrow::Borrow;
@@ -565,6 +942,63 @@ mod tests {
         assert_eq_slice(cm.matrix, &[2., 1., 0., 3.]);
     }
 
+    #[test]
+    fn test_count_confusion_matrix() {
+        let y_true = [0, 0, 0, 1, 1, 2, 2, 2, 2];
+        let y_pred = array![0, 1, 1, 1, 2, 2, 0, 2, 2];
+
+        let cm = confusion_matrix(&y_true, y_pred.view(), 3);
+        assert_eq!(cm, array![[1, 2, 0], [0, 1, 1], [1, 0, 3]]);
+
+        let close = |a: Array1<f32>, b: &[f32]| {
+            assert_eq!(a.len(), b.len());
+            for (a, b) in a.iter().zip(b.iter()) {
+                assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+            }
+        };
+        close(precision_per_class(&cm), &[1. / 2., 1. / 3., 3. / 4.]);
+        close(recall_per_class(&cm), &[1. / 3., 1. / 2., 3. / 4.]);
+        close(f1_per_class(&cm), &[2. / 5., 2. / 5., 3. / 4.]);
+
+        // a class without samples and predictions has zero scores
+        let cm = confusion_matrix(&y_true, y_pred.view(), 4);
+        close(f1_per_class(&cm), &[2. / 5., 2. / 5., 3. / 4., 0.]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_count_confusion_matrix_length() {
+        confusion_matrix(&[0, 1], array![0, 1, 1].view(), 2);
+    }
+
+    #[test]
+    fn test_averaged_metrics() {
+        // balanced fixture with three samples per class
+        let y_true = [0, 0, 0, 1, 1, 1, 2, 2, 2];
+        let y_pred = array![0, 0, 1, 1, 1, 2, 2, 2, 0];
+        let cm = confusion_matrix(&y_true, y_pred.view(), 3);
+
+        assert!((macro_f1(&cm) - 2. / 3.).abs() < 1e-6);
+        assert!((macro_f1(&cm) - weighted_f1(&cm)).abs() < 1e-6);
+        assert!((balanced_accuracy(&cm) - 2. / 3.).abs() < 1e-6);
+
+        // imbalanced fixture, a classifier predicting the majority class only
+        let y_true = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1];
+        let y_pred = array![0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let cm = confusion_matrix(&y_true, y_pred.view(), 2);
+
+        let f1_majority = 2. * 0.8 / 1.8;
+        assert!((macro_f1(&cm) - f1_majority / 2.).abs() < 1e-6);
+        assert!((weighted_f1(&cm) - 0.8 * f1_majority).abs() < 1e-6);
+        assert!(weighted_f1(&cm) > macro_f1(&cm));
+        assert!((balanced_accuracy(&cm) - 0.5).abs() < 1e-6);
+
+        // a class which neither occurs nor is predicted does not change the scores
+        let cm = confusion_matrix(&y_true, y_pred.view(), 3);
+        assert!((macro_f1(&cm) - f1_majority / 2.).abs() < 1e-6);
+        assert!((balanced_accuracy(&cm) - 0.5).abs() < 1e-6);
+    }
+
     #[test]
     fn test_cm_metrices() {
         let predicted = Array1::from(vec![0, 1, 0, 1, 0, 1]);
@@ -652,6 +1086,118 @@ mod tests {
         assert!((roc.area_under_curve() - 0.5) < 0.04);
     }
 
+    #[test]
+    fn test_roc_scores() {
+        // decision values of a classifier, which separates both classes perfectly
+        let scores = array![-2.5, -0.3, 0.4, 1.2, -1.0];
+        let y_true = [false, false, true, true, false];
+        assert_eq!(roc_auc(scores.view(), &y_true), 1.0);
+
+        let (fpr, tpr, thresholds) = roc_curve(scores.view(), &y_true);
+        assert_eq!(tpr, array![0., 0.5, 1., 1., 1., 1.]);
+        assert_eq!(fpr, array![0., 0., 0., 1. / 3., 2. / 3., 1.]);
+        assert_eq!(thresholds[0], f64::INFINITY);
+        assert_eq!(
+            thresholds.slice(s![1..]),
+            array![1.2, 0.4, -0.3, -1.0, -2.5]
+        );
+
+        // tied scores form a single point of the curve
+        let scores = array![0.5f32, 0.5, 0.5, 0.9];
+        let y_true = [true, false, false, true];
+        let (fpr, tpr, thresholds) = roc_curve(scores.view(), &y_true);
+        assert_eq!(thresholds.len(), 3);
+        assert_eq!(fpr, array![0., 0., 1.]);
+        assert_eq!(tpr, array![0., 0.5, 1.]);
+        assert_eq!(roc_auc(scores.view(), &y_true), 0.75);
+
+        // inverted scores have an area of zero
+        let scores = array![1., 2., 3., 4.];
+        assert_eq!(roc_auc(scores.view(), &[true, true, false, false]), 0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_roc_single_class() {
+        let scores = array![0.1, 0.4, 0.8];
+        roc_auc(scores.view(), &[true, true, true]);
+    }
+
+    #[test]
+    fn test_log_loss() {
+        let classes = [3, 5, 7];
+        let y_true = [5, 3, 7, 5];
+
+        // confident and correct predictions have a loss close to zero
+        let correct = array![[0., 1., 0.], [1., 0., 0.], [0., 0., 1.], [0., 1., 0.]];
+        assert!(log_loss(correct.view(), &y_true, &classes) < 1e-12);
+
+        // confident but wrong predictions are clipped to a large, finite loss
+        let wrong = array![[1f64, 0., 0.], [0., 1., 0.], [1., 0., 0.], [0., 0., 1.]];
+        let loss = log_loss(wrong.view(), &y_true, &classes);
+        assert!(loss.is_finite() && loss > 30.);
+
+        // uniform probabilities give `ln(n_classes)`
+        let uniform = Array2::from_elem((4, 3), 1. / 3.);
+        let loss: f64 = log_loss(uniform.view(), &y_true, &classes);
+        assert!((loss - 3f64.ln()).abs() < 1e-12);
+
+        let proba = array![[0.2f32, 0.8], [0.6, 0.4]];
+        let loss = log_loss(proba.view(), &[1, 0], &[0, 1]);
+        assert!((loss - -(0.8f32.ln() + 0.6f32.ln()) / 2.).abs() < 1e-6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_log_loss_unknown_class() {
+        log_loss(array![[0.5, 0.5]].view(), &[2], &[0, 1]);
+    }
+
+    /// Classifier returning fixed predictions, regardless of the records
+    struct Fixed {
+        labels: Vec<usize>,
+        proba: Array2<f64>,
+    }
+
+    impl Classifier<f64, usize> for Fixed {
+        fn predict_labels(&self, _: ArrayView2<f64>) -> Vec<usize> {
+            self.labels.clone()
+        }
+
+        fn predict_class_proba(&self, _: ArrayView2<f64>) -> (Array2<f64>, Vec<usize>) {
+            (self.proba.clone(), vec![3, 5, 7])
+        }
+    }
+
+    #[test]
+    fn test_metric() {
+        let model = Fixed {
+            labels: vec![5, 3, 5, 7, 3],
+            proba: array![
+                [0.2, 0.7, 0.1],
+                [0.6, 0.3, 0.1],
+                [0.1, 0.5, 0.4],
+                [0.2, 0.2, 0.6],
+                [0.5, 0.1, 0.4]
+            ],
+        };
+        let x = Array2::zeros((5, 2));
+        let y = [5, 3, 7, 7, 5];
+
+        let accuracy = model.score_with(x.view(), &y, Accuracy);
+        let cm = model.labels.as_slice().confusion_matrix(&y[..]);
+        assert!((accuracy - 0.6).abs() < 1e-12);
+        assert!((accuracy - cm.accuracy() as f64).abs() < 1e-6);
+
+        // the classes 3, 5, 7 are encoded as 1, 0, 2 in order of occurrence
+        let matrix = confusion_matrix(&[0, 1, 2, 2, 0], array![0, 1, 0, 2, 1].view(), 3);
+        let f1 = model.score_with(x.view(), &y, F1);
+        assert!((f1 - macro_f1(&matrix) as f64).abs() < 1e-6);
+
+        let loss = model.score_with(x.view(), &y, LogLoss);
+        assert!((loss - log_loss(model.proba.view(), &y, &[3, 5, 7])).abs() < 1e-12);
+    }
+
     #[test]
     fn split_one_vs_all() {
         let predicted = array![0, 3, 2, 0, 1, 1, 1, 3, 2, 3];
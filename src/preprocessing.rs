@@ -0,0 +1,253 @@
+//! Preprocessing of records before fitting a model
+//!
+use ndarray::{Array1, Array2, ArrayBase, ArrayView1, ArrayView2, Axis, Data, Ix2};
+use std::cmp::Ordering;
+
+use crate::dataset::{Dataset, Float, Targets};
+use crate::traits::Transformer;
+
+/// Standardize features by removing the mean and scaling to unit variance
+///
+/// The mean and standard deviation of each feature are computed when the scaler is fitted and
+/// reused in every transformation, such that test data is scaled in the same way as the training
+/// data. Features with zero variance are only centered, their scale is left untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StandardScaler<F> {
+    mean: Array1<F>,
+    std: Array1<F>,
+}
+
+impl<F: Float> StandardScaler<F> {
+    /// Compute the mean and standard deviation of each column of `records`
+    ///
+    /// __Panics__ if `records` has no rows
+    pub fn fit<D: Data<Elem = F>>(records: &ArrayBase<D, Ix2>) -> StandardScaler<F> {
+        let mean = records
+            .mean_axis(Axis(0))
+            .expect("Cannot fit a scaler without observations");
+        let std = records.var_axis(Axis(0), F::zero()).mapv(|x| x.sqrt());
+
+        StandardScaler { mean, std }
+    }
+
+    /// Fit the scaler on `records` and return it together with the transformed records
+    pub fn fit_transform<D: Data<Elem = F>>(
+        records: &ArrayBase<D, Ix2>,
+    ) -> (StandardScaler<F>, Array2<F>) {
+        let scaler = Self::fit(records);
+        let transformed = scaler.transform(records.view());
+
+        (scaler, transformed)
+    }
+
+    /// Mean of each feature
+    pub fn mean(&self) -> ArrayView1<'_, F> {
+        self.mean.view()
+    }
+
+    /// Standard deviation of each feature
+    pub fn std(&self) -> ArrayView1<'_, F> {
+        self.std.view()
+    }
+
+    /// Scaling factor of each feature, which is one for constant features
+    fn scale(&self) -> Array1<F> {
+        self.std.mapv(|x| if x > F::zero() { x } else { F::one() })
+    }
+
+    /// Center and scale `records` with the fitted statistics
+    ///
+    /// __Panics__ if the number of features differs from the fitted records
+    pub fn transform(&self, records: ArrayView2<F>) -> Array2<F> {
+        assert_eq!(
+            records.ncols(),
+            self.mean.len(),
+            "Expected {} features",
+            self.mean.len()
+        );
+
+        (&records - &self.mean) / &self.scale()
+    }
+
+    /// Undo the transformation and return records in the original scale
+    ///
+    /// __Panics__ if the number of features differs from the fitted records
+    pub fn inverse_transform(&self, records: ArrayView2<F>) -> Array2<F> {
+        assert_eq!(
+            records.ncols(),
+            self.mean.len(),
+            "Expected {} features",
+            self.mean.len()
+        );
+
+        &records * &self.scale() + &self.mean
+    }
+}
+
+impl<'a, F: Float> Transformer<ArrayView2<'a, F>, Array2<F>> for StandardScaler<F> {
+    fn transform(&self, x: ArrayView2<'a, F>) -> Array2<F> {
+        StandardScaler::transform(self, x)
+    }
+}
+
+impl<F: Float, T: Targets> Transformer<Dataset<Array2<F>, T>, Dataset<Array2<F>, T>>
+    for StandardScaler<F>
+{
+    fn transform(&self, x: Dataset<Array2<F>, T>) -> Dataset<Array2<F>, T> {
+        let records = StandardScaler::transform(self, x.records.view());
+        let weights = x.weights().map(|w| w.to_vec()).unwrap_or_default();
+
+        Dataset::new(records, x.targets).with_weights(weights)
+    }
+}
+
+/// Estimate the mutual information between each feature and the class labels
+///
+/// Every feature is discretized into `n_bins` bins of equal width between its minimum and
+/// maximum, and the mutual information between the bins and the labels is computed from their
+/// joint frequencies in nats. Features, which do not carry any information about the labels, have
+/// a score of zero, constant features in particular. The scores can be passed to
+/// [select_k_best](fn.select_k_best.html) to drop irrelevant features before fitting a model.
+///
+/// __Panics__ if `n_bins` is zero, or if the number of records and labels differ
+pub fn mutual_info_classif<A: Float>(x: ArrayView2<A>, y: &[usize], n_bins: usize) -> Array1<A> {
+    assert!(n_bins > 0, "Number of bins has to be positive");
+    assert_eq!(x.nrows(), y.len(), "Expected one label for every record");
+
+    // map the labels to consecutive indices
+    let mut classes = y.to_vec();
+    classes.sort_unstable();
+    classes.dedup();
+    let labels = y
+        .iter()
+        .map(|label| classes.binary_search(label).unwrap())
+        .collect::<Vec<_>>();
+
+    let nsamples = A::from(x.nrows()).unwrap();
+    x.gencolumns()
+        .into_iter()
+        .map(|column| {
+            let min = column.fold(A::infinity(), |acc, x| acc.min(*x));
+            let max = column.fold(A::neg_infinity(), |acc, x| acc.max(*x));
+            let width = (max - min) / A::from(n_bins).unwrap();
+
+            let mut joint = Array2::<usize>::zeros((n_bins, classes.len()));
+            for (val, label) in column.iter().zip(labels.iter()) {
+                let bin = if width > A::zero() {
+                    ((*val - min) / width)
+                        .to_usize()
+                        .unwrap_or(0)
+                        .min(n_bins - 1)
+                } else {
+                    0
+                };
+                joint[(bin, *label)] += 1;
+            }
+
+            let bins = joint.sum_axis(Axis(1));
+            let counts = joint.sum_axis(Axis(0));
+            joint
+                .indexed_iter()
+                .filter(|(_, count)| **count > 0)
+                .map(|((bin, class), count)| {
+                    let count = A::from(*count).unwrap();
+                    let expected = A::from(bins[bin] * counts[class]).unwrap() / nsamples;
+
+                    count / nsamples * (count / expected).ln()
+                })
+                .fold(A::zero(), |acc, x| acc + x)
+                .max(A::zero())
+        })
+        .collect()
+}
+
+/// Select the `k` features with the highest scores
+///
+/// Returns the column indices of the selected features in ascending order, which can be used to
+/// slice the records with `select(Axis(1), ..)`. Ties are resolved in favour of the earlier
+/// feature and all features are selected if `k` exceeds their number.
+pub fn select_k_best<A: Float>(scores: ArrayView1<A>, k: usize) -> Vec<usize> {
+    let mut order = (0..scores.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| {
+        scores[*b]
+            .partial_cmp(&scores[*a])
+            .unwrap_or(Ordering::Equal)
+    });
+    order.truncate(k);
+    order.sort_unstable();
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mutual_info_classif, select_k_best, StandardScaler};
+    use crate::traits::Transformer;
+    use crate::Dataset;
+    use approx::assert_abs_diff_eq;
+    use ndarray::{array, s, Array2, ArrayBase, Axis, Data, Dimension};
+
+    fn assert_close<D: Data<Elem = f64>, I: Dimension>(a: &ArrayBase<D, I>, b: &[f64]) {
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(b.iter()) {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_standard_scaler() {
+        let records = array![[1., 10., 3.], [2., 20., 3.], [3., 60., 3.], [6., 30., 3.]];
+        let (scaler, transformed) = StandardScaler::fit_transform(&records);
+
+        assert_close(&scaler.mean(), &[3., 30., 3.]);
+        assert_close(&transformed.mean_axis(Axis(0)).unwrap(), &[0., 0., 0.]);
+        // the constant feature is centered, but not scaled
+        assert_close(&transformed.var_axis(Axis(0), 0.), &[1., 1., 0.]);
+        assert_abs_diff_eq!(scaler.std()[2], 0.);
+
+        let restored: Array2<f64> = scaler.inverse_transform(transformed.view());
+        assert_close(&restored, records.as_slice().unwrap());
+
+        // test data is scaled with the statistics of the training data
+        let test = array![[3., 30., 4.]];
+        assert_close(&scaler.transform(test.view()), &[0., 0., 1.]);
+
+        let dataset =
+            Dataset::new(records, vec![0usize, 1, 0, 1]).with_weights(vec![1., 2., 1., 2.]);
+        let dataset = Transformer::transform(&scaler, dataset);
+        assert_close(&dataset.records, transformed.as_slice().unwrap());
+        assert_eq!(dataset.weights(), Some(&[1., 2., 1., 2.][..]));
+    }
+
+    #[test]
+    fn test_mutual_info() {
+        let y = vec![0, 0, 0, 1, 1, 1, 2, 2, 2];
+        // the second feature is a function of the label, the first one is unrelated and the
+        // third one is constant
+        let x = array![
+            [0.3, 0.0, 1.],
+            [0.8, 0.1, 1.],
+            [0.1, 0.2, 1.],
+            [0.7, 1.0, 1.],
+            [0.2, 1.1, 1.],
+            [0.9, 1.2, 1.],
+            [0.4, 2.0, 1.],
+            [0.6, 2.1, 1.],
+            [0.5, 2.2, 1.]
+        ];
+
+        let scores = mutual_info_classif(x.view(), &y, 3);
+        // a perfect feature has the entropy of the labels
+        assert_abs_diff_eq!(scores[1], 3f64.ln(), epsilon = 1e-12);
+        assert!(scores[0] < scores[1]);
+        assert_abs_diff_eq!(scores[2], 0.);
+
+        assert_eq!(select_k_best(scores.view(), 1), vec![1]);
+        assert_eq!(select_k_best(scores.view(), 2), vec![0, 1]);
+        assert_eq!(select_k_best(scores.view(), 5), vec![0, 1, 2]);
+        assert_eq!(
+            x.select(Axis(1), &select_k_best(scores.view(), 1)),
+            x.slice(s![.., 1..2])
+        );
+    }
+}